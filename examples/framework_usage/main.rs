@@ -53,29 +53,26 @@ async fn on_error(error: Error, ctx: poise::ErrorContext<'_, Data, Error>) {
 
 #[tokio::main]
 async fn main() {
-    let options = poise::FrameworkOptions {
+    let mut options = poise::FrameworkOptions {
         prefix_options: poise::PrefixFrameworkOptions {
             prefix: Some("~".into()),
             edit_tracker: Some(poise::EditTracker::for_timespan(Duration::from_secs(3600))),
-            additional_prefixes: vec![
-                poise::Prefix::Literal("hey bot"),
-                poise::Prefix::Literal("hey bot,"),
-            ],
+            literal_prefixes: vec!["hey bot".into(), "hey bot,".into()],
             ..Default::default()
         },
-        on_error: |error, ctx| Box::pin(on_error(error, ctx)),
         pre_command: |ctx| {
             Box::pin(async move {
                 println!("Executing command {}...", ctx.command().unwrap().name());
             })
         },
-        post_command: |ctx| {
+        post_command: |ctx, _outcome| {
             Box::pin(async move {
                 println!("Executed command {}!", ctx.command().unwrap().name());
             })
         },
         ..Default::default()
     };
+    options.set_on_error(|error, ctx| Box::pin(on_error(error, ctx)));
 
     poise::Framework::build()
         .token(var("TOKEN").expect("Missing `TOKEN` env var, see README for more information."))
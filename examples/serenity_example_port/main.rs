@@ -150,8 +150,26 @@ async fn pre_command(ctx: Context<'_>) {
     *entry += 1;
 }
 
-async fn post_command(ctx: Context<'_>) {
-    println!("Processed command '{}'", ctx.command().unwrap().name());
+async fn post_command(ctx: Context<'_>, outcome: poise::CommandOutcome<'_, Error>) {
+    match outcome {
+        poise::CommandOutcome::Success => {
+            println!("Processed command '{}'", ctx.command().unwrap().name())
+        }
+        poise::CommandOutcome::Error(e) => println!(
+            "Command '{}' returned error {:?}",
+            ctx.command().unwrap().name(),
+            e
+        ),
+        poise::CommandOutcome::Panic => {
+            println!("Command '{}' panicked", ctx.command().unwrap().name())
+        }
+        poise::CommandOutcome::Timeout => {
+            println!("Command '{}' timed out", ctx.command().unwrap().name())
+        }
+        poise::CommandOutcome::Rejected => {
+            println!("Command '{}' was rejected", ctx.command().unwrap().name())
+        }
+    }
 }
 
 // TODO: unify the command checks in poise::FrameworkOptions and then implement a command check here
@@ -234,17 +252,16 @@ fn _dispatch_error_no_macro<'fut>(
 
 #[tokio::main]
 async fn main() {
-    let options = poise::FrameworkOptions {
+    let mut options = poise::FrameworkOptions {
         listener: |ctx, event, framework, user_data| {
             Box::pin(event_listener(ctx, event, framework, user_data))
         },
-        on_error: |error, ctx| Box::pin(on_error(error, ctx)),
         // Set a function to be called prior to each command execution. This
         // provides all context of the command that would also be passed to the actual command code
         pre_command: |ctx| Box::pin(pre_command(ctx)),
         // Similar to `pre_command`, except will be called directly _after_
         // command execution.
-        post_command: |ctx| Box::pin(post_command(ctx)),
+        post_command: |ctx, outcome| Box::pin(post_command(ctx, outcome)),
 
         // Options specific to prefix commands, i.e. commands invoked via chat messages
         prefix_options: poise::PrefixFrameworkOptions {
@@ -260,6 +277,8 @@ async fn main() {
 
         ..Default::default()
     };
+    // Unlike the other callbacks, the error handler may capture state, so it's set via a method
+    options.set_on_error(|error, ctx| Box::pin(on_error(error, ctx)));
 
     // The Framework builder will automatically retrieve the bot owner and application ID via the
     // passed token, so that information need not be passed here
@@ -50,6 +50,21 @@ impl syn::fold::Fold for AllLifetimesToStatic {
 #[derive(Debug, Default)]
 struct StringList(Vec<String>);
 
+/// Variant of `poise::TypingMode`. Given as `broadcast_typing = "Persistent"`, or just
+/// `broadcast_typing` for `Once`
+#[derive(Debug)]
+struct TypingMode(syn::Ident);
+
+impl darling::FromMeta for TypingMode {
+    fn from_word() -> darling::Result<Self> {
+        Ok(Self(syn::parse_quote! { Once }))
+    }
+
+    fn from_string(value: &str) -> darling::Result<Self> {
+        <syn::Ident as darling::FromMeta>::from_string(value).map(Self)
+    }
+}
+
 impl darling::FromMeta for StringList {
     fn from_list(items: &[::syn::NestedMeta]) -> darling::Result<Self> {
         items
@@ -69,10 +84,20 @@ pub struct CommandOptions {
     context_menu_command: Option<String>,
 
     aliases: StringList,
+    examples: StringList,
+    priority: i32,
     track_edits: bool,
-    broadcast_typing: bool,
+    delete_response_on_trigger_delete: bool,
+    broadcast_typing: Option<TypingMode>,
+    redact_args: bool,
+    reply_to_invocation: Option<bool>,
+    allow_self_author: Option<bool>,
+    // In seconds
+    timeout: Option<u64>,
     explanation_fn: Option<syn::Path>,
+    description_localized: Option<syn::Path>,
     check: Option<syn::Path>,
+    check_with_reason: Option<syn::Path>,
     on_error: Option<syn::Path>,
     rename: Option<String>,
     discard_spare_arguments: bool,
@@ -81,9 +106,14 @@ pub struct CommandOptions {
     required_permissions: Option<syn::punctuated::Punctuated<syn::Ident, syn::Token![|]>>,
     required_bot_permissions: Option<syn::punctuated::Punctuated<syn::Ident, syn::Token![|]>>,
     owners_only: bool,
+    guild_only: bool,
+    dm_only: bool,
     identifying_name: Option<String>,
     category: Option<String>,
     subcommands: StringList,
+    subcommand_required: bool,
+    on_missing_required_args: Option<syn::Ident>,
+    argument_delimiters: Option<String>,
 
     // In seconds
     global_cooldown: Option<u64>,
@@ -91,6 +121,9 @@ pub struct CommandOptions {
     guild_cooldown: Option<u64>,
     channel_cooldown: Option<u64>,
     member_cooldown: Option<u64>,
+
+    max_concurrent: Option<u32>,
+    max_concurrent_per_user: Option<u32>,
 }
 
 /// Representation of the function parameter attribute arguments
@@ -102,6 +135,8 @@ struct ParamOptions {
     lazy: bool,
     flag: bool,
     rest: bool,
+    min: Option<i64>,
+    max: Option<i64>,
 }
 
 /// Part of the Invocation struct. Represents a single parameter of a Discord command.
@@ -159,6 +194,7 @@ fn make_command_id(inv: &Invocation) -> proc_macro2::TokenStream {
     let identifying_name = identifying_name.as_ref().unwrap_or(&inv.command_name);
 
     let description = wrap_option(inv.description);
+    let description_localized = wrap_option(inv.more.description_localized.as_ref());
     let hide_in_help = &inv.more.hide_in_help;
     let category = wrap_option(inv.more.category.as_ref());
 
@@ -168,15 +204,21 @@ fn make_command_id(inv: &Invocation) -> proc_macro2::TokenStream {
     let channel_cooldown = wrap_option(inv.more.channel_cooldown);
     let member_cooldown = wrap_option(inv.more.member_cooldown);
 
+    let max_concurrent = wrap_option(inv.more.max_concurrent);
+    let max_concurrent_per_user = wrap_option(inv.more.max_concurrent_per_user);
+
     let required_permissions = inv.required_permissions;
     let required_bot_permissions = inv.required_bot_permissions;
     let owners_only = inv.more.owners_only;
+    let guild_only = inv.more.guild_only;
+    let dm_only = inv.more.dm_only;
 
     quote::quote! {
         ::poise::CommandId {
             identifying_name: String::from(#identifying_name),
             category: #category,
             inline_help: #description,
+            description_localized: #description_localized,
             hide_in_help: #hide_in_help,
             cooldowns: std::sync::Mutex::new(::poise::Cooldowns::new(::poise::CooldownConfig {
                 global: #global_cooldown.map(std::time::Duration::from_secs),
@@ -185,9 +227,15 @@ fn make_command_id(inv: &Invocation) -> proc_macro2::TokenStream {
                 channel: #channel_cooldown.map(std::time::Duration::from_secs),
                 member: #member_cooldown.map(std::time::Duration::from_secs),
             })),
+            concurrency: std::sync::Mutex::new(::poise::Concurrency::new(::poise::ConcurrencyConfig {
+                global: #max_concurrent,
+                user: #max_concurrent_per_user,
+            })),
             required_permissions: #required_permissions,
             required_bot_permissions: #required_bot_permissions,
             owners_only: #owners_only,
+            guild_only: #guild_only,
+            dm_only: #dm_only,
         }
     }
 }
@@ -208,6 +256,11 @@ pub fn command(
         return Err(syn::Error::new(proc_macro2::Span::call_site(), err_msg).into());
     }
 
+    if args.guild_only && args.dm_only {
+        let err_msg = "`guild_only` and `dm_only` are mutually exclusive";
+        return Err(syn::Error::new(proc_macro2::Span::call_site(), err_msg).into());
+    }
+
     // Collect argument names/types/attributes to insert into generated function
     let mut parameters = Vec::new();
     for command_param in function.sig.inputs.iter_mut().skip(1) {
@@ -8,21 +8,33 @@ pub fn generate_prefix_command_spec(
     let explanation = match &inv.more.explanation_fn {
         Some(explanation_fn) => quote::quote! { Some(#explanation_fn) },
         None => match &inv.explanation {
-            Some(extracted_explanation) => quote::quote! { Some(|| #extracted_explanation.into()) },
+            Some(extracted_explanation) => {
+                quote::quote! { Some(|_| #extracted_explanation.into()) }
+            }
             None => quote::quote! { None },
         },
     };
 
-    // Box::pin the check and on_error callbacks in order to store them in a struct
+    // Box::pin the check, check_with_reason and on_error callbacks in order to store them in a struct
     let check = match &inv.more.check {
         Some(check) => {
             quote::quote! { Some(|ctx| Box::pin(#check(ctx.into()))) }
         }
         None => quote::quote! { None },
     };
+    let check_with_reason = match &inv.more.check_with_reason {
+        Some(check_with_reason) => {
+            quote::quote! { Some(|ctx| Box::pin(#check_with_reason(ctx.into()))) }
+        }
+        None => quote::quote! { None },
+    };
     let on_error = match &inv.more.on_error {
         Some(on_error) => {
-            quote::quote! { Some(|err, ctx| Box::pin(#on_error(err, ctx.into()))) }
+            quote::quote! { Some(::std::sync::Arc::new({
+                let on_error: fn(_, ::poise::PrefixCommandErrorContext<'_, _, _>) -> ::poise::BoxFuture<'_, ()> =
+                    |err, ctx| Box::pin(#on_error(err, ctx.into()));
+                on_error
+            })) }
         }
         None => quote::quote! { None },
     };
@@ -33,6 +45,20 @@ pub fn generate_prefix_command_spec(
         None
     };
 
+    // A greedy Vec parameter takes as many arguments as possible, so a second one would only ever
+    // receive the arguments that the first one gives back
+    let mut greedy_vec_params = inv
+        .parameters
+        .iter()
+        .filter(|p| !p.more.lazy && super::extract_vec_type(&p.type_).is_some());
+    if let (Some(_), Some(second)) = (greedy_vec_params.next(), greedy_vec_params.next()) {
+        return Err(syn::Error::new(
+            second.span,
+            "only one Vec parameter can be greedy; mark the others with #[lazy]",
+        )
+        .into());
+    }
+
     let param_specs =
         inv.parameters
             .iter()
@@ -75,29 +101,157 @@ pub fn generate_prefix_command_spec(
             })
             .collect::<Result<Vec<_>, darling::Error>>()?;
 
+    let parameters = inv.parameters.iter().map(|p| {
+        let name = p.name.to_string();
+        let required = !p.more.flag
+            && super::extract_option_type(&p.type_).is_none()
+            && super::extract_vec_type(&p.type_).is_none();
+        let description = super::wrap_option(p.more.description.as_ref());
+        let is_flag = p.more.flag;
+        let greedy = p.more.rest || super::extract_vec_type(&p.type_).is_some();
+        let min = super::wrap_option(p.more.min);
+        let max = super::wrap_option(p.more.max);
+        quote::quote! {
+            ::poise::ParameterMeta {
+                name: #name,
+                required: #required,
+                description: #description,
+                is_flag: #is_flag,
+                greedy: #greedy,
+                min: #min,
+                max: #max,
+            }
+        }
+    });
+
     let command_name = &inv.command_name;
     let track_edits = inv.more.track_edits;
-    let broadcast_typing = inv.more.broadcast_typing;
+    let delete_response_on_trigger_delete = inv.more.delete_response_on_trigger_delete;
+    let broadcast_typing = match &inv.more.broadcast_typing {
+        Some(super::TypingMode(mode)) => quote::quote! { Some(::poise::TypingMode::#mode) },
+        None => quote::quote! { None },
+    };
+    let redact_args = inv.more.redact_args;
+    let reply_to_invocation = match inv.more.reply_to_invocation {
+        Some(reply_to_invocation) => quote::quote! { Some(#reply_to_invocation) },
+        None => quote::quote! { None },
+    };
+    let allow_self_author = match inv.more.allow_self_author {
+        Some(allow_self_author) => quote::quote! { Some(#allow_self_author) },
+        None => quote::quote! { None },
+    };
+    let timeout = match inv.more.timeout {
+        Some(timeout) => quote::quote! { Some(std::time::Duration::from_secs(#timeout)) },
+        None => quote::quote! { None },
+    };
+    let subcommand_required = inv.more.subcommand_required;
+    let argument_delimiters = match &inv.more.argument_delimiters {
+        Some(delimiters) => delimiters.chars().collect(),
+        None => vec![' ', '\t', '\n'],
+    };
     let aliases = &inv.more.aliases.0;
+    let examples = &inv.more.examples.0;
+    let priority = inv.more.priority;
     let param_names = inv.parameters.iter().map(|p| &p.name).collect::<Vec<_>>();
+
+    // Numeric bounds are checked after all arguments parsed successfully
+    let range_checks = inv
+        .parameters
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| p.more.min.is_some() || p.more.max.is_some())
+        .map(|(i, p)| {
+            let name = &p.name;
+            let min = super::wrap_option(p.more.min);
+            let max = super::wrap_option(p.more.max);
+            quote::quote! {
+                if let Err(e) = ::poise::OutOfRange::check(&#name, #min, #max) {
+                    let e = ::poise::ArgumentParseError {
+                        arg_index: #i,
+                        arg_name: Some(stringify!(#name)),
+                        input: args.to_owned(),
+                        source: Box::new(e),
+                    };
+                    return Err((e.into(), ::poise::CommandErrorLocation::ArgumentParse));
+                }
+            }
+        });
+
+    let on_missing_required_args = match &inv.more.on_missing_required_args {
+        Some(behavior) => behavior.clone(),
+        None => syn::parse_quote! { Error },
+    };
+    // Only generated when requested, because it requires all parameter types to implement Default
+    let invoke_with_defaults = if on_missing_required_args == "InvokeWithDefaults" {
+        let defaults = param_names
+            .iter()
+            .map(|_| quote::quote! { Default::default() });
+        Some(quote::quote! {
+            let behavior = ctx.command.map(|command| command.options.on_missing_required_args);
+            if behavior == Some(::poise::MissingArgBehavior::InvokeWithDefaults)
+                && args.trim().is_empty()
+            {
+                let response = inner(ctx.into(), #( #defaults, )* )
+                    .await
+                    .map_err(|e| (e, ::poise::CommandErrorLocation::Body))?;
+                return ::poise::CommandReturn::send_response(response, ::poise::Context::Prefix(ctx))
+                    .await
+                    .map_err(|e| (e, ::poise::CommandErrorLocation::Body));
+            }
+        })
+    } else {
+        None
+    };
     Ok(quote::quote! {
         ::poise::PrefixCommand {
             name: #command_name,
             action: |ctx, args| Box::pin(async move {
-                let ( #( #param_names, )* .. ) = ::poise::parse_prefix_args!(
-                    ctx.discord, ctx.msg, args =>
+                let syntax = ctx.argument_syntax();
+                let ( #( #param_names, )* .. ) = match ::poise::parse_prefix_args!(
+                    ctx.discord, ctx.msg, args, syntax =>
                     #( #param_specs, )*
                     #wildcard_arg
-                ).await?;
-                inner(ctx.into(), #( #param_names, )* ).await
+                ).await {
+                    Ok(args) => args,
+                    Err(mut e) => {
+                        #invoke_with_defaults
+                        e.arg_name = <[&'static str]>::get(
+                            &[ #( stringify!(#param_names), )* ],
+                            e.arg_index,
+                        ).copied();
+                        if let Some(command) = ctx.command {
+                            e.detect_wrong_argument_count(command.parameters, args, syntax);
+                        }
+                        return Err((e.into(), ::poise::CommandErrorLocation::ArgumentParse));
+                    }
+                };
+                #( #range_checks )*
+                let response = inner(ctx.into(), #( #param_names, )* )
+                    .await
+                    .map_err(|e| (e, ::poise::CommandErrorLocation::Body))?;
+                ::poise::CommandReturn::send_response(response, ::poise::Context::Prefix(ctx))
+                    .await
+                    .map_err(|e| (e, ::poise::CommandErrorLocation::Body))
             }),
             id: std::sync::Arc::clone(&command_id),
+            parameters: &[ #( #parameters, )* ],
             options: ::poise::PrefixCommandOptions {
                 track_edits: #track_edits,
+                delete_response_on_trigger_delete: #delete_response_on_trigger_delete,
                 broadcast_typing: #broadcast_typing,
+                redact_args: #redact_args,
+                reply_to_invocation: #reply_to_invocation,
+                allow_self_author: #allow_self_author,
+                timeout: #timeout,
+                subcommand_required: #subcommand_required,
+                on_missing_required_args: ::poise::MissingArgBehavior::#on_missing_required_args,
+                argument_delimiters: &[ #( #argument_delimiters, )* ],
                 aliases: &[ #( #aliases, )* ],
+                examples: &[ #( #examples, )* ],
+                priority: #priority,
                 multiline_help: #explanation,
                 check: #check,
+                check_with_reason: #check_with_reason,
                 on_error: #on_error,
             }
         }
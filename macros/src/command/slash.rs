@@ -3,16 +3,26 @@ use syn::spanned::Spanned as _;
 use super::{extract_option_type, extract_vec_type, Invocation};
 
 fn generate_options(inv: &Invocation) -> proc_macro2::TokenStream {
-    // Box::pin the check and on_error callbacks in order to store them in a struct
+    // Box::pin the check, check_with_reason and on_error callbacks in order to store them in a struct
     let check = match &inv.more.check {
         Some(check) => {
             quote::quote! { Some(|ctx| Box::pin(#check(ctx.into()))) }
         }
         None => quote::quote! { None },
     };
+    let check_with_reason = match &inv.more.check_with_reason {
+        Some(check_with_reason) => {
+            quote::quote! { Some(|ctx| Box::pin(#check_with_reason(ctx.into()))) }
+        }
+        None => quote::quote! { None },
+    };
     let on_error = match &inv.more.on_error {
         Some(on_error) => quote::quote! {
-            Some(|err, ctx| Box::pin(#on_error(err, ::poise::CommandErrorContext::Application(ctx))))
+            Some(::std::sync::Arc::new({
+                let on_error: fn(_, ::poise::ApplicationCommandErrorContext<'_, _, _>) -> ::poise::BoxFuture<'_, ()> =
+                    |err, ctx| Box::pin(#on_error(err, ::poise::CommandErrorContext::Application(ctx)));
+                on_error
+            }))
         },
         None => quote::quote! { None },
     };
@@ -21,6 +31,7 @@ fn generate_options(inv: &Invocation) -> proc_macro2::TokenStream {
     quote::quote! {
         ::poise::ApplicationCommandOptions {
             check: #check,
+            check_with_reason: #check_with_reason,
             on_error: #on_error,
             ephemeral: #ephemeral,
         }
@@ -161,7 +172,8 @@ pub fn generate_slash_command_spec(
                     #( (#param_names: #param_types), )*
                 ).await?;
 
-                inner(ctx.into(), #( #param_names, )*).await
+                let response = inner(ctx.into(), #( #param_names, )*).await?;
+                ::poise::CommandReturn::send_response(response, ::poise::Context::Application(ctx)).await
             }),
             id: std::sync::Arc::clone(&command_id),
             options: #options,
@@ -188,7 +200,10 @@ pub fn generate_context_menu_command_spec(
         ::poise::ContextMenuCommand {
             name: #name,
             action: <#param_type as ::poise::ContextMenuParameter<_, _>>::to_action(|ctx, value| {
-                Box::pin(async move { inner(ctx.into(), value).await })
+                Box::pin(async move {
+                    let response = inner(ctx.into(), value).await?;
+                    ::poise::CommandReturn::send_response(response, ::poise::Context::Application(ctx)).await
+                })
             }),
             id: std::sync::Arc::clone(&command_id),
             options: #options,
@@ -0,0 +1,161 @@
+//! Confirmation prompt for destructive commands using message component buttons
+
+use crate::serenity_prelude as serenity;
+
+/// Options for [`confirm_with_options`]
+#[derive(Clone, Debug)]
+pub struct ConfirmationOptions {
+    /// How long to wait for the invoker to click one of the buttons.
+    ///
+    /// Defaults to 30 seconds
+    pub timeout: std::time::Duration,
+    /// If true, [`ConfirmationError::TimedOut`] is returned when nobody clicks a button in time.
+    /// Otherwise, a timeout counts as declining.
+    ///
+    /// Defaults to false
+    pub error_on_timeout: bool,
+}
+
+impl Default for ConfirmationOptions {
+    fn default() -> Self {
+        Self {
+            timeout: std::time::Duration::from_secs(30),
+            error_on_timeout: false,
+        }
+    }
+}
+
+/// Error that can be returned from [`confirm_with_options`]
+#[derive(Debug)]
+pub enum ConfirmationError {
+    /// Sending the prompt or updating it failed
+    Serenity(serenity::Error),
+    /// No button was clicked within [`ConfirmationOptions::timeout`]. Only returned if
+    /// [`ConfirmationOptions::error_on_timeout`] is set
+    TimedOut,
+}
+
+impl std::fmt::Display for ConfirmationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Serenity(e) => e.fmt(f),
+            Self::TimedOut => f.write_str("Confirmation timed out"),
+        }
+    }
+}
+
+impl std::error::Error for ConfirmationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Serenity(e) => Some(e),
+            Self::TimedOut => None,
+        }
+    }
+}
+
+impl From<serenity::Error> for ConfirmationError {
+    fn from(e: serenity::Error) -> Self {
+        Self::Serenity(e)
+    }
+}
+
+/// Custom ID suffixes, labels and styles of the buttons
+const BUTTONS: [(&str, &str, serenity::ButtonStyle); 2] = [
+    ("confirm", "✅ Confirm", serenity::ButtonStyle::Success),
+    ("cancel", "❌ Cancel", serenity::ButtonStyle::Danger),
+];
+
+fn create_buttons(
+    c: &mut serenity::CreateComponents,
+    ctx_id: u64,
+    disabled: bool,
+) -> &mut serenity::CreateComponents {
+    c.create_action_row(|ar| {
+        for &(name, label, style) in &BUTTONS {
+            ar.create_button(|b| {
+                b.style(style)
+                    .label(label)
+                    .custom_id(format!("{}{}", ctx_id, name))
+                    .disabled(disabled)
+            });
+        }
+        ar
+    })
+}
+
+/// Shorthand of [`confirm_with_options`] with the default [`ConfirmationOptions`]
+pub async fn confirm<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    prompt: &str,
+) -> Result<bool, ConfirmationError> {
+    confirm_with_options(ctx, prompt, ConfirmationOptions::default()).await
+}
+
+/// Sends the prompt along with ✅ and ❌ buttons and waits for the command invoker to click one
+/// of them. Returns whether the invoker confirmed. Clicks by other users are ignored.
+///
+/// Once a button was clicked or the timeout elapsed, the buttons are disabled.
+///
+/// ```rust,no_run
+/// # async fn _test(ctx: poise::Context<'_, (), ()>) -> Result<(), poise::builtins::ConfirmationError> {
+/// if poise::builtins::confirm(ctx, "Are you sure you want to ban this user?").await? {
+///     // ban the user
+/// } else {
+///     ctx.say("Cancelled").await?;
+/// }
+/// # Ok(()) }
+/// ```
+pub async fn confirm_with_options<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    prompt: &str,
+    options: ConfirmationOptions,
+) -> Result<bool, ConfirmationError> {
+    let ctx_id = ctx.id();
+    let reply = ctx
+        .send(|m| {
+            m.content(prompt)
+                .components(|c| create_buttons(c, ctx_id, false))
+        })
+        .await?;
+    let mut msg = match reply {
+        Some(reply) => reply.message().await?,
+        None => return Ok(false),
+    };
+
+    let discord = ctx.discord();
+    let deadline = std::time::Instant::now() + options.timeout;
+    loop {
+        let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+        let mci = match serenity::CollectComponentInteraction::new(discord)
+            .message_id(msg.id)
+            .author_id(ctx.author().id)
+            .timeout(remaining)
+            .await
+        {
+            Some(x) => x,
+            None => break,
+        };
+
+        let confirmed = match mci.data.custom_id.trim_start_matches(&ctx_id.to_string()) {
+            "confirm" => true,
+            "cancel" => false,
+            _ => continue,
+        };
+        mci.create_interaction_response(discord, |ir| {
+            ir.kind(serenity::InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|d| d.components(|c| create_buttons(c, ctx_id, true)))
+        })
+        .await?;
+        return Ok(confirmed);
+    }
+
+    msg.edit(discord, |m| {
+        m.components(|c| create_buttons(c, ctx_id, true))
+    })
+    .await?;
+    if options.error_on_timeout {
+        Err(ConfirmationError::TimedOut)
+    } else {
+        Ok(false)
+    }
+}
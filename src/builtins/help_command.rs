@@ -0,0 +1,310 @@
+//! Ready-made prefix help command
+
+use crate::serenity_prelude as serenity;
+
+/// Formatting options of [`help_command`], set via [`crate::PrefixFrameworkOptions::help_config`]
+#[derive(Clone, Debug, Default)]
+pub struct HelpConfig {
+    /// Whether to send the help as an embed instead of plain text.
+    ///
+    /// Defaults to false
+    pub embed: bool,
+    /// Color of the embed. Only used if [`Self::embed`] is set
+    pub color: Option<serenity::Colour>,
+    /// Text shown at the bottom of the help. If None, the command list ends with a hint on how to
+    /// get help for a specific command
+    pub footer: Option<String>,
+}
+
+/// Contents of a help response, independent of whether it's sent as plain text or embed
+#[derive(Debug, Default, PartialEq)]
+struct HelpPage {
+    title: Option<String>,
+    description: Option<String>,
+    /// Section names along with their lines
+    sections: Vec<(String, Vec<String>)>,
+    footer: Option<String>,
+}
+
+impl HelpPage {
+    /// Renders the page as a code block, like [`super::help`]
+    fn to_plain_text(&self) -> String {
+        let mut text = String::from("```\n");
+        for paragraph in self.title.iter().chain(&self.description) {
+            text += paragraph;
+            text += "\n\n";
+        }
+        for (name, lines) in &self.sections {
+            text += name;
+            text += ":\n";
+            for line in lines {
+                text += "  ";
+                text += line;
+                text += "\n";
+            }
+        }
+        if let Some(footer) = &self.footer {
+            text += "\n";
+            text += footer;
+            text += "\n";
+        }
+        text += "```";
+        text
+    }
+
+    fn create_embed<'a>(
+        &self,
+        e: &'a mut serenity::CreateEmbed,
+        config: &HelpConfig,
+    ) -> &'a mut serenity::CreateEmbed {
+        if let Some(title) = &self.title {
+            e.title(title);
+        }
+        if let Some(description) = &self.description {
+            e.description(description);
+        }
+        for (name, lines) in &self.sections {
+            e.field(name, format!("```\n{}\n```", lines.join("\n")), false);
+        }
+        if let Some(footer) = &self.footer {
+            e.footer(|f| f.text(footer));
+        }
+        if let Some(color) = config.color {
+            e.color(color);
+        }
+        e
+    }
+}
+
+/// Returns a prefix help command to add to [`crate::PrefixFrameworkOptions::commands`]. Invoked
+/// as `~help`, it lists all prefix commands grouped by category. Invoked as `~help <command>`, it
+/// shows the [multiline help](crate::PrefixCommandOptions::multiline_help) (or else the short
+/// description), usage, aliases and examples of the given command. Subcommands are addressed by
+/// their path, like `~help config set`. Commands added via
+/// [`crate::Framework::add_prefix_command`] are not listed, but can be looked up like that.
+///
+/// Commands marked with `hide_in_help` or `owners_only` are only shown to
+/// [owners](crate::FrameworkOptions::owners), and commands disabled via
+/// [`crate::PrefixFrameworkOptions::command_enabled`] are omitted. The formatting can be
+/// customized via [`crate::PrefixFrameworkOptions::help_config`].
+///
+/// For a help command which also supports slash commands, see [`super::help`].
+///
+/// ```rust
+/// # type Error = Box<dyn std::error::Error + Send + Sync>;
+/// let options = poise::FrameworkOptions::<(), Error> {
+///     prefix_options: poise::PrefixFrameworkOptions {
+///         commands: vec![poise::builtins::help_command()],
+///         help_config: poise::builtins::HelpConfig {
+///             embed: true,
+///             ..Default::default()
+///         },
+///         ..Default::default()
+///     },
+///     ..Default::default()
+/// };
+/// ```
+pub fn help_command<U, E>() -> crate::PrefixCommandMeta<U, E>
+where
+    U: Send + Sync,
+    E: From<serenity::Error> + Send,
+{
+    crate::PrefixCommandMeta {
+        command: crate::PrefixCommand {
+            name: "help",
+            action: |ctx, args| Box::pin(help_action(ctx, args)),
+            id: std::sync::Arc::new(crate::CommandId {
+                identifying_name: String::from("help"),
+                category: None,
+                hide_in_help: false,
+                inline_help: Some("Show this menu"),
+                description_localized: None,
+                cooldowns: std::sync::Mutex::new(crate::Cooldowns::new(Default::default())),
+                concurrency: std::sync::Mutex::new(crate::Concurrency::new(Default::default())),
+                required_permissions: serenity::Permissions::empty(),
+                required_bot_permissions: serenity::Permissions::empty(),
+                owners_only: false,
+                guild_only: false,
+                dm_only: false,
+            }),
+            parameters: &[crate::ParameterMeta {
+                name: "command",
+                required: false,
+                description: Some("Command to show help about"),
+                is_flag: false,
+                greedy: true,
+                min: None,
+                max: None,
+            }],
+            options: crate::PrefixCommandOptions {
+                track_edits: true,
+                ..Default::default()
+            },
+        },
+        subcommands: Vec::new(),
+    }
+}
+
+async fn help_action<U, E>(
+    ctx: crate::PrefixContext<'_, U, E>,
+    args: &str,
+) -> Result<(), (E, crate::CommandErrorLocation)>
+where
+    U: Send + Sync,
+    E: From<serenity::Error> + Send,
+{
+    let path = args.split_whitespace().collect::<Vec<_>>();
+    let page = if path.is_empty() {
+        command_list(ctx).await
+    } else {
+        command_help(ctx, &path).await
+    };
+
+    let config = &ctx.framework.options().prefix_options.help_config;
+    crate::send_reply(ctx.into(), |m| {
+        if config.embed {
+            m.embed(|e| page.create_embed(e, config))
+        } else {
+            m.content(page.to_plain_text())
+        }
+    })
+    .await
+    .map_err(|e| (e.into(), crate::CommandErrorLocation::Body))?;
+    Ok(())
+}
+
+/// Whether the command is listed to the invoking user
+async fn is_visible<'a, U, E>(
+    ctx: crate::PrefixContext<'a, U, E>,
+    command: &'a crate::PrefixCommand<U, E>,
+) -> bool {
+    let is_owner = ctx.framework.options().owners.contains(&ctx.msg.author.id);
+    let restricted = command.id.hide_in_help || command.id.owners_only;
+    (is_owner || !restricted) && super::is_command_enabled(ctx.into(), command).await
+}
+
+async fn command_list<U, E>(ctx: crate::PrefixContext<'_, U, E>) -> HelpPage {
+    let config = &ctx.framework.options().prefix_options.help_config;
+    let uncategorized_label = &ctx
+        .framework
+        .options()
+        .prefix_options
+        .help_uncategorized_label;
+    let locale = crate::Context::Prefix(ctx).locale().await;
+
+    let mut sections = Vec::new();
+    for (category_name, commands) in ctx.framework.commands_by_category() {
+        let mut lines = Vec::new();
+        for command in commands {
+            let command = match command.prefix {
+                Some(command_meta) => &command_meta.command,
+                None => continue,
+            };
+            if !is_visible(ctx, command).await {
+                continue;
+            }
+            lines.push(format!(
+                "{}{:<12}{}",
+                ctx.prefix,
+                command.name,
+                command
+                    .id
+                    .localized_inline_help(locale.as_deref())
+                    .unwrap_or_default()
+            ));
+        }
+        if !lines.is_empty() {
+            let category_name = category_name.unwrap_or(uncategorized_label);
+            sections.push((category_name.to_owned(), lines));
+        }
+    }
+
+    let footer = config.footer.clone().unwrap_or_else(|| {
+        format!(
+            "Type {}help <command> for more info on a command.",
+            ctx.prefix
+        )
+    });
+    HelpPage {
+        sections,
+        footer: Some(footer),
+        ..Default::default()
+    }
+}
+
+async fn command_help<U, E>(ctx: crate::PrefixContext<'_, U, E>, path: &[&str]) -> HelpPage {
+    let config = &ctx.framework.options().prefix_options.help_config;
+    let runtime_commands = ctx.framework.runtime_prefix_commands();
+    let command = match ctx.framework.find_prefix_command(&runtime_commands, path) {
+        Some(command) if is_visible(ctx, command).await => command,
+        _ => {
+            return HelpPage {
+                description: Some(format!("No such command `{}`", path.join(" "))),
+                ..Default::default()
+            }
+        }
+    };
+
+    let description = match command.options.multiline_help {
+        Some(multiline_help) => multiline_help(ctx.into()),
+        None => {
+            let locale = crate::Context::Prefix(ctx).locale().await;
+            let inline_help = command.id.localized_inline_help(locale.as_deref());
+            inline_help.unwrap_or_else(|| "No help available".to_owned())
+        }
+    };
+
+    // The usage string only contains the last path segment, so the parents are prepended
+    let parents = path[..path.len() - 1]
+        .iter()
+        .map(|name| format!("{} ", name))
+        .collect::<String>();
+    let mut sections = vec![(
+        "Usage".to_owned(),
+        vec![command.usage_string(&format!("{}{}", ctx.prefix, parents))],
+    )];
+    if !command.options.aliases.is_empty() {
+        let aliases = command.options.aliases.join(", ");
+        sections.push(("Aliases".to_owned(), vec![aliases]));
+    }
+    if !command.options.examples.is_empty() {
+        let examples = command.options.examples.iter();
+        let examples = examples.map(|example| format!("{}{}", ctx.prefix, example));
+        sections.push(("Examples".to_owned(), examples.collect()));
+    }
+
+    HelpPage {
+        title: Some(format!("{}{}{}", ctx.prefix, parents, command.name)),
+        description: Some(description),
+        sections,
+        footer: config.footer.clone(),
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_help_page_to_plain_text() {
+    let page = HelpPage {
+        title: Some("~ban".into()),
+        description: Some("Bans a user".into()),
+        sections: vec![
+            ("Usage".into(), vec!["~ban <user> [reason]".into()]),
+            ("Aliases".into(), vec!["b, yeet".into()]),
+        ],
+        footer: Some("Be careful".into()),
+    };
+    assert_eq!(
+        page.to_plain_text(),
+        "```\n~ban\n\nBans a user\n\nUsage:\n  ~ban <user> [reason]\nAliases:\n  b, yeet\n\nBe \
+        careful\n```"
+    );
+
+    let page = HelpPage {
+        sections: vec![("Commands".into(), vec!["~ping".into(), "~help".into()])],
+        ..Default::default()
+    };
+    assert_eq!(
+        page.to_plain_text(),
+        "```\nCommands:\n  ~ping\n  ~help\n```"
+    );
+}
@@ -3,6 +3,19 @@
 //! This file provides sample commands and utility functions like help menus or error handlers to
 //! use as a starting point for the framework.
 
+mod help_command;
+pub use help_command::*;
+
+#[cfg(feature = "collector")]
+mod confirm;
+#[cfg(feature = "collector")]
+pub use confirm::*;
+
+#[cfg(feature = "collector")]
+mod paginate;
+#[cfg(feature = "collector")]
+pub use paginate::*;
+
 use crate::serenity_prelude as serenity;
 
 type BoxErrorSendSync = Box<dyn std::error::Error + Send + Sync>;
@@ -15,14 +28,14 @@ pub async fn on_error<D>(e: BoxErrorSendSync, ctx: crate::ErrorContext<'_, D, Bo
     println!("Encountered an error: {:?}", e);
     match ctx {
         crate::ErrorContext::Command(ctx) => {
-            let user_error_msg = if let Some(crate::ArgumentParseError(e)) = e.downcast_ref() {
+            let user_error_msg = if let Some(e) = e.downcast_ref::<crate::ArgumentParseError>() {
                 // If we caught an argument parse error, give a helpful error message with the
                 // command explanation if available
 
                 let mut usage = "Please check the help menu for usage information".into();
                 if let crate::CommandErrorContext::Prefix(ctx) = &ctx {
                     if let Some(multiline_help) = &ctx.command.options.multiline_help {
-                        usage = multiline_help();
+                        usage = multiline_help(ctx.ctx.into());
                     }
                 }
                 format!("**{}**\n{}", e, usage)
@@ -44,6 +57,24 @@ pub async fn on_error<D>(e: BoxErrorSendSync, ctx: crate::ErrorContext<'_, D, Bo
                 e
             )
         }
+        crate::ErrorContext::InvokeOnMention(ctx) => {
+            println!(
+                "Error in mention callback from message {:?}: {}",
+                ctx.msg.content, e
+            )
+        }
+        crate::ErrorContext::NonCommandMessage(ctx) => {
+            println!(
+                "Error in non-command message callback from message {:?}: {}",
+                ctx.msg.content, e
+            )
+        }
+        crate::ErrorContext::Component(ctx) => {
+            println!(
+                "Error in component handler for {:?}: {}",
+                ctx.interaction.data.custom_id, e
+            )
+        }
         crate::ErrorContext::Setup => println!("Setup failed: {}", e),
     }
 }
@@ -79,6 +110,13 @@ pub enum HelpResponseMode {
 /// A help command that outputs text in a code block, groups commands by categories, and annotates
 /// commands with a slash if they exist as slash commands.
 ///
+/// Command descriptions are shown in the invoker's locale (see [`crate::Context::locale`]) if the
+/// command provides translations via [`crate::CommandId::description_localized`].
+///
+/// Commands marked with `hide_in_help` are only listed if the invoking user is one of the
+/// [owners](crate::FrameworkOptions::owners). Commands disabled via
+/// [`crate::PrefixFrameworkOptions::command_enabled`] are omitted.
+///
 /// Example usage from Ferris, the Discord bot running in the Rust community server:
 /// ```rust
 /// # type Error = Box<dyn std::error::Error>;
@@ -142,48 +180,76 @@ pub async fn help<U, E>(
         HelpResponseMode::Ephemeral => true,
     };
 
-    if let Some(command) = command {
-        let reply = if let Some(command) = ctx
+    // Command descriptions are translated if the commands provide translations
+    let locale = ctx.locale().await;
+    let locale = locale.as_deref();
+
+    if let Some(command_name) = command {
+        let command = ctx
             .framework()
             .options()
             .prefix_options
             .commands
             .iter()
             .map(|cmd_meta| &cmd_meta.command)
-            .find(|cmd| cmd.name == command)
-        {
-            match command.options.multiline_help {
-                Some(f) => f(),
+            .find(|cmd| cmd.name == command_name);
+        let command = match command {
+            Some(command) if is_command_enabled(ctx, command).await => Some(command),
+            _ => None,
+        };
+
+        let reply = if let Some(command) = command {
+            let mut reply = match command.options.multiline_help {
+                Some(f) => f(ctx),
                 None => command
                     .id
-                    .inline_help
-                    .unwrap_or("No help available")
-                    .to_owned(),
+                    .localized_inline_help(locale)
+                    .unwrap_or_else(|| "No help available".to_owned()),
+            };
+
+            if !command.options.examples.is_empty() {
+                let prefix = match ctx {
+                    crate::Context::Prefix(ctx) => ctx.prefix,
+                    crate::Context::Application(_) => {
+                        let prefix = &ctx.framework().options().prefix_options.prefix;
+                        prefix.as_deref().unwrap_or("")
+                    }
+                };
+                reply += "\n\nExamples:";
+                for example in command.options.examples {
+                    reply += &format!("\n  {}{}", prefix, example);
+                }
             }
+            reply
         } else {
-            format!("No such command `{}`", command)
+            format!("No such command `{}`", command_name)
         };
 
         ctx.send(|f| f.content(reply).ephemeral(ephemeral)).await?;
         return Ok(());
     }
 
-    let mut categories =
-        crate::util::OrderedMap::<Option<&str>, Vec<crate::CommandDefinitionRef<'_, U, E>>>::new();
-    for cmd in ctx.framework().commands() {
-        categories
-            .get_or_insert_with(cmd.id.category, Vec::new)
-            .push(cmd);
-    }
+    // Hidden commands are only listed for bot owners
+    let show_hidden = ctx.framework().options().owners.contains(&ctx.author().id);
 
+    let uncategorized_label = &ctx
+        .framework()
+        .options()
+        .prefix_options
+        .help_uncategorized_label;
     let mut menu = String::from("```\n");
-    for (category_name, commands) in categories {
-        menu += category_name.unwrap_or("Commands");
+    for (category_name, commands) in ctx.framework().commands_by_category() {
+        menu += category_name.unwrap_or(uncategorized_label);
         menu += ":\n";
         for command in commands {
-            if command.id.hide_in_help {
+            if command.id.hide_in_help && !show_hidden {
                 continue;
             }
+            if let Some(prefix_command) = command.prefix {
+                if !is_command_enabled(ctx, &prefix_command.command).await {
+                    continue;
+                }
+            }
 
             let (prefix, command_name) = if let Some(slash_command) = &command.slash {
                 ("/", slash_command.name())
@@ -201,7 +267,7 @@ pub async fn help<U, E>(
                 "  {}{:<12}{}\n",
                 prefix,
                 command_name,
-                command.id.inline_help.unwrap_or("")
+                command.id.localized_inline_help(locale).unwrap_or_default()
             );
         }
     }
@@ -214,6 +280,17 @@ pub async fn help<U, E>(
     Ok(())
 }
 
+/// Whether the command is enabled according to [`crate::PrefixFrameworkOptions::command_enabled`]
+async fn is_command_enabled<'a, U, E>(
+    ctx: crate::Context<'a, U, E>,
+    command: &'a crate::PrefixCommand<U, E>,
+) -> bool {
+    match ctx.framework().options().prefix_options.command_enabled {
+        Some(command_enabled) => command_enabled(ctx, command).await,
+        None => true,
+    }
+}
+
 /// Generic function to register application commands, either globally or in a guild. Only bot
 /// owners can register globally, only guild owners can register in guild.
 ///
@@ -0,0 +1,145 @@
+//! Sample pagination implementation using message component buttons
+
+use crate::serenity_prelude as serenity;
+
+/// Options for [`paginate_with_options`]
+#[derive(Clone, Debug)]
+pub struct PaginationOptions {
+    /// How long to wait for a button click before the navigation buttons are disabled. The timer
+    /// restarts on every click.
+    ///
+    /// Defaults to 60 seconds
+    pub timeout: std::time::Duration,
+    /// If true, clicks from users other than the command invoker are ignored.
+    ///
+    /// Defaults to true
+    pub only_invoker: bool,
+}
+
+impl Default for PaginationOptions {
+    fn default() -> Self {
+        Self {
+            timeout: std::time::Duration::from_secs(60),
+            only_invoker: true,
+        }
+    }
+}
+
+/// Custom ID suffixes and labels of the navigation buttons
+const BUTTONS: [(&str, &str); 4] = [("first", "⏮"), ("prev", "◀"), ("next", "▶"), ("last", "⏭")];
+
+fn create_buttons(
+    c: &mut serenity::CreateComponents,
+    ctx_id: u64,
+    disabled: bool,
+) -> &mut serenity::CreateComponents {
+    c.create_action_row(|ar| {
+        for (name, label) in &BUTTONS {
+            ar.create_button(|b| {
+                b.style(serenity::ButtonStyle::Secondary)
+                    .label(label)
+                    .custom_id(format!("{}{}", ctx_id, name))
+                    .disabled(disabled)
+            });
+        }
+        ar
+    })
+}
+
+/// Shorthand of [`paginate_with_options`] with the default [`PaginationOptions`]
+pub async fn paginate<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    pages: &[serenity::CreateEmbed],
+) -> Result<(), serenity::Error> {
+    paginate_with_options(ctx, pages, PaginationOptions::default()).await
+}
+
+/// Sends the first of the given embeds along with ⏮ ◀ ▶ ⏭ buttons to navigate between them.
+///
+/// The button clicks are handled in a background task, so this function returns as soon as the
+/// first page has been sent. Once no button has been clicked for [`PaginationOptions::timeout`],
+/// the buttons are disabled.
+///
+/// ```rust,no_run
+/// # async fn _test(ctx: poise::Context<'_, (), ()>) -> Result<(), poise::serenity::Error> {
+/// # use poise::serenity_prelude as serenity;
+/// let pages = ["Page one", "Page two", "Page three"]
+///     .iter()
+///     .map(|&text| {
+///         let mut embed = serenity::CreateEmbed::default();
+///         embed.description(text);
+///         embed
+///     })
+///     .collect::<Vec<_>>();
+/// poise::builtins::paginate(ctx, &pages).await?;
+/// # Ok(()) }
+/// ```
+pub async fn paginate_with_options<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    pages: &[serenity::CreateEmbed],
+    options: PaginationOptions,
+) -> Result<(), serenity::Error> {
+    let first_page = match pages.first() {
+        Some(x) => x.clone(),
+        None => return Ok(()),
+    };
+
+    let ctx_id = ctx.id();
+    let reply = ctx
+        .send(|m| {
+            m.embed(|e| {
+                *e = first_page;
+                e
+            })
+            .components(|c| create_buttons(c, ctx_id, false))
+        })
+        .await?;
+    let mut msg = match reply {
+        Some(reply) => reply.message().await?,
+        None => return Ok(()),
+    };
+
+    let discord = ctx.discord().clone();
+    let author_id = ctx.author().id;
+    let pages = pages.to_vec();
+    tokio::spawn(async move {
+        let mut current_page = 0;
+        loop {
+            let mut collector = serenity::CollectComponentInteraction::new(&discord)
+                .message_id(msg.id)
+                .timeout(options.timeout);
+            if options.only_invoker {
+                collector = collector.author_id(author_id);
+            }
+            let mci = match collector.await {
+                Some(x) => x,
+                None => break,
+            };
+
+            let button = mci.data.custom_id.trim_start_matches(&ctx_id.to_string());
+            current_page = match button {
+                "first" => 0,
+                "prev" => current_page.saturating_sub(1),
+                "next" => usize::min(current_page + 1, pages.len() - 1),
+                "last" => pages.len() - 1,
+                _ => continue,
+            };
+
+            let page = pages[current_page].clone();
+            let _: Result<_, _> = mci
+                .create_interaction_response(&discord, |ir| {
+                    ir.kind(serenity::InteractionResponseType::UpdateMessage)
+                        .interaction_response_data(|d| d.add_embed(page))
+                })
+                .await;
+        }
+
+        let _: Result<_, _> = msg
+            .edit(&discord, |m| {
+                m.components(|c| create_buttons(c, ctx_id, true))
+            })
+            .await;
+    });
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+//! Routing of message component interactions, like button clicks, to handlers registered by
+//! commands
+
+use crate::{serenity_prelude as serenity, BoxFuture};
+
+/// Context passed to component handlers registered via [`crate::Context::register_component`]
+pub struct ComponentContext<'a, U, E> {
+    /// Serenity's context, like HTTP or cache
+    pub discord: &'a serenity::Context,
+    /// The component interaction, for example the button click
+    pub interaction: &'a serenity::MessageComponentInteraction,
+    /// Read-only reference to the framework
+    ///
+    /// Useful if you need the list of commands, for example for a custom help command
+    pub framework: &'a crate::Framework<U, E>,
+    /// Your custom user data
+    pub data: &'a U,
+}
+// manual Copy+Clone implementations because Rust is getting confused about the type parameter
+impl<U, E> Clone for ComponentContext<'_, U, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<U, E> Copy for ComponentContext<'_, U, E> {}
+
+/// A component handler, see [`crate::Context::register_component`]
+pub type ComponentHandler<U, E> = std::sync::Arc<
+    dyn for<'a> Fn(ComponentContext<'a, U, E>) -> BoxFuture<'a, Result<(), E>> + Send + Sync,
+>;
+
+/// Registered component handlers by custom ID, each with the time after which it is discarded
+pub(crate) struct ComponentRegistry<U, E> {
+    handlers: std::collections::HashMap<String, (ComponentHandler<U, E>, std::time::Instant)>,
+}
+
+impl<U, E> ComponentRegistry<U, E> {
+    pub fn new() -> Self {
+        Self {
+            handlers: std::collections::HashMap::new(),
+        }
+    }
+
+    /// Registers the handler, replacing any previous handler for the same custom ID
+    pub fn insert(
+        &mut self,
+        custom_id: String,
+        handler: ComponentHandler<U, E>,
+        expires_at: std::time::Instant,
+    ) {
+        self.purge(std::time::Instant::now());
+        self.handlers.insert(custom_id, (handler, expires_at));
+    }
+
+    /// Returns whether a handler was registered for the custom ID
+    pub fn remove(&mut self, custom_id: &str) -> bool {
+        self.handlers.remove(custom_id).is_some()
+    }
+
+    /// Returns the handler for the custom ID, unless it expired
+    pub fn get(
+        &mut self,
+        custom_id: &str,
+        now: std::time::Instant,
+    ) -> Option<ComponentHandler<U, E>> {
+        self.purge(now);
+        self.handlers
+            .get(custom_id)
+            .map(|(handler, _)| std::sync::Arc::clone(handler))
+    }
+
+    /// Discards the handlers which expired
+    fn purge(&mut self, now: std::time::Instant) {
+        self.handlers.retain(|_, (_, expires_at)| *expires_at > now);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_component_registry() {
+    use std::time::{Duration, Instant};
+
+    let handler: ComponentHandler<(), ()> = std::sync::Arc::new(|_| Box::pin(async { Ok(()) }));
+    let mut registry = ComponentRegistry::new();
+    let now = Instant::now();
+    registry.insert("a".into(), handler.clone(), now + Duration::from_secs(60));
+    registry.insert("b".into(), handler.clone(), now + Duration::from_secs(10));
+
+    assert!(registry.get("a", now).is_some());
+    assert!(registry.get("b", now).is_some());
+    assert!(registry.get("c", now).is_none());
+
+    // Expired handlers are discarded
+    assert!(registry.get("b", now + Duration::from_secs(10)).is_none());
+    assert!(registry.get("a", now + Duration::from_secs(30)).is_some());
+    assert!(!registry.handlers.contains_key("b"));
+
+    assert!(registry.remove("a"));
+    assert!(!registry.remove("a"));
+    assert!(registry.get("a", now).is_none());
+}
@@ -0,0 +1,176 @@
+use crate::serenity_prelude as serenity;
+
+/// Configuration struct for [`Concurrency`]
+#[derive(Default, Clone, Debug)]
+pub struct ConcurrencyConfig {
+    /// Maximum number of invocations of the command that may run at the same time
+    pub global: Option<u32>,
+    /// Maximum number of invocations of the command by a single user that may run at the same time
+    pub user: Option<u32>,
+}
+
+/// Passed to [`crate::FrameworkOptions::max_concurrency_reached`] when an invocation was rejected
+/// because too many invocations of the command are already running
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MaxConcurrencyReached {
+    /// The limit that was reached
+    pub limit: u32,
+    /// Whether the limit applies per user ([`ConcurrencyConfig::user`]) or to all users
+    /// ([`ConcurrencyConfig::global`])
+    pub per_user: bool,
+}
+
+impl std::fmt::Display for MaxConcurrencyReached {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.per_user {
+            write!(
+                f,
+                "You can only run this command {} times at once. Please wait for your previous \
+                invocations to finish",
+                self.limit
+            )
+        } else {
+            write!(
+                f,
+                "This command can only run {} times at once. Please try again later",
+                self.limit
+            )
+        }
+    }
+}
+
+impl std::error::Error for MaxConcurrencyReached {}
+
+/// Tracks the running invocations of a single command to enforce concurrency limits
+///
+/// You probably don't need to use this directly. `#[poise::command]` automatically generates a
+/// concurrency handler.
+pub struct Concurrency {
+    config: ConcurrencyConfig,
+
+    running: u32,
+    running_per_user: std::collections::HashMap<serenity::UserId, u32>,
+}
+
+impl Concurrency {
+    /// Create a new concurrency handler with the given limits
+    pub fn new(config: ConcurrencyConfig) -> Self {
+        Self {
+            config,
+
+            running: 0,
+            running_per_user: std::collections::HashMap::new(),
+        }
+    }
+
+    /// Returns the number of invocations which are currently running, in total and by the given
+    /// user
+    pub fn running(&self, user_id: serenity::UserId) -> (u32, u32) {
+        let running_by_user = self.running_per_user.get(&user_id).copied().unwrap_or(0);
+        (self.running, running_by_user)
+    }
+
+    /// Reserves a slot for an invocation by the given user, unless a limit is reached. The slot is
+    /// freed when the returned guard is dropped, which also happens if the invocation errors,
+    /// panics or is cancelled.
+    pub fn acquire(
+        this: &std::sync::Mutex<Self>,
+        user_id: serenity::UserId,
+    ) -> Result<ConcurrencyGuard<'_>, MaxConcurrencyReached> {
+        let mut concurrency = this.lock().unwrap();
+        let (running, running_by_user) = concurrency.running(user_id);
+        if let Some(limit) = concurrency.config.global {
+            if running >= limit {
+                return Err(MaxConcurrencyReached {
+                    limit,
+                    per_user: false,
+                });
+            }
+        }
+        if let Some(limit) = concurrency.config.user {
+            if running_by_user >= limit {
+                return Err(MaxConcurrencyReached {
+                    limit,
+                    per_user: true,
+                });
+            }
+        }
+
+        concurrency.running += 1;
+        *concurrency.running_per_user.entry(user_id).or_insert(0) += 1;
+        Ok(ConcurrencyGuard {
+            concurrency: this,
+            user_id,
+        })
+    }
+
+    /// Frees the slot of a finished invocation
+    fn release(&mut self, user_id: serenity::UserId) {
+        self.running -= 1;
+        if let Some(running_by_user) = self.running_per_user.get_mut(&user_id) {
+            *running_by_user -= 1;
+            if *running_by_user == 0 {
+                self.running_per_user.remove(&user_id);
+            }
+        }
+    }
+}
+
+/// A running invocation, returned from [`Concurrency::acquire`]. Frees the slot of the invocation
+/// when dropped.
+pub struct ConcurrencyGuard<'a> {
+    concurrency: &'a std::sync::Mutex<Concurrency>,
+    user_id: serenity::UserId,
+}
+
+impl Drop for ConcurrencyGuard<'_> {
+    fn drop(&mut self) {
+        // Don't panic while possibly unwinding from a panic in the command
+        let mut concurrency = match self.concurrency.lock() {
+            Ok(x) => x,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        concurrency.release(self.user_id);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_concurrency() {
+    let (user_1, user_2) = (serenity::UserId(1), serenity::UserId(2));
+    let concurrency = std::sync::Mutex::new(Concurrency::new(ConcurrencyConfig {
+        global: Some(3),
+        user: Some(2),
+    }));
+
+    let guard_1 = Concurrency::acquire(&concurrency, user_1).unwrap();
+    let guard_2 = Concurrency::acquire(&concurrency, user_1).unwrap();
+    assert_eq!(
+        Concurrency::acquire(&concurrency, user_1).err(),
+        Some(MaxConcurrencyReached {
+            limit: 2,
+            per_user: true
+        })
+    );
+    let guard_3 = Concurrency::acquire(&concurrency, user_2).unwrap();
+    assert_eq!(
+        Concurrency::acquire(&concurrency, user_2).err(),
+        Some(MaxConcurrencyReached {
+            limit: 3,
+            per_user: false
+        })
+    );
+
+    // Finished invocations free their slot
+    drop(guard_1);
+    assert_eq!(concurrency.lock().unwrap().running(user_1), (2, 1));
+    let _guard_4 = Concurrency::acquire(&concurrency, user_2).unwrap();
+
+    drop((guard_2, guard_3));
+    assert_eq!(concurrency.lock().unwrap().running(user_1), (1, 0));
+    assert!(!concurrency
+        .lock()
+        .unwrap()
+        .running_per_user
+        .contains_key(&user_1));
+}
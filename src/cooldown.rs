@@ -18,6 +18,28 @@ pub struct CooldownConfig {
     pub member: Option<Duration>,
 }
 
+/// The data that cooldown buckets are keyed by. Can be created from a [`crate::Context`], but also
+/// manually to inspect cooldowns outside of a command invocation.
+#[derive(Clone, Debug)]
+pub struct CooldownContext {
+    /// The invoking user
+    pub user_id: serenity::UserId,
+    /// The guild of the invocation, if any
+    pub guild_id: Option<serenity::GuildId>,
+    /// The channel of the invocation
+    pub channel_id: serenity::ChannelId,
+}
+
+impl<U, E> From<crate::Context<'_, U, E>> for CooldownContext {
+    fn from(ctx: crate::Context<'_, U, E>) -> Self {
+        Self {
+            user_id: ctx.author().id,
+            guild_id: ctx.guild_id(),
+            channel_id: ctx.channel_id(),
+        }
+    }
+}
+
 /// Handles cooldowns for a single command
 ///
 /// You probably don't need to use this directly. `#[poise::command]` automatically generates a
@@ -49,19 +71,24 @@ impl Cooldowns {
     /// Queries the cooldown buckets and checks if all cooldowns have expired and command
     /// execution may proceed. If not, Some is returned with the remaining cooldown
     pub fn get_wait_time<U, E>(&self, ctx: crate::Context<'_, U, E>) -> Option<Duration> {
+        self.get_wait_time_for(&ctx.into())
+    }
+
+    /// Like [`Self::get_wait_time`], but for manually constructed cooldown bucket keys
+    pub fn get_wait_time_for(&self, ctx: &CooldownContext) -> Option<Duration> {
         let mut cooldown_data = vec![
             (self.cooldown.global, self.global_invocation),
             (
                 self.cooldown.user,
-                self.user_invocations.get(&ctx.author().id).copied(),
+                self.user_invocations.get(&ctx.user_id).copied(),
             ),
             (
                 self.cooldown.channel,
-                self.channel_invocations.get(&ctx.channel_id()).copied(),
+                self.channel_invocations.get(&ctx.channel_id).copied(),
             ),
         ];
 
-        if let Some(guild_id) = ctx.guild_id() {
+        if let Some(guild_id) = ctx.guild_id {
             cooldown_data.push((
                 self.cooldown.guild,
                 self.guild_invocations.get(&guild_id).copied(),
@@ -69,7 +96,7 @@ impl Cooldowns {
             cooldown_data.push((
                 self.cooldown.member,
                 self.member_invocations
-                    .get(&(ctx.author().id, guild_id))
+                    .get(&(ctx.user_id, guild_id))
                     .copied(),
             ));
         }
@@ -86,16 +113,82 @@ impl Cooldowns {
 
     /// Indicates that a command has been executed and all associated cooldowns should start running
     pub fn start_cooldown<U, E>(&mut self, ctx: crate::Context<'_, U, E>) {
+        self.start_cooldown_for(&ctx.into())
+    }
+
+    /// Like [`Self::start_cooldown`], but for manually constructed cooldown bucket keys
+    pub fn start_cooldown_for(&mut self, ctx: &CooldownContext) {
         let now = Instant::now();
+        self.prune(now);
 
-        self.global_invocation = Some(now);
-        self.user_invocations.insert(ctx.author().id, now);
-        self.channel_invocations.insert(ctx.channel_id(), now);
+        // Only store timestamps for buckets that actually have a cooldown configured
+        if self.cooldown.global.is_some() {
+            self.global_invocation = Some(now);
+        }
+        if self.cooldown.user.is_some() {
+            self.user_invocations.insert(ctx.user_id, now);
+        }
+        if self.cooldown.channel.is_some() {
+            self.channel_invocations.insert(ctx.channel_id, now);
+        }
 
-        if let Some(guild_id) = ctx.guild_id() {
-            self.guild_invocations.insert(guild_id, now);
-            self.member_invocations
-                .insert((ctx.author().id, guild_id), now);
+        if let Some(guild_id) = ctx.guild_id {
+            if self.cooldown.guild.is_some() {
+                self.guild_invocations.insert(guild_id, now);
+            }
+            if self.cooldown.member.is_some() {
+                self.member_invocations.insert((ctx.user_id, guild_id), now);
+            }
         }
     }
+
+    /// Forget all invocation timestamps whose cooldown has already expired, so that the buckets
+    /// don't grow unboundedly
+    fn prune(&mut self, now: Instant) {
+        fn retain_running<K: Eq>(
+            invocations: &mut OrderedMap<K, Instant>,
+            cooldown: Option<Duration>,
+            now: Instant,
+        ) {
+            let cooldown = cooldown.unwrap_or_default();
+            invocations.retain(|_, &last_invocation| {
+                now.saturating_duration_since(last_invocation) < cooldown
+            });
+        }
+
+        retain_running(&mut self.user_invocations, self.cooldown.user, now);
+        retain_running(&mut self.guild_invocations, self.cooldown.guild, now);
+        retain_running(&mut self.channel_invocations, self.cooldown.channel, now);
+        retain_running(&mut self.member_invocations, self.cooldown.member, now);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_cooldowns() {
+    let ctx = |user_id| CooldownContext {
+        user_id: serenity::UserId(user_id),
+        guild_id: None,
+        channel_id: serenity::ChannelId(1),
+    };
+
+    let mut cooldowns = Cooldowns::new(CooldownConfig {
+        user: Some(Duration::from_secs(5)),
+        ..Default::default()
+    });
+    assert_eq!(cooldowns.get_wait_time_for(&ctx(1)), None);
+    cooldowns.start_cooldown_for(&ctx(1));
+    assert!(cooldowns.get_wait_time_for(&ctx(1)).unwrap() <= Duration::from_secs(5));
+    assert_eq!(cooldowns.get_wait_time_for(&ctx(2)), None);
+    // No channel cooldown is configured, so no channel timestamps should be stored
+    assert!(cooldowns.channel_invocations.0.is_empty());
+
+    // Expired timestamps are pruned on the next invocation
+    let mut cooldowns = Cooldowns::new(CooldownConfig {
+        user: Some(Duration::from_secs(0)),
+        ..Default::default()
+    });
+    cooldowns.start_cooldown_for(&ctx(1));
+    cooldowns.start_cooldown_for(&ctx(2));
+    assert_eq!(cooldowns.user_invocations.0.len(), 1);
 }
@@ -0,0 +1,175 @@
+//! Parsing of human-readable durations like `10m` or `2h30m`, as commonly needed for moderation
+//! commands
+
+use std::time::Duration;
+
+/// Error that can be returned from [`parse_duration`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DurationParseError {
+    /// The input was empty
+    Empty,
+    /// The input started with a minus sign
+    Negative,
+    /// A unit was not preceded by a number, or the input contained an unexpected character
+    MissingNumber,
+    /// A number was not followed by a unit
+    MissingUnit,
+    /// A number was followed by a character that is not a supported unit
+    UnknownUnit(char),
+    /// The duration is too large to be represented
+    Overflow,
+}
+
+impl std::fmt::Display for DurationParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => f.write_str("Missing duration"),
+            Self::Negative => f.write_str("Duration can't be negative"),
+            Self::MissingNumber => f.write_str("Expected a number in duration"),
+            Self::MissingUnit => f.write_str("Expected a unit (s, m, h, d, w) after number"),
+            Self::UnknownUnit(unit) => write!(f, "Unknown duration unit `{}`", unit),
+            Self::Overflow => f.write_str("Duration is too large"),
+        }
+    }
+}
+
+impl std::error::Error for DurationParseError {}
+
+/// Units supported by [`parse_duration`], along with their length in seconds
+const UNITS: &[(char, u64)] = &[
+    ('w', 7 * 24 * 60 * 60),
+    ('d', 24 * 60 * 60),
+    ('h', 60 * 60),
+    ('m', 60),
+    ('s', 1),
+];
+
+/// Parses a human-readable duration consisting of one or more numbers, each followed by a unit:
+/// `s` (seconds), `m` (minutes), `h` (hours), `d` (days), or `w` (weeks). Units are case
+/// insensitive and may be combined, like `1h30m`.
+///
+/// ```rust
+/// # use std::time::Duration;
+/// assert_eq!(poise::parse_duration("10m"), Ok(Duration::from_secs(600)));
+/// assert_eq!(poise::parse_duration("2h30m"), Ok(Duration::from_secs(9000)));
+/// assert!(poise::parse_duration("-5m").is_err());
+/// assert!(poise::parse_duration("5").is_err());
+/// ```
+pub fn parse_duration(s: &str) -> Result<Duration, DurationParseError> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err(DurationParseError::Empty);
+    }
+    if s.starts_with('-') {
+        return Err(DurationParseError::Negative);
+    }
+
+    let mut total_secs: u64 = 0;
+    let mut rest = s;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if number_len == 0 {
+            return Err(DurationParseError::MissingNumber);
+        }
+        // The string consists of ASCII digits only, so parsing can only fail due to overflow
+        let number = rest[..number_len]
+            .parse::<u64>()
+            .map_err(|_| DurationParseError::Overflow)?;
+        rest = &rest[number_len..];
+
+        let unit = rest.chars().next().ok_or(DurationParseError::MissingUnit)?;
+        let unit_secs = UNITS
+            .iter()
+            .find(|&&(name, _)| name == unit.to_ascii_lowercase())
+            .map(|&(_, secs)| secs)
+            .ok_or(DurationParseError::UnknownUnit(unit))?;
+        rest = &rest[unit.len_utf8()..];
+
+        total_secs = number
+            .checked_mul(unit_secs)
+            .and_then(|secs| total_secs.checked_add(secs))
+            .ok_or(DurationParseError::Overflow)?;
+    }
+
+    Ok(Duration::from_secs(total_secs))
+}
+
+/// A command parameter type for human-readable durations like `10m` or `2h30m`. See
+/// [`parse_duration`] for the supported format.
+///
+/// Can be used as a parameter in prefix and slash commands. In slash commands, it is a string
+/// parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct HumanDuration(pub Duration);
+
+impl std::str::FromStr for HumanDuration {
+    type Err = DurationParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_duration(s).map(Self)
+    }
+}
+
+impl From<HumanDuration> for Duration {
+    fn from(duration: HumanDuration) -> Self {
+        duration.0
+    }
+}
+
+impl std::fmt::Display for HumanDuration {
+    /// Formats the duration in the format accepted by [`parse_duration`], for example `1h30m`.
+    /// Sub-second precision is discarded.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut secs = self.0.as_secs();
+        if secs == 0 {
+            return f.write_str("0s");
+        }
+        for &(unit, unit_secs) in UNITS {
+            if secs >= unit_secs {
+                write!(f, "{}{}", secs / unit_secs, unit)?;
+                secs %= unit_secs;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_parse_duration() {
+    let secs = |secs| Ok(Duration::from_secs(secs));
+    assert_eq!(parse_duration("30s"), secs(30));
+    assert_eq!(parse_duration("10m"), secs(600));
+    assert_eq!(parse_duration("2h30m"), secs(9000));
+    assert_eq!(parse_duration("1d"), secs(86400));
+    assert_eq!(parse_duration("1w2d"), secs(777600));
+    assert_eq!(parse_duration("1H30M"), secs(5400));
+    assert_eq!(parse_duration(" 0s "), secs(0));
+
+    assert_eq!(parse_duration(""), Err(DurationParseError::Empty));
+    assert_eq!(parse_duration("-5m"), Err(DurationParseError::Negative));
+    assert_eq!(parse_duration("m"), Err(DurationParseError::MissingNumber));
+    assert_eq!(
+        parse_duration("1h 30m"),
+        Err(DurationParseError::MissingNumber)
+    );
+    assert_eq!(parse_duration("10"), Err(DurationParseError::MissingUnit));
+    assert_eq!(
+        parse_duration("10y"),
+        Err(DurationParseError::UnknownUnit('y'))
+    );
+    assert_eq!(
+        parse_duration("99999999999999999999s"),
+        Err(DurationParseError::Overflow)
+    );
+    assert_eq!(
+        parse_duration("9999999999999999w"),
+        Err(DurationParseError::Overflow)
+    );
+
+    for &input in &["0s", "45s", "1h30m", "1w2d3h4m5s"] {
+        assert_eq!(input.parse::<HumanDuration>().unwrap().to_string(), input);
+    }
+}
@@ -9,7 +9,7 @@ use crate::BoxFuture;
 /// - [`Self::options`]
 ///
 /// Before starting, the builder will make an HTTP request to retrieve the bot's application ID and
-/// owner.
+/// owners.
 pub struct FrameworkBuilder<U, E> {
     user_data_setup: Option<
         Box<
@@ -156,6 +156,13 @@ impl<U, E> FrameworkBuilder<U, E> {
         for (command, meta_builder) in self.commands {
             options.command(command, meta_builder);
         }
+        if options.owners.is_empty() {
+            if let Some(team) = &application_info.team {
+                options
+                    .owners
+                    .extend(team.members.iter().map(|member| member.user.id));
+            }
+        }
         options.owners.insert(application_info.owner.id);
 
         // Create serenity client
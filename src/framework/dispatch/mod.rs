@@ -3,6 +3,9 @@ mod prefix;
 mod slash;
 
 pub use prefix::dispatch_message;
+pub(crate) use prefix::{
+    find_command_by_path, find_command_name_conflict, find_runtime_command_conflict,
+};
 
 use crate::serenity_prelude as serenity;
 
@@ -47,35 +50,118 @@ async fn user_permissions(
     guild.user_permissions_in(channel, &member).ok()
 }
 
-async fn check_required_permissions_and_owners_only<U, E>(
-    ctx: crate::Context<'_, U, E>,
-    required_permissions: serenity::Permissions,
-    owners_only: bool,
-) -> bool {
-    if owners_only && !ctx.framework().options().owners.contains(&ctx.author().id) {
-        return false;
+fn check_owners_only<U, E>(ctx: crate::Context<'_, U, E>, owners_only: bool) -> bool {
+    !owners_only || ctx.framework().options().owners.contains(&ctx.author().id)
+}
+
+fn check_guild_only<U, E>(ctx: crate::Context<'_, U, E>, guild_only: bool) -> bool {
+    !guild_only || ctx.guild_id().is_some()
+}
+
+fn check_dm_only<U, E>(ctx: crate::Context<'_, U, E>, dm_only: bool) -> bool {
+    !dm_only || ctx.guild_id().is_none()
+}
+
+/// Returns the configured [check order](crate::FrameworkOptions::check_order) without duplicates
+/// and with the missing checks appended in default order
+fn resolve_check_order(check_order: &[crate::CheckKind]) -> Vec<crate::CheckKind> {
+    let mut resolved = Vec::with_capacity(crate::CheckKind::DEFAULT_ORDER.len());
+    for &check_kind in check_order.iter().chain(crate::CheckKind::DEFAULT_ORDER) {
+        if !resolved.contains(&check_kind) {
+            resolved.push(check_kind);
+        }
     }
+    resolved
+}
 
-    if !required_permissions.is_empty() {
-        let user_permissions = user_permissions(
-            ctx.discord(),
-            ctx.guild_id(),
-            ctx.channel_id(),
-            ctx.discord().cache.current_user_id(),
-        )
-        .await;
-        match user_permissions {
-            Some(perms) => {
-                if !perms.contains(required_permissions) {
-                    return false;
-                }
+/// Whether the invocation is exempt from cooldowns according to
+/// [`crate::FrameworkOptions::cooldown_exempt`]
+async fn check_cooldown_exempt<U, E>(ctx: crate::Context<'_, U, E>) -> bool {
+    match ctx.framework().options().cooldown_exempt {
+        Some(cooldown_exempt) => cooldown_exempt(ctx).await,
+        None => false,
+    }
+}
+
+/// Registers the invocation as running, see [`crate::Framework::shutdown`]. If the framework is
+/// shutting down, [`crate::FrameworkOptions::shutting_down_handler`] is called and None is
+/// returned.
+///
+/// The returned guard must be kept alive until the command finished.
+async fn start_invocation<'a, U, E>(
+    ctx: crate::Context<'a, U, E>,
+) -> Result<Option<super::InvocationGuard<'a>>, E> {
+    match ctx.framework().start_invocation() {
+        Some(guard) => Ok(Some(guard)),
+        None => {
+            if let Some(callback) = ctx.framework().options().shutting_down_handler {
+                callback(ctx).await?;
             }
-            // better safe than sorry: when perms are unknown, restrict access
-            None => return false,
+            Ok(None)
         }
     }
+}
+
+/// Reserves a slot for the invocation in the command's
+/// [concurrency limits](crate::CommandId::concurrency). If a limit is reached,
+/// [`crate::FrameworkOptions::max_concurrency_reached`] is called and None is returned.
+///
+/// The returned guard must be kept alive until the command finished.
+async fn acquire_concurrency_slot<'a, U, E>(
+    ctx: crate::Context<'a, U, E>,
+    command_id: &'a crate::CommandId,
+) -> Result<Option<crate::ConcurrencyGuard<'a>>, E> {
+    match crate::Concurrency::acquire(&command_id.concurrency, ctx.author().id) {
+        Ok(guard) => Ok(Some(guard)),
+        Err(max_concurrency_reached) => {
+            if let Some(callback) = ctx.framework().options().max_concurrency_reached {
+                callback(ctx, max_concurrency_reached).await?;
+            }
+            Ok(None)
+        }
+    }
+}
+
+/// Returns the subset of the required permissions which the invoking user is lacking
+async fn check_missing_user_permissions<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    required_permissions: serenity::Permissions,
+) -> serenity::Permissions {
+    if required_permissions.is_empty() {
+        return serenity::Permissions::empty();
+    }
+
+    let user_permissions = user_permissions(
+        ctx.discord(),
+        ctx.guild_id(),
+        ctx.channel_id(),
+        ctx.author().id,
+    )
+    .await;
+    match user_permissions {
+        Some(perms) => required_permissions - perms,
+        // better safe than sorry: when perms are unknown, restrict access
+        None => required_permissions,
+    }
+}
 
-    true
+/// Creates a tracing span to wrap a command invocation in. The `command.args` field is empty and
+/// may be recorded by the caller
+#[cfg(feature = "tracing")]
+fn invocation_span<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    command_name: &str,
+    invocation_kind: &'static str,
+) -> tracing::Span {
+    tracing::info_span!(
+        "command",
+        command.name = command_name,
+        user.id = ctx.author().id.0,
+        guild.id = ?ctx.guild_id().map(|id| id.0),
+        channel.id = ctx.channel_id().0,
+        invocation.kind = invocation_kind,
+        command.args = tracing::field::Empty,
+    )
 }
 
 async fn check_missing_bot_permissions<U, E>(
@@ -120,10 +206,18 @@ pub async fn dispatch_event<U, E>(
             }
         }
         crate::Event::Message { new_message } => {
-            if let Err(Some((err, ctx))) =
-                prefix::dispatch_message(framework, &ctx, new_message, false, false).await
+            let runtime_commands = framework.runtime_prefix_commands();
+            if let Err(Some((err, ctx))) = prefix::dispatch_message(
+                framework,
+                &ctx,
+                new_message,
+                &runtime_commands,
+                false,
+                false,
+            )
+            .await
             {
-                if let Some(on_error) = ctx.command.options.on_error {
+                if let Some(on_error) = &ctx.command.options.on_error {
                     (on_error)(err, ctx).await;
                 } else {
                     (framework.options.on_error)(
@@ -136,15 +230,61 @@ pub async fn dispatch_event<U, E>(
         }
         crate::Event::MessageUpdate { event, .. } => {
             if let Some(edit_tracker) = &framework.options.prefix_options.edit_tracker {
-                let msg = edit_tracker.write().unwrap().process_message_update(
-                    event,
-                    framework.options().prefix_options.ignore_edit_tracker_cache,
-                );
-
-                if let Some((msg, previously_tracked)) = msg {
-                    if let Err(Some((err, ctx))) =
-                        prefix::dispatch_message(framework, &ctx, &msg, true, previously_tracked)
+                let on_cache_miss = framework.options.prefix_options.on_edit_tracker_cache_miss;
+                let (msg, cache_miss) = {
+                    let mut edit_tracker = edit_tracker.write().unwrap();
+                    // Edits of the bot's own messages are skipped regardless of
+                    // execute_self_messages, because edit tracking could loop on them
+                    let bot_id = ctx.cache.current_user_id();
+                    let author_id = event.author.as_ref().map(|author| author.id);
+                    if edit_tracker.is_own_edit(event.id, author_id, bot_id) {
+                        (None, false)
+                    } else if on_cache_miss.is_some() && !edit_tracker.is_tracked(event.id) {
+                        (None, true)
+                    } else {
+                        let msg = edit_tracker.process_message_update(
+                            event,
+                            framework.options().prefix_options.ignore_edit_tracker_cache,
+                        );
+                        (msg, false)
+                    }
+                };
+
+                // The callback runs outside of the lock, because it may fetch the message via HTTP
+                let msg = match on_cache_miss {
+                    Some(on_cache_miss) if cache_miss => {
+                        on_cache_miss(&ctx, event, framework.get_user_data().await)
                             .await
+                            .map(|msg| (msg, false))
+                    }
+                    _ => msg,
+                };
+
+                let coalesced_edit = match &msg {
+                    Some((msg, _)) => edit_tracker.write().unwrap().register_edit(msg.id),
+                    None => None,
+                };
+
+                // Wait for further edits; if one arrives, it takes over re-running the command
+                let is_latest_edit = match (&msg, coalesced_edit) {
+                    (Some((msg, _)), Some((min_edit_interval, edit))) => {
+                        tokio::time::sleep(min_edit_interval).await;
+                        edit_tracker.read().unwrap().is_latest_edit(msg.id, edit)
+                    }
+                    _ => true,
+                };
+
+                if let (Some((msg, previously_tracked)), true) = (msg, is_latest_edit) {
+                    let runtime_commands = framework.runtime_prefix_commands();
+                    if let Err(Some((err, ctx))) = prefix::dispatch_message(
+                        framework,
+                        &ctx,
+                        &msg,
+                        &runtime_commands,
+                        true,
+                        previously_tracked,
+                    )
+                    .await
                     {
                         (framework.options.on_error)(
                             err,
@@ -155,6 +295,21 @@ pub async fn dispatch_event<U, E>(
                 }
             }
         }
+        crate::Event::MessageDelete {
+            deleted_message_id, ..
+        } => {
+            if let Some(edit_tracker) = &framework.options.prefix_options.edit_tracker {
+                let bot_responses = edit_tracker
+                    .write()
+                    .unwrap()
+                    .process_message_delete_with_chunks(*deleted_message_id);
+                for bot_response in bot_responses {
+                    if let Err(e) = bot_response.delete(&ctx).await {
+                        println!("Failed to delete bot response: {}", e);
+                    }
+                }
+            }
+        }
         crate::Event::InteractionCreate {
             interaction: serenity::Interaction::ApplicationCommand(interaction),
         } => {
@@ -166,7 +321,7 @@ pub async fn dispatch_event<U, E>(
             )
             .await
             {
-                if let Some(on_error) = error_ctx.ctx.command.options().on_error {
+                if let Some(on_error) = &error_ctx.ctx.command.options().on_error {
                     on_error(e, error_ctx).await;
                 } else {
                     (framework.options.on_error)(
@@ -190,7 +345,7 @@ pub async fn dispatch_event<U, E>(
             )
             .await
             {
-                if let Some(on_error) = error_ctx.ctx.command.options().on_error {
+                if let Some(on_error) = &error_ctx.ctx.command.options().on_error {
                     on_error(e, error_ctx).await;
                 } else {
                     (framework.options.on_error)(e, crate::ErrorContext::Autocomplete(error_ctx))
@@ -198,6 +353,23 @@ pub async fn dispatch_event<U, E>(
                 }
             }
         }
+        crate::Event::InteractionCreate {
+            interaction: serenity::Interaction::MessageComponent(interaction),
+        } => {
+            // Components without a registered handler may be handled by collectors instead
+            if let Some(handler) = framework.component_handler(&interaction.data.custom_id) {
+                let component_ctx = crate::ComponentContext {
+                    discord: &ctx,
+                    interaction,
+                    framework,
+                    data: framework.get_user_data().await,
+                };
+                if let Err(e) = handler(component_ctx).await {
+                    (framework.options.on_error)(e, crate::ErrorContext::Component(component_ctx))
+                        .await;
+                }
+            }
+        }
         _ => {}
     }
 
@@ -209,3 +381,23 @@ pub async fn dispatch_event<U, E>(
         (framework.options.on_error)(e, crate::ErrorContext::Listener(&event)).await;
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_resolve_check_order() {
+        use crate::CheckKind::*;
+
+        assert_eq!(resolve_check_order(&[]), crate::CheckKind::DEFAULT_ORDER);
+        assert_eq!(
+            resolve_check_order(crate::CheckKind::DEFAULT_ORDER),
+            crate::CheckKind::DEFAULT_ORDER
+        );
+        assert_eq!(
+            resolve_check_order(&[Cooldown, OwnerOnly, Cooldown]),
+            [Cooldown, OwnerOnly, GuildOnly, Permissions, CustomCheck]
+        );
+    }
+}
@@ -1,72 +1,319 @@
 use crate::serenity_prelude as serenity;
 
-// Returns tuple of stripped prefix and rest of the message, if any prefix matches
-async fn strip_prefix<'a, U, E>(
+/// Checks whether the content starts with the given literal prefix, optionally comparing ASCII
+/// characters case-insensitively
+fn starts_with_prefix(content: &str, prefix: &str, case_insensitive: bool) -> bool {
+    if case_insensitive {
+        content
+            .get(..prefix.len())
+            .map_or(false, |head| head.eq_ignore_ascii_case(prefix))
+    } else {
+        content.starts_with(prefix)
+    }
+}
+
+/// Strips the longest of the given literal prefixes off the message content. Returns a tuple of
+/// the stripped prefix, as written in the message, and the rest of the message.
+///
+/// Each prefix is given along with whether it should be compared case-insensitively. If multiple
+/// prefixes of the same length match, the first one wins.
+fn strip_longest_literal_prefix<'a, 'b>(
+    content: &'a str,
+    prefixes: impl Iterator<Item = (&'b str, bool)>,
+) -> Option<(&'a str, &'a str)> {
+    let (longest_match, _) = prefixes
+        .filter(|&(prefix, case_insensitive)| starts_with_prefix(content, prefix, case_insensitive))
+        // Unlike max_by_key, min_by_key returns the first element if several are equal
+        .min_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()))?;
+    Some(content.split_at(longest_match.len()))
+}
+
+/// Retrieves the [dynamic prefix](crate::PrefixFrameworkOptions::dynamic_prefix) for the given
+/// message, going through the [cache](crate::PrefixFrameworkOptions::dynamic_prefix_cache) if
+/// configured. Returns None if no dynamic prefix is configured.
+async fn get_dynamic_prefix<'a, U, E>(
     framework: &'a crate::Framework<U, E>,
     ctx: &'a serenity::Context,
     msg: &'a serenity::Message,
-) -> Option<(&'a str, &'a str)> {
-    if let Some(dynamic_prefix) = framework.options.prefix_options.dynamic_prefix {
-        if let Some(prefix) = dynamic_prefix(ctx, msg, framework.get_user_data().await).await {
-            if msg.content.starts_with(&prefix) {
-                return Some(msg.content.split_at(prefix.len()));
+) -> Option<std::borrow::Cow<'a, str>> {
+    let dynamic_prefix = framework.options.prefix_options.dynamic_prefix?;
+    let cache = &framework.options.prefix_options.dynamic_prefix_cache;
+    match (cache, msg.guild_id) {
+        (Some(cache), Some(guild_id)) => match cache.get(guild_id) {
+            Some(prefix) => prefix.map(std::borrow::Cow::Owned),
+            None => {
+                let prefix = dynamic_prefix(ctx, msg, framework.get_user_data().await).await;
+                cache.insert(guild_id, prefix.as_deref().map(String::from));
+                prefix
             }
-        }
+        },
+        _ => dynamic_prefix(ctx, msg, framework.get_user_data().await).await,
     }
+}
 
-    if let Some(prefix) = &framework.options.prefix_options.prefix {
-        if let Some(content) = msg.content.strip_prefix(prefix) {
-            return Some((prefix, content));
-        }
+/// Strips the static prefixes, i.e. [`crate::PrefixFrameworkOptions::prefix`],
+/// [`crate::PrefixFrameworkOptions::literal_prefixes`] and
+/// [`crate::PrefixFrameworkOptions::additional_prefixes`], off the message content
+fn strip_static_prefix<'a, U, E>(
+    options: &crate::PrefixFrameworkOptions<U, E>,
+    content: &'a str,
+) -> Option<(&'a str, &'a str)> {
+    let case_insensitive = options.case_insensitive_prefixes;
+
+    // Literal prefixes are checked before regex prefixes. The main prefix is treated as the
+    // first literal prefix
+    let literal_prefixes = options
+        .prefix
+        .iter()
+        .chain(&options.literal_prefixes)
+        .map(|prefix| (prefix.as_str(), case_insensitive))
+        .chain(
+            options
+                .additional_prefixes
+                .iter()
+                .filter_map(|prefix| match prefix {
+                    crate::Prefix::Literal(prefix) => Some((*prefix, case_insensitive)),
+                    crate::Prefix::OwnedLiteral(prefix) => {
+                        Some((prefix.as_str(), case_insensitive))
+                    }
+                    crate::Prefix::CaseInsensitiveLiteral(prefix) => Some((*prefix, true)),
+                    crate::Prefix::Regex(_) => None,
+                }),
+        );
+    if let Some((prefix, content)) = strip_longest_literal_prefix(content, literal_prefixes) {
+        return Some((prefix, content));
     }
 
-    if let Some((prefix, content)) = framework
-        .options
-        .prefix_options
+    options
         .additional_prefixes
         .iter()
         .find_map(|prefix| match prefix {
-            &crate::Prefix::Literal(prefix) => Some((prefix, msg.content.strip_prefix(prefix)?)),
+            crate::Prefix::Literal(_)
+            | crate::Prefix::OwnedLiteral(_)
+            | crate::Prefix::CaseInsensitiveLiteral(_) => None,
             crate::Prefix::Regex(prefix) => {
-                let regex_match = prefix.find(&msg.content)?;
+                let regex_match = prefix.find(content)?;
                 if regex_match.start() == 0 {
-                    Some(msg.content.split_at(regex_match.end()))
+                    Some(content.split_at(regex_match.end()))
                 } else {
                     None
                 }
             }
         })
-    {
-        return Some((prefix, content));
+}
+
+/// Checks whether the whitespace between the stripped prefix and the rest of the message conforms
+/// to [`crate::PrefixFrameworkOptions::require_space_after_prefix`]. Returns the rest of the
+/// message if so.
+///
+/// Bot mentions and omitted prefixes in DMs are always accepted
+fn check_prefix_separator<'a>(
+    prefix: &str,
+    content: &'a str,
+    require_space: Option<bool>,
+) -> Option<&'a str> {
+    let is_mention = is_mention_prefix(prefix);
+    let separator_matches = match require_space {
+        None => true,
+        // Whitespace at the end of the prefix itself, like in "hey bot ", counts as well
+        Some(true) => {
+            prefix.ends_with(char::is_whitespace) || content.starts_with(char::is_whitespace)
+        }
+        Some(false) => !content.starts_with(char::is_whitespace),
+    };
+
+    if separator_matches || is_mention || prefix.is_empty() {
+        Some(content)
+    } else {
+        None
+    }
+}
+
+/// Whether the stripped prefix is a mention of the bot, see [`strip_mention_prefix`]
+fn is_mention_prefix(prefix: &str) -> bool {
+    prefix.starts_with("<@") && prefix.ends_with('>')
+}
+
+/// Decides between the prefix sources, in order of precedence:
+/// 1. the return value of [`crate::PrefixFrameworkOptions::stripped_dynamic_prefix`], if Some
+/// 2. the return value of [`crate::PrefixFrameworkOptions::dynamic_prefix`], if Some. The static
+///    prefixes are not considered then, even if the dynamic prefix doesn't match
+/// 3. the static prefixes
+fn apply_prefix_precedence<'a>(
+    content: &'a str,
+    stripped_dynamic_prefix: Option<(&'a str, &'a str)>,
+    dynamic_prefix: Option<&str>,
+    strip_static_prefix: impl FnOnce() -> Option<(&'a str, &'a str)>,
+    case_insensitive: bool,
+) -> Option<(&'a str, &'a str)> {
+    if stripped_dynamic_prefix.is_some() {
+        return stripped_dynamic_prefix;
     }
 
-    if let Some(dynamic_prefix) = framework.options.prefix_options.stripped_dynamic_prefix {
-        if let Some((prefix, content)) =
-            dynamic_prefix(ctx, msg, framework.get_user_data().await).await
-        {
-            return Some((prefix, content));
+    match dynamic_prefix {
+        Some(prefix) => {
+            if starts_with_prefix(content, prefix, case_insensitive) {
+                Some(content.split_at(prefix.len()))
+            } else {
+                None
+            }
+        }
+        None => strip_static_prefix(),
+    }
+}
+
+/// Strips a mention of the bot from the start of the message. Returns the mention and the rest of
+/// the message, which may start with whitespace
+fn strip_mention_prefix(content: &str, bot_id: serenity::UserId) -> Option<(&str, &str)> {
+    // Mentions are either <@USER_ID> or, for nickname mentions, <@!USER_ID>
+    let rest = content.strip_prefix("<@")?;
+    let rest = rest.strip_prefix('!').unwrap_or(rest);
+    let rest = rest
+        .strip_prefix(&bot_id.0.to_string())?
+        .strip_prefix('>')?;
+    Some(content.split_at(content.len() - rest.len()))
+}
+
+/// Whether the message content mentions the bot anywhere. Unlike [`serenity::Message::mentions`],
+/// this doesn't include the implicit mention of a reply ping
+fn mentions_bot(content: &str, bot_id: serenity::UserId) -> bool {
+    content.contains(&format!("<@{}>", bot_id.0)) || content.contains(&format!("<@!{}>", bot_id.0))
+}
+
+// Returns tuple of stripped prefix and rest of the message, if any prefix matches
+async fn strip_prefix<'a, U, E>(
+    framework: &'a crate::Framework<U, E>,
+    ctx: &'a serenity::Context,
+    msg: &'a serenity::Message,
+) -> Option<(&'a str, &'a str)> {
+    let options = &framework.options.prefix_options;
+    let content = message_content(options, msg);
+
+    let stripped_dynamic_prefix = match options.stripped_dynamic_prefix {
+        Some(stripped_dynamic_prefix) => {
+            stripped_dynamic_prefix(ctx, msg, framework.get_user_data().await).await
         }
+        None => None,
+    };
+    // The dynamic prefix would be disregarded anyways, so don't bother invoking the callback
+    let dynamic_prefix = match stripped_dynamic_prefix {
+        Some(_) => None,
+        None => get_dynamic_prefix(framework, ctx, msg).await,
+    };
+    if let Some((prefix, content)) = apply_prefix_precedence(
+        content,
+        stripped_dynamic_prefix,
+        dynamic_prefix.as_deref(),
+        || strip_static_prefix(options, content),
+        options.case_insensitive_prefixes,
+    ) {
+        return Some((prefix, content));
+    }
+
+    let (mention_prefix, stripped_content) =
+        strip_mention_prefix(content, ctx.cache.current_user_id())?;
+    // A bare mention is stripped regardless, so that it can invoke the help command
+    if options.help_when_mentioned
+        && options.help_command.is_some()
+        && stripped_content.trim().is_empty()
+    {
+        return Some((mention_prefix, stripped_content));
     }
 
-    if framework.options.prefix_options.mention_as_prefix {
-        // Mentions are either <@USER_ID> or <@!USER_ID>
-        if let Some(stripped_content) = (|| {
-            msg.content
-                .strip_prefix("<@")?
-                .trim_start_matches('!')
-                .strip_prefix(&ctx.cache.current_user_id().0.to_string())?
-                .strip_prefix('>')
-        })() {
-            let mention_prefix = &msg.content[..(msg.content.len() - stripped_content.len())];
-            return Some((mention_prefix, stripped_content));
+    // The callback is only invoked for messages which actually start with a bot mention
+    let mention_as_prefix = match options.mention_as_prefix_for {
+        Some(mention_as_prefix_for) => {
+            mention_as_prefix_for(ctx, msg, framework.get_user_data().await).await
         }
+        None => options.mention_as_prefix,
+    };
+    if mention_as_prefix {
+        return Some((mention_prefix, stripped_content));
     }
 
     None
 }
 
+/// Returns the message content to match prefixes against, with leading invisible characters
+/// removed if [`crate::PrefixFrameworkOptions::trim_invisible_prefix`] is set. The returned string
+/// is always a suffix of the message content, so [`args_offset`] stays correct
+fn message_content<'a, U, E>(
+    options: &crate::PrefixFrameworkOptions<U, E>,
+    msg: &'a serenity::Message,
+) -> &'a str {
+    if options.trim_invisible_prefix {
+        trim_invisible_start(&msg.content)
+    } else {
+        &msg.content
+    }
+}
+
+/// Strips leading whitespace and zero-width or otherwise invisible characters, which some clients
+/// and copy-pasting prepend to messages
+fn trim_invisible_start(content: &str) -> &str {
+    content.trim_start_matches(|c: char| {
+        c.is_whitespace()
+            || matches!(
+                c,
+                // Zero-width space, non-joiner and joiner, word joiner, byte order mark, soft
+                // hyphen and Mongolian vowel separator
+                '\u{200B}'
+                    | '\u{200C}'
+                    | '\u{200D}'
+                    | '\u{2060}'
+                    | '\u{FEFF}'
+                    | '\u{AD}'
+                    | '\u{180E}'
+            )
+    })
+}
+
+/// Whether the message was sent in a channel or category where messages are ignored, see
+/// [`crate::PrefixFrameworkOptions::blocked_channels`]. Owners are never blocked
+fn is_channel_blocked<U, E>(
+    framework: &crate::Framework<U, E>,
+    ctx: &serenity::Context,
+    msg: &serenity::Message,
+) -> bool {
+    let options = &framework.options.prefix_options;
+    if framework.options.owners.contains(&msg.author.id) {
+        return false;
+    }
+    if options
+        .blocked_channels
+        .read()
+        .unwrap()
+        .contains(&msg.channel_id)
+    {
+        return true;
+    }
+
+    let blocked_categories = options.blocked_categories.read().unwrap();
+    if blocked_categories.is_empty() {
+        return false;
+    }
+    let guild_id = match msg.guild_id {
+        Some(x) => x,
+        None => return false,
+    };
+    let category_id = ctx
+        .cache
+        .guild_field(guild_id, |guild| {
+            match guild.channels.get(&msg.channel_id) {
+                Some(serenity::Channel::Guild(channel)) => channel.category_id,
+                _ => None,
+            }
+        })
+        .flatten();
+    match category_id {
+        Some(category_id) => blocked_categories.contains(&category_id),
+        None => false,
+    }
+}
+
 /// Find a command within nested PrefixCommandMeta's by the user message string. Also returns
-/// the arguments, i.e. the remaining string.
+/// the command's path (see [`crate::PrefixContext::invoked_command_path`]), the arguments, i.e.
+/// the remaining string, and whether the invocation is exempt from the command's cooldown.
 ///
 /// May throw an error if a command check fails
 fn find_command<'a, U, E>(
@@ -75,11 +322,14 @@ fn find_command<'a, U, E>(
     msg: &'a serenity::Message,
     prefix: &'a str,
     commands: &'a [crate::PrefixCommandMeta<U, E>],
+    parent_path: &'static [&'static str],
     remaining_message: &'a str,
+    triggered_by_edit: bool,
+    is_subcommand: bool,
 ) -> crate::BoxFuture<
     'a,
     Result<
-        Option<(&'a crate::PrefixCommandMeta<U, E>, &'a str)>,
+        Option<(FoundCommand<'a, U, E>, &'a str, bool)>,
         (E, crate::PrefixCommandErrorContext<'a, U, E>),
     >,
 >
@@ -92,144 +342,876 @@ where
         msg,
         prefix,
         commands,
+        parent_path,
         remaining_message,
+        triggered_by_edit,
+        is_subcommand,
     ))
 }
 
+/// A command found by dispatch, along with its path (see
+/// [`crate::PrefixContext::invoked_command_path`])
+type FoundCommand<'a, U, E> = (&'a crate::PrefixCommandMeta<U, E>, &'static [&'static str]);
+
+/// Searches the command tree for the given command and returns the names of its parent commands
+/// followed by its own name. Commands that aren't part of the tree, for example ones returned by
+/// [`crate::PrefixFrameworkOptions::custom_dispatch`], only report their own name
+fn locate_command_path<U, E>(
+    framework: &crate::Framework<U, E>,
+    runtime_commands: &[std::sync::Arc<crate::PrefixCommandMeta<U, E>>],
+    command_meta: &crate::PrefixCommandMeta<U, E>,
+) -> &'static [&'static str] {
+    fn search<U, E>(
+        commands: &[crate::PrefixCommandMeta<U, E>],
+        needle: &crate::PrefixCommandMeta<U, E>,
+        path: &mut Vec<&'static str>,
+    ) -> bool {
+        for command_meta in commands {
+            path.push(command_meta.command.name);
+            if std::ptr::eq(command_meta, needle) || search(&command_meta.subcommands, needle, path)
+            {
+                return true;
+            }
+            path.pop();
+        }
+        false
+    }
+
+    let options = &framework.options.prefix_options;
+    let mut path = Vec::new();
+    let found = search(&options.commands, command_meta, &mut path)
+        || options.help_command.as_ref().map_or(false, |help_command| {
+            search(std::slice::from_ref(help_command), command_meta, &mut path)
+        })
+        || runtime_commands.iter().any(|runtime_command| {
+            search(
+                std::slice::from_ref(&**runtime_command),
+                command_meta,
+                &mut path,
+            )
+        });
+    if !found {
+        path = vec![command_meta.command.name];
+    }
+    crate::util::intern_command_path(&path)
+}
+
+/// Returns the byte index in the message content where the given arguments start. The arguments
+/// are always the remaining end of the message content.
+fn args_offset(msg: &serenity::Message, args: &str) -> usize {
+    msg.content.len().saturating_sub(args.len())
+}
+
+/// How an invoked command name matched a command. Ordered by precedence in dispatch, i.e. a
+/// match of the command name beats a match of an alias
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum NameMatch {
+    Name,
+    Alias,
+}
+
+/// Checks whether the invoked command name refers to a command with the given name and aliases.
+/// Aliases are subject to the same case sensitivity as the primary name.
+fn command_name_matches(
+    name: &str,
+    aliases: &[&str],
+    invoked_name: &str,
+    case_insensitive: bool,
+    normalizer: Option<fn(&str) -> std::borrow::Cow<'_, str>>,
+) -> bool {
+    name_match(name, aliases, invoked_name, case_insensitive, normalizer).is_some()
+}
+
+/// Like [`command_name_matches`], but also returns whether the name or an alias matched
+fn name_match(
+    name: &str,
+    aliases: &[&str],
+    invoked_name: &str,
+    case_insensitive: bool,
+    normalizer: Option<fn(&str) -> std::borrow::Cow<'_, str>>,
+) -> Option<NameMatch> {
+    let considered_equal = if case_insensitive {
+        |a: &str, b: &str| a.eq_ignore_ascii_case(b)
+    } else {
+        |a: &str, b: &str| a == b
+    };
+
+    let invoked_name = normalize_command_name(invoked_name, normalizer);
+    let matches =
+        |name: &str| considered_equal(&normalize_command_name(name, normalizer), &invoked_name);
+    if matches(name) {
+        Some(NameMatch::Name)
+    } else if aliases.iter().any(|&alias| matches(alias)) {
+        Some(NameMatch::Alias)
+    } else {
+        None
+    }
+}
+
+/// Orders the commands which matched an invoked name, given as `(how it matched, priority)` or
+/// None if it didn't match, in the order in which dispatch tries them: name matches before alias
+/// matches, then higher [priority](crate::PrefixCommandOptions::priority) first, then
+/// registration order. Returns the indices of the matching commands
+fn resolution_order(matches: &[Option<(NameMatch, i32)>]) -> Vec<usize> {
+    let mut order = matches
+        .iter()
+        .enumerate()
+        .filter_map(|(i, &m)| m.map(|(kind, priority)| (kind, std::cmp::Reverse(priority), i)))
+        .collect::<Vec<_>>();
+    order.sort();
+    order.into_iter().map(|(_, _, i)| i).collect()
+}
+
+/// Returns the commands which are invoked by the given name, in the order in which dispatch tries
+/// them (see [`resolution_order`])
+fn matching_commands<'a, U, E>(
+    options: &crate::PrefixFrameworkOptions<U, E>,
+    commands: &'a [crate::PrefixCommandMeta<U, E>],
+    invoked_name: &str,
+) -> impl Iterator<Item = &'a crate::PrefixCommandMeta<U, E>> {
+    let matches = commands
+        .iter()
+        .map(|command_meta| {
+            let command = &command_meta.command;
+            let name_match = name_match(
+                command.name,
+                command.options.aliases,
+                invoked_name,
+                options.case_insensitive_commands,
+                options.command_name_normalizer,
+            );
+            name_match.map(|name_match| (name_match, command.options.priority))
+        })
+        .collect::<Vec<_>>();
+    resolution_order(&matches)
+        .into_iter()
+        .map(move |i| &commands[i])
+}
+
+/// Resolves a command by its names along the subcommand tree, like `["config", "set"]`, using the
+/// same name matching as dispatch. Returns None if the path is empty or any segment doesn't match
+pub(crate) fn find_command_by_path<'a, U, E>(
+    options: &crate::PrefixFrameworkOptions<U, E>,
+    commands: &'a [crate::PrefixCommandMeta<U, E>],
+    path: &[&str],
+) -> Option<&'a crate::PrefixCommand<U, E>> {
+    let (name, remaining_path) = path.split_first()?;
+    let command_meta = matching_commands(options, commands, name).next()?;
+
+    if remaining_path.is_empty() {
+        Some(&command_meta.command)
+    } else {
+        find_command_by_path(options, &command_meta.subcommands, remaining_path)
+    }
+}
+
+/// Searches the command tree for two different commands on the same level which are invoked by
+/// the same name, or by the same alias, and have the same priority. Dispatch couldn't tell such
+/// commands apart, so the second one would be silently shadowed by the first one.
+///
+/// Returns a description of the first conflict found. Entries which share their
+/// [`crate::CommandId`] instance belong to the same command and never conflict. A name of one
+/// command which is an alias of another is no conflict, because the name takes precedence.
+pub(crate) fn find_command_name_conflict<U, E>(
+    options: &crate::PrefixFrameworkOptions<U, E>,
+    commands: &[crate::PrefixCommandMeta<U, E>],
+    parent_path: &[&str],
+) -> Option<String> {
+    let names = commands
+        .iter()
+        .map(|command_meta| {
+            let command = &command_meta.command;
+            let id = std::sync::Arc::as_ptr(&command.id) as *const ();
+            (
+                command.name,
+                command.options.aliases,
+                command.options.priority,
+                id,
+            )
+        })
+        .collect::<Vec<_>>();
+    if let Some((a, b, name)) = find_name_conflict(
+        &names,
+        options.case_insensitive_commands,
+        options.command_name_normalizer,
+    ) {
+        let path = |command_meta: &crate::PrefixCommandMeta<U, E>| {
+            let mut path = parent_path.to_vec();
+            path.push(command_meta.command.name);
+            path.join(" ")
+        };
+        return Some(format!(
+            "prefix commands `{}` and `{}` are both invoked by the name `{}`",
+            path(&commands[a]),
+            path(&commands[b]),
+            name
+        ));
+    }
+
+    commands.iter().find_map(|command_meta| {
+        let mut path = parent_path.to_vec();
+        path.push(command_meta.command.name);
+        find_command_name_conflict(options, &command_meta.subcommands, &path)
+    })
+}
+
+/// Checks whether a command added via [`crate::Framework::add_prefix_command`] would be shadowed
+/// by one of the already registered static or runtime commands. Dispatch tries the runtime commands
+/// one after another after the static commands, so unlike in [`find_command_name_conflict`],
+/// neither priorities nor names taking precedence over aliases resolve conflicts here. The
+/// subcommands of the new command are checked like static commands.
+///
+/// Returns a description of the first conflict found.
+pub(crate) fn find_runtime_command_conflict<U, E>(
+    options: &crate::PrefixFrameworkOptions<U, E>,
+    runtime_commands: &[std::sync::Arc<crate::PrefixCommandMeta<U, E>>],
+    new_command: &crate::PrefixCommandMeta<U, E>,
+) -> Option<String> {
+    let new_command_name = new_command.command.name;
+    let new_names = std::iter::once(&new_command_name).chain(new_command.command.options.aliases);
+    let existing_commands = options
+        .commands
+        .iter()
+        .chain(runtime_commands.iter().map(|command_meta| &**command_meta));
+    for command_meta in existing_commands {
+        let command = &command_meta.command;
+        let shared_name = new_names.clone().find(|name| {
+            command_name_matches(
+                command.name,
+                command.options.aliases,
+                name,
+                options.case_insensitive_commands,
+                options.command_name_normalizer,
+            )
+        });
+        if let Some(name) = shared_name {
+            return Some(format!(
+                "prefix commands `{}` and `{}` are both invoked by the name `{}`",
+                command.name, new_command_name, name
+            ));
+        }
+    }
+
+    find_command_name_conflict(options, &new_command.subcommands, &[new_command_name])
+}
+
+/// Finds two commands with different IDs and equal priority, given as
+/// `(name, aliases, priority, id)`, which are both invoked by the same name or both by the same
+/// alias. Returns the indices of both commands and the shared name, as spelled by the second
+/// command
+fn find_name_conflict<'a>(
+    commands: &[(&'a str, &'a [&'a str], i32, *const ())],
+    case_insensitive: bool,
+    normalizer: Option<fn(&str) -> std::borrow::Cow<'_, str>>,
+) -> Option<(usize, usize, &'a str)> {
+    let same_name =
+        |a: &str, b: &str| command_name_matches(a, &[], b, case_insensitive, normalizer);
+    for (b, &(name_b, aliases_b, priority_b, id_b)) in commands.iter().enumerate() {
+        for (a, &(name_a, aliases_a, priority_a, id_a)) in commands[..b].iter().enumerate() {
+            if id_a == id_b || priority_a != priority_b {
+                continue;
+            }
+            if same_name(name_a, name_b) {
+                return Some((a, b, name_b));
+            }
+            let shared_alias = aliases_b
+                .iter()
+                .find(|&&alias_b| aliases_a.iter().any(|&alias_a| same_name(alias_a, alias_b)));
+            if let Some(&shared_alias) = shared_alias {
+                return Some((a, b, shared_alias));
+            }
+        }
+    }
+    None
+}
+
+/// Applies the [command name normalizer](crate::PrefixFrameworkOptions::command_name_normalizer),
+/// if any
+fn normalize_command_name(
+    name: &str,
+    normalizer: Option<fn(&str) -> std::borrow::Cow<'_, str>>,
+) -> std::borrow::Cow<'_, str> {
+    match normalizer {
+        Some(normalizer) => normalizer(name),
+        None => std::borrow::Cow::Borrowed(name),
+    }
+}
+
+/// Runs [`crate::PrefixFrameworkOptions::command_enabled`] and the checks of the given command
+/// (see [`crate::CheckKind`]). Returns None if the command may not run, in which case the handler
+/// of the failed check and [`crate::FrameworkOptions::post_command`] were called already.
+/// Otherwise returns whether the invocation is exempt from the command's cooldown (see
+/// [`crate::FrameworkOptions::cooldown_exempt`]).
+///
+/// `command_enabled` and the global [`crate::FrameworkOptions::command_check`] only run once per
+/// invocation, for the top-level command. So they are skipped if `is_subcommand` is true.
+async fn check_command<'a, U, E>(
+    framework: &'a crate::Framework<U, E>,
+    ctx: crate::PrefixContext<'a, U, E>,
+    command: &'a crate::PrefixCommand<U, E>,
+    is_subcommand: bool,
+) -> Result<Option<bool>, (E, crate::PrefixCommandErrorContext<'a, U, E>)> {
+    let checks_result = run_checks(framework, ctx, command, is_subcommand).await;
+    if !matches!(checks_result, Ok(Some(_))) {
+        let outcome = crate::CommandOutcome::Rejected;
+        (framework.options.post_command)(crate::Context::Prefix(ctx), outcome).await;
+    }
+    checks_result
+}
+
+/// The checks of [`check_command`], without calling the post command hook on rejection
+async fn run_checks<'a, U, E>(
+    framework: &'a crate::Framework<U, E>,
+    ctx: crate::PrefixContext<'a, U, E>,
+    command: &'a crate::PrefixCommand<U, E>,
+    is_subcommand: bool,
+) -> Result<Option<bool>, (E, crate::PrefixCommandErrorContext<'a, U, E>)> {
+    // Disabled commands are skipped before any checks run
+    let command_enabled = framework.options.prefix_options.command_enabled;
+    if let (Some(command_enabled), false) = (command_enabled, is_subcommand) {
+        if !command_enabled(ctx.into(), command).await {
+            if let Some(callback) = framework.options.prefix_options.command_disabled_handler {
+                callback(ctx).await.map_err(|e| {
+                    (
+                        e,
+                        crate::PrefixCommandErrorContext {
+                            ctx,
+                            command,
+                            location: crate::CommandErrorLocation::CommandDisabledCallback,
+                        },
+                    )
+                })?;
+            }
+            return Ok(None);
+        }
+    }
+
+    // Run the checks in the configured order. The first failing check stops the evaluation
+    let mut cooldown_exempt = false;
+    for check_kind in super::resolve_check_order(framework.options.check_order) {
+        match check_kind {
+            crate::CheckKind::OwnerOnly => {
+                // Make sure that owners_only commands are only invoked by owners
+                if !super::check_owners_only(ctx.into(), command.id.owners_only) {
+                    if let Some(callback) = framework.options.not_an_owner_handler {
+                        callback(ctx.into()).await.map_err(|e| {
+                            (
+                                e,
+                                crate::PrefixCommandErrorContext {
+                                    ctx,
+                                    command,
+                                    location: crate::CommandErrorLocation::NotAnOwnerCallback,
+                                },
+                            )
+                        })?;
+                    }
+                    return Ok(None);
+                }
+            }
+            crate::CheckKind::GuildOnly => {
+                // Make sure that guild_only commands aren't invoked in DMs
+                if !super::check_guild_only(ctx.into(), command.id.guild_only) {
+                    if let Some(callback) = framework.options.guild_only_handler {
+                        callback(ctx.into()).await.map_err(|e| {
+                            (
+                                e,
+                                crate::PrefixCommandErrorContext {
+                                    ctx,
+                                    command,
+                                    location: crate::CommandErrorLocation::GuildOnlyCallback,
+                                },
+                            )
+                        })?;
+                    }
+                    return Ok(None);
+                }
+
+                // Make sure that dm_only commands aren't invoked in guilds
+                if !super::check_dm_only(ctx.into(), command.id.dm_only) {
+                    if let Some(callback) = framework.options.dm_only_handler {
+                        callback(ctx.into()).await.map_err(|e| {
+                            (
+                                e,
+                                crate::PrefixCommandErrorContext {
+                                    ctx,
+                                    command,
+                                    location: crate::CommandErrorLocation::DmOnlyCallback,
+                                },
+                            )
+                        })?;
+                    }
+                    return Ok(None);
+                }
+            }
+            crate::CheckKind::Permissions => {
+                // Make sure that user has required permissions
+                let missing_user_permissions = super::check_missing_user_permissions(
+                    ctx.into(),
+                    command.id.required_permissions,
+                )
+                .await;
+                if !missing_user_permissions.is_empty() {
+                    if let Some(callback) = framework.options.missing_user_permissions_handler {
+                        callback(ctx.into(), missing_user_permissions)
+                            .await
+                            .map_err(|e| {
+                                (
+                                    e,
+                                    crate::PrefixCommandErrorContext {
+                                        ctx,
+                                        command,
+                                        location:
+                                            crate::CommandErrorLocation::MissingUserPermissionsCallback,
+                                    },
+                                )
+                            })?;
+                    }
+                    return Ok(None);
+                }
+
+                // Make sure the bot has the permissions it needs
+                let missing_bot_permissions = super::check_missing_bot_permissions(
+                    ctx.into(),
+                    command.id.required_bot_permissions,
+                )
+                .await;
+                if !missing_bot_permissions.is_empty() {
+                    (ctx.framework.options().missing_bot_permissions_handler)(
+                        ctx.into(),
+                        missing_bot_permissions,
+                    )
+                    .await
+                    .map_err(|e| {
+                        (
+                            e,
+                            crate::PrefixCommandErrorContext {
+                                ctx,
+                                command,
+                                location:
+                                    crate::CommandErrorLocation::MissingBotPermissionsCallback,
+                            },
+                        )
+                    })?;
+                    return Ok(None);
+                }
+            }
+            crate::CheckKind::Cooldown => {
+                // The cooldown is only started in dispatch_message, for the invoked command
+                cooldown_exempt = super::check_cooldown_exempt(ctx.into()).await;
+                if !cooldown_exempt {
+                    let cooldowns = &command.id.cooldowns;
+                    let cooldown_left = cooldowns.lock().unwrap().get_wait_time(ctx.into());
+                    if let Some(cooldown_left) = cooldown_left {
+                        if let Some(callback) = framework.options.cooldown_hit {
+                            callback(ctx.into(), cooldown_left).await.map_err(|e| {
+                                (
+                                    e,
+                                    crate::PrefixCommandErrorContext {
+                                        ctx,
+                                        command,
+                                        location: crate::CommandErrorLocation::CooldownCallback,
+                                    },
+                                )
+                            })?;
+                        }
+                        return Ok(None);
+                    }
+                }
+            }
+            crate::CheckKind::CustomCheck => {
+                // Only continue if the global check and then the command specific check return true
+                let check_error = |e| {
+                    (
+                        e,
+                        crate::PrefixCommandErrorContext {
+                            command,
+                            ctx,
+                            location: crate::CommandErrorLocation::Check,
+                        },
+                    )
+                };
+                if let (Some(check), false) = (framework.options.command_check, is_subcommand) {
+                    if !check(crate::Context::Prefix(ctx))
+                        .await
+                        .map_err(check_error)?
+                    {
+                        if let Some(callback) = framework.options.on_global_check_failure {
+                            callback(crate::Context::Prefix(ctx)).await.map_err(|e| {
+                                (
+                                    e,
+                                    crate::PrefixCommandErrorContext {
+                                        command,
+                                        ctx,
+                                        location:
+                                            crate::CommandErrorLocation::GlobalCheckFailureCallback,
+                                    },
+                                )
+                            })?;
+                        }
+                        return Ok(None);
+                    }
+                }
+                if let Some(check) = command.options.check {
+                    if !check(ctx).await.map_err(check_error)? {
+                        return Ok(None);
+                    }
+                }
+
+                if let Some(check) = command.options.check_with_reason {
+                    let check_result = check(ctx).await.map_err(|e| {
+                        (
+                            e,
+                            crate::PrefixCommandErrorContext {
+                                command,
+                                ctx,
+                                location: crate::CommandErrorLocation::Check,
+                            },
+                        )
+                    })?;
+                    if let crate::CheckResult::Reject { reason, silent } = check_result {
+                        if let (Some(callback), false) =
+                            (framework.options.on_check_failure, silent)
+                        {
+                            callback(ctx.into(), reason).await.map_err(|e| {
+                                (
+                                    e,
+                                    crate::PrefixCommandErrorContext {
+                                        command,
+                                        ctx,
+                                        location: crate::CommandErrorLocation::CheckFailureCallback,
+                                    },
+                                )
+                            })?;
+                        }
+                        return Ok(None);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(Some(cooldown_exempt))
+}
+
 async fn _find_command<'a, U, E>(
     framework: &'a crate::Framework<U, E>,
     ctx: &'a serenity::Context,
     msg: &'a serenity::Message,
     prefix: &'a str,
     commands: &'a [crate::PrefixCommandMeta<U, E>],
+    parent_path: &'static [&'static str],
     remaining_message: &'a str,
+    triggered_by_edit: bool,
+    is_subcommand: bool,
 ) -> Result<
-    Option<(&'a crate::PrefixCommandMeta<U, E>, &'a str)>,
+    Option<(FoundCommand<'a, U, E>, &'a str, bool)>,
     (E, crate::PrefixCommandErrorContext<'a, U, E>),
 >
 where
     U: Send + Sync,
 {
-    let considered_equal = if framework.options.prefix_options.case_insensitive_commands {
-        |a: &str, b: &str| a.eq_ignore_ascii_case(b)
-    } else {
-        |a: &str, b: &str| a == b
-    };
-
     let (command_name, remaining_message) = {
         let mut iter = remaining_message.splitn(2, char::is_whitespace);
         (iter.next().unwrap(), iter.next().unwrap_or("").trim_start())
     };
 
     let mut first_matching_command = None;
-    for command_meta in commands {
+    let options = &framework.options.prefix_options;
+    for command_meta in matching_commands(options, commands, command_name) {
         let command = &command_meta.command;
 
-        let primary_name_matches = considered_equal(command.name, command_name);
-        let alias_matches = command
-            .options
-            .aliases
-            .iter()
-            .any(|alias| considered_equal(alias, command_name));
-        if !primary_name_matches && !alias_matches {
+        // Messages by the bot itself only reach commands that accept them
+        let self_authored = ctx.cache.current_user_id() == msg.author.id;
+        if self_authored && !allows_self_author(framework, command) {
             continue;
         }
 
+        let mut path = parent_path.to_vec();
+        path.push(command.name);
+        let path = crate::util::intern_command_path(&path);
+
         let ctx = crate::PrefixContext {
             discord: ctx,
             msg,
             prefix,
+            args_offset: args_offset(msg, remaining_message),
             framework,
             data: framework.get_user_data().await,
             command: Some(&command_meta.command),
+            invoked_command_path: path,
+            invoked_by_edit: triggered_by_edit,
+        };
+
+        let cooldown_exempt = match check_command(framework, ctx, command, is_subcommand).await? {
+            Some(cooldown_exempt) => cooldown_exempt,
+            None => continue,
+        };
+
+        let subcommand = find_command(
+            framework,
+            ctx.discord,
+            msg,
+            prefix,
+            &command_meta.subcommands,
+            path,
+            remaining_message,
+            triggered_by_edit,
+            true,
+        )
+        .await?;
+        first_matching_command = match subcommand {
+            Some(subcommand) => Some(subcommand),
+            // A subcommand was named, but rejected the invocation. The parent must not run in its
+            // place, or the subcommand's checks could be bypassed
+            None if names_subcommand(framework, command_meta, remaining_message) => None,
+            None => Some(((command_meta, path), remaining_message, cooldown_exempt)),
         };
+        break;
+    }
+
+    Ok(first_matching_command)
+}
 
-        // Make sure that user has required permissions
-        if !super::check_required_permissions_and_owners_only(
-            crate::Context::Prefix(ctx),
-            command.id.required_permissions,
-            command.id.owners_only,
+/// Whether the first word of the arguments is the name or an alias of one of the command's
+/// subcommands
+fn names_subcommand<U, E>(
+    framework: &crate::Framework<U, E>,
+    command_meta: &crate::PrefixCommandMeta<U, E>,
+    args: &str,
+) -> bool {
+    let options = &framework.options.prefix_options;
+    let name = args.split(char::is_whitespace).next().unwrap_or("");
+    command_meta.subcommands.iter().any(|subcommand_meta| {
+        let subcommand = &subcommand_meta.command;
+        command_name_matches(
+            subcommand.name,
+            subcommand.options.aliases,
+            name,
+            options.case_insensitive_commands,
+            options.command_name_normalizer,
         )
-        .await
-        {
-            continue;
+    })
+}
+
+/// Returns up to three of the given names which are at most `max_distance` edits away from the typed
+/// name, closest first
+fn closest_names(
+    names: impl Iterator<Item = &'static str>,
+    typed_name: &str,
+    max_distance: usize,
+    case_insensitive: bool,
+    normalizer: Option<fn(&str) -> std::borrow::Cow<'_, str>>,
+) -> Vec<&'static str> {
+    let normalize = |name: &str| {
+        let name = normalize_command_name(name, normalizer);
+        if case_insensitive {
+            name.to_ascii_lowercase()
+        } else {
+            name.into_owned()
         }
+    };
+    let typed_name = normalize(typed_name);
 
-        // Before running any checks, make sure the bot has the permissions it needs
-        let missing_bot_permissions =
-            super::check_missing_bot_permissions(ctx.into(), command.id.required_bot_permissions)
-                .await;
-        if !missing_bot_permissions.is_empty() {
-            (ctx.framework.options().missing_bot_permissions_handler)(
-                ctx.into(),
-                missing_bot_permissions,
+    let mut candidates = names
+        .map(|name| {
+            let distance = crate::util::levenshtein_distance(&typed_name, &normalize(name));
+            (distance, name)
+        })
+        .filter(|&(distance, _)| distance <= max_distance)
+        .collect::<Vec<_>>();
+    // Stable sort, so equally close names stay in registration order
+    candidates.sort_by_key(|&(distance, _)| distance);
+    // The same name may be registered multiple times, for example by a command and a runtime
+    // command. Duplicates aren't necessarily adjacent, so keep the first occurrence of each name
+    let mut seen = std::collections::HashSet::new();
+    candidates.retain(|&(_, name)| seen.insert(name));
+    candidates.truncate(3);
+    candidates.into_iter().map(|(_, name)| name).collect()
+}
+
+/// Whether the typed name is the name or an alias of any top-level command
+fn matches_any_command<U, E>(
+    framework: &crate::Framework<U, E>,
+    runtime_commands: &[std::sync::Arc<crate::PrefixCommandMeta<U, E>>],
+    typed_name: &str,
+) -> bool {
+    let options = &framework.options.prefix_options;
+    options
+        .commands
+        .iter()
+        .chain(runtime_commands.iter().map(|command_meta| &**command_meta))
+        .any(|command_meta| {
+            let command = &command_meta.command;
+            command_name_matches(
+                command.name,
+                command.options.aliases,
+                typed_name,
+                options.case_insensitive_commands,
+                options.command_name_normalizer,
             )
-            .await
-            .map_err(|e| {
-                (
-                    e,
-                    crate::PrefixCommandErrorContext {
-                        ctx,
-                        command,
-                        location: crate::CommandErrorLocation::MissingBotPermissionsCallback,
-                    },
-                )
-            })?;
-            continue;
-        }
+        })
+}
 
-        // Only continue if command checks returns true
-        let checks_passing = (|| async {
-            let global_check_passes = match &framework.options.command_check {
-                Some(check) => check(crate::Context::Prefix(ctx)).await?,
-                None => true,
-            };
+/// Whether the command may be invoked by messages of the bot itself, according to
+/// [`crate::PrefixCommandOptions::allow_self_author`] or else
+/// [`crate::PrefixFrameworkOptions::execute_self_messages`]
+fn allows_self_author<U, E>(
+    framework: &crate::Framework<U, E>,
+    command: &crate::PrefixCommand<U, E>,
+) -> bool {
+    command
+        .options
+        .allow_self_author
+        .unwrap_or(framework.options.prefix_options.execute_self_messages)
+}
 
-            let command_specific_check_passes = match &command.options.check {
-                Some(check) => check(ctx).await?,
-                None => true,
-            };
+/// Whether any command, including subcommands, explicitly opts into being invoked by messages of
+/// the bot itself. Used to skip prefix parsing of the bot's own messages where possible
+fn any_command_allows_self_author<U, E>(
+    framework: &crate::Framework<U, E>,
+    runtime_commands: &[std::sync::Arc<crate::PrefixCommandMeta<U, E>>],
+) -> bool {
+    fn recurse<U, E>(commands: &[crate::PrefixCommandMeta<U, E>]) -> bool {
+        commands.iter().any(|command_meta| {
+            command_meta.command.options.allow_self_author == Some(true)
+                || recurse(&command_meta.subcommands)
+        })
+    }
 
-            Ok(global_check_passes && command_specific_check_passes)
-        })()
-        .await
-        .map_err(|e| {
-            (
-                e,
-                crate::PrefixCommandErrorContext {
-                    command,
-                    ctx,
-                    location: crate::CommandErrorLocation::Check,
-                },
-            )
-        })?;
-        if !checks_passing {
-            continue;
-        }
+    recurse(&framework.options.prefix_options.commands)
+        || runtime_commands
+            .iter()
+            .any(|command_meta| recurse(std::slice::from_ref(&**command_meta)))
+}
 
-        first_matching_command = Some(
-            match find_command(
-                framework,
-                ctx.discord,
-                msg,
-                prefix,
-                &command_meta.subcommands,
-                remaining_message,
-            )
-            .await?
-            {
-                Some((subcommand_meta, remaining_message)) => (subcommand_meta, remaining_message),
-                None => (command_meta, remaining_message),
-            },
-        );
-        break;
+/// Calls [`crate::PrefixFrameworkOptions::on_unknown_command`] if the command name in the message
+/// doesn't belong to any command. Returns whether the callback was called.
+async fn unknown_command<U, E>(
+    framework: &crate::Framework<U, E>,
+    ctx: &serenity::Context,
+    msg: &serenity::Message,
+    prefix: &str,
+    runtime_commands: &[std::sync::Arc<crate::PrefixCommandMeta<U, E>>],
+    msg_content: &str,
+) -> bool
+where
+    U: Send + Sync,
+{
+    let options = &framework.options.prefix_options;
+    let callback = match options.on_unknown_command {
+        Some(x) => x,
+        None => return false,
+    };
+
+    let typed_name = msg_content.split(char::is_whitespace).next().unwrap_or("");
+    if typed_name.is_empty() {
+        return false;
     }
 
-    Ok(first_matching_command)
+    // If the name does match a command, it was rejected for another reason, like failed checks
+    if matches_any_command(framework, runtime_commands, typed_name) {
+        return false;
+    }
+
+    let commands = options
+        .commands
+        .iter()
+        .chain(runtime_commands.iter().map(|command_meta| &**command_meta));
+    let names = commands.flat_map(|command_meta| {
+        let command = &command_meta.command;
+        std::iter::once(command.name).chain(command.options.aliases.iter().copied())
+    });
+    let suggestions = closest_names(
+        names,
+        typed_name,
+        options.unknown_command_max_distance,
+        options.case_insensitive_commands,
+        options.command_name_normalizer,
+    );
+
+    let ctx = crate::PrefixContext {
+        discord: ctx,
+        msg,
+        prefix,
+        args_offset: args_offset(msg, msg_content),
+        framework,
+        data: framework.get_user_data().await,
+        command: None,
+        invoked_command_path: &[],
+        invoked_by_edit: false,
+    };
+    callback(ctx, typed_name, suggestions).await;
+    true
+}
+
+/// Calls [`crate::PrefixFrameworkOptions::invoke_on_mention`] if the message mentions the bot
+async fn invoke_on_mention<U, E>(
+    framework: &crate::Framework<U, E>,
+    ctx: &serenity::Context,
+    msg: &serenity::Message,
+) where
+    U: Send + Sync,
+{
+    let callback = match framework.options.prefix_options.invoke_on_mention {
+        Some(x) => x,
+        None => return,
+    };
+
+    if !mentions_bot(&msg.content, ctx.cache.current_user_id()) {
+        return;
+    }
+
+    let ctx = crate::PrefixContext {
+        discord: ctx,
+        msg,
+        prefix: "",
+        args_offset: 0,
+        framework,
+        data: framework.get_user_data().await,
+        command: None,
+        invoked_command_path: &[],
+        invoked_by_edit: false,
+    };
+    if let Err(e) = callback(ctx).await {
+        (framework.options.on_error)(e, crate::ErrorContext::InvokeOnMention(ctx)).await;
+    }
+}
+
+/// Calls [`crate::PrefixFrameworkOptions::non_command_message`]
+async fn non_command_message<U, E>(
+    framework: &crate::Framework<U, E>,
+    ctx: &serenity::Context,
+    msg: &serenity::Message,
+    prefix: &str,
+) where
+    U: Send + Sync,
+{
+    let callback = match framework.options.prefix_options.non_command_message {
+        Some(x) => x,
+        None => return,
+    };
+
+    let ctx = crate::PrefixContext {
+        discord: ctx,
+        msg,
+        prefix,
+        args_offset: 0,
+        framework,
+        data: framework.get_user_data().await,
+        command: None,
+        invoked_command_path: &[],
+        invoked_by_edit: false,
+    };
+    if let Err(e) = callback(ctx).await {
+        (framework.options.on_error)(e, crate::ErrorContext::NonCommandMessage(ctx)).await;
+    }
 }
 
 /// Manually dispatches a message with the prefix framework.
 ///
+/// `runtime_commands` should be a snapshot obtained from
+/// [`crate::Framework::runtime_prefix_commands`].
+///
 /// Returns:
 /// - Ok(()) if a command was successfully dispatched and run
 /// - Err(None) if no command was run but no error happened
@@ -238,34 +1220,170 @@ pub async fn dispatch_message<'a, U, E>(
     framework: &'a crate::Framework<U, E>,
     ctx: &'a serenity::Context,
     msg: &'a serenity::Message,
+    runtime_commands: &'a [std::sync::Arc<crate::PrefixCommandMeta<U, E>>],
     triggered_by_edit: bool,
     previously_tracked: bool,
 ) -> Result<(), Option<(E, crate::PrefixCommandErrorContext<'a, U, E>)>>
 where
     U: Send + Sync,
 {
-    // Strip prefix and whitespace between prefix and command
-    let (prefix, msg_content) = strip_prefix(framework, ctx, msg).await.ok_or(None)?;
-    let msg_content = msg_content.trim_start();
-
-    // Check if we're allowed to execute our own messages
+    // Check if we're allowed to execute our own messages. If not globally, some commands may
+    // still opt in, but the message is otherwise ignored
     let bot_id = ctx.cache.current_user_id();
     let execute_self_messages = framework.options.prefix_options.execute_self_messages;
-    if bot_id == msg.author.id && !execute_self_messages {
+    let ignore_non_commands = bot_id == msg.author.id && !execute_self_messages;
+    if ignore_non_commands && !any_command_allows_self_author(framework, runtime_commands) {
         return Err(None);
     }
 
-    let (command_meta, args) = find_command(
-        framework,
-        ctx,
-        msg,
-        prefix,
-        &framework.options.prefix_options.commands,
-        msg_content,
-    )
-    .await
-    .map_err(Some)?
-    .ok_or(None)?;
+    if is_channel_blocked(framework, ctx, msg) {
+        return Err(None);
+    }
+
+    // Strip prefix and whitespace between prefix and command. In DMs, the prefix may be omitted
+    // if configured
+    let mut prefix_omitted = false;
+    let require_space = framework.options.prefix_options.require_space_after_prefix;
+    let stripped = strip_prefix(framework, ctx, msg)
+        .await
+        .and_then(|(prefix, content)| {
+            check_prefix_separator(prefix, content, require_space).map(|content| (prefix, content))
+        });
+    let (prefix, msg_content) = match stripped {
+        Some(x) => x,
+        None if framework.options.prefix_options.ignore_prefix_in_dms && msg.guild_id.is_none() => {
+            prefix_omitted = true;
+            ("", message_content(&framework.options.prefix_options, msg))
+        }
+        None => {
+            if !triggered_by_edit && !ignore_non_commands {
+                non_command_message(framework, ctx, msg, "").await;
+                invoke_on_mention(framework, ctx, msg).await;
+            }
+            return Err(None);
+        }
+    };
+    let msg_content = msg_content.trim_start();
+
+    let mut found_command = None;
+    // A message consisting of just a bot mention invokes the help command without arguments
+    if let (Some(help_command), true) = (
+        &framework.options.prefix_options.help_command,
+        msg_content.is_empty()
+            && is_mention_prefix(prefix)
+            && framework.options.prefix_options.help_when_mentioned,
+    ) {
+        // Name matching is bypassed, but the checks still apply
+        let path = crate::util::intern_command_path(&[help_command.command.name]);
+        let check_ctx = crate::PrefixContext {
+            discord: ctx,
+            msg,
+            prefix,
+            args_offset: msg.content.len(),
+            framework,
+            data: framework.get_user_data().await,
+            command: Some(&help_command.command),
+            invoked_command_path: path,
+            invoked_by_edit: triggered_by_edit,
+        };
+        let cooldown_exempt = check_command(framework, check_ctx, &help_command.command, false)
+            .await
+            .map_err(Some)?
+            .ok_or(None)?;
+        found_command = Some(((help_command, path), msg_content, cooldown_exempt));
+    }
+    if let (Some(custom_dispatch), true) = (
+        framework.options.prefix_options.custom_dispatch,
+        found_command.is_none(),
+    ) {
+        let custom_ctx = crate::PrefixContext {
+            discord: ctx,
+            msg,
+            prefix,
+            args_offset: args_offset(msg, msg_content),
+            framework,
+            data: framework.get_user_data().await,
+            command: None,
+            invoked_command_path: &[],
+            invoked_by_edit: triggered_by_edit,
+        };
+        if let Some((command_meta, args)) = custom_dispatch(custom_ctx).await {
+            // Name matching is bypassed, but the checks still apply
+            let path = locate_command_path(framework, runtime_commands, command_meta);
+            let check_ctx = crate::PrefixContext {
+                args_offset: args_offset(msg, args),
+                command: Some(&command_meta.command),
+                invoked_command_path: path,
+                ..custom_ctx
+            };
+            let cooldown_exempt = check_command(framework, check_ctx, &command_meta.command, false)
+                .await
+                .map_err(Some)?
+                .ok_or(None)?;
+            found_command = Some(((command_meta, path), args, cooldown_exempt));
+        }
+    }
+    if found_command.is_none() {
+        found_command = find_command(
+            framework,
+            ctx,
+            msg,
+            prefix,
+            &framework.options.prefix_options.commands,
+            &[],
+            msg_content,
+            triggered_by_edit,
+            false,
+        )
+        .await
+        .map_err(Some)?;
+    }
+    // If a static command has the typed name but rejected the invocation, a runtime command with
+    // the same name must not run in its place
+    let typed_name = msg_content.split(char::is_whitespace).next().unwrap_or("");
+    let static_name_matched = matches_any_command(framework, &[], typed_name);
+    for runtime_command in runtime_commands {
+        if found_command.is_some() || static_name_matched {
+            break;
+        }
+        found_command = find_command(
+            framework,
+            ctx,
+            msg,
+            prefix,
+            std::slice::from_ref(&**runtime_command),
+            &[],
+            msg_content,
+            triggered_by_edit,
+            false,
+        )
+        .await
+        .map_err(Some)?;
+    }
+    let ((command_meta, path), args, cooldown_exempt) = match found_command {
+        Some(x) => x,
+        None => {
+            if triggered_by_edit || ignore_non_commands {
+                return Err(None);
+            }
+
+            // A command name which matches a command means that the command rejected the
+            // invocation, so this is not a non-command message
+            if !matches_any_command(framework, runtime_commands, typed_name) {
+                non_command_message(framework, ctx, msg, prefix).await;
+            }
+
+            // Without a prefix, most DMs are likely not meant as commands at all, so they
+            // shouldn't trigger unknown command responses
+            if prefix_omitted
+                || !unknown_command(framework, ctx, msg, prefix, runtime_commands, msg_content)
+                    .await
+            {
+                invoke_on_mention(framework, ctx, msg).await;
+            }
+            return Err(None);
+        }
+    };
     let command = &command_meta.command;
 
     // Check if we should disregard this invocation if it was triggered by an edit
@@ -279,52 +1397,521 @@ where
         discord: ctx,
         msg,
         prefix,
+        args_offset: args_offset(msg, args),
         framework,
         data: framework.get_user_data().await,
         command: Some(command),
+        invoked_command_path: path,
+        invoked_by_edit: triggered_by_edit,
     };
 
-    let cooldowns = &command.id.cooldowns;
-    let cooldown_left = cooldowns.lock().unwrap().get_wait_time(ctx.into());
-    if let Some(cooldown_left) = cooldown_left {
-        if let Some(callback) = ctx.framework.options().cooldown_hit {
-            callback(ctx.into(), cooldown_left).await.map_err(|e| {
+    // If a subcommand had matched, find_command would have returned the subcommand instead
+    if command.options.subcommand_required && !command_meta.subcommands.is_empty() {
+        if let Some(callback) = framework.options.prefix_options.subcommand_required_handler {
+            callback(ctx, command_meta).await.map_err(|e| {
                 Some((
                     e,
                     crate::PrefixCommandErrorContext {
                         ctx,
                         command,
-                        location: crate::CommandErrorLocation::CooldownCallback,
+                        location: crate::CommandErrorLocation::SubcommandRequiredCallback,
                     },
                 ))
             })?;
         }
         return Err(None);
     }
-    cooldowns.lock().unwrap().start_cooldown(ctx.into());
 
-    // Typing is broadcasted as long as this object is alive
-    let _typing_broadcaster = if command.options.broadcast_typing {
-        msg.channel_id.start_typing(&ctx.discord.http).ok()
-    } else {
-        None
+    if command.options.on_missing_required_args == crate::MissingArgBehavior::ShowHelp
+        && args.trim().is_empty()
+        && command
+            .parameters
+            .iter()
+            .any(|parameter| parameter.required)
+    {
+        let help = match command.options.multiline_help {
+            Some(multiline_help) => multiline_help(ctx.into()),
+            None => format!("Usage: `{}`", command.usage_string(prefix)),
+        };
+        let _: Result<_, _> = crate::say_reply(ctx.into(), help).await;
+        return Err(None);
+    }
+
+    // Kept alive until the command finished, so that shutdown waits for it
+    let _invocation_guard = match super::start_invocation(ctx.into()).await {
+        Ok(Some(guard)) => guard,
+        Ok(None) => return Err(None),
+        Err(e) => {
+            return Err(Some((
+                e,
+                crate::PrefixCommandErrorContext {
+                    ctx,
+                    command,
+                    location: crate::CommandErrorLocation::ShuttingDownCallback,
+                },
+            )))
+        }
+    };
+
+    // Kept alive until the command finished, to free the slot even on errors or panics
+    let _concurrency_guard = match super::acquire_concurrency_slot(ctx.into(), &command.id).await {
+        Ok(Some(guard)) => guard,
+        Ok(None) => return Err(None),
+        Err(e) => {
+            return Err(Some((
+                e,
+                crate::PrefixCommandErrorContext {
+                    ctx,
+                    command,
+                    location: crate::CommandErrorLocation::MaxConcurrencyCallback,
+                },
+            )))
+        }
+    };
+
+    // The cooldown was already checked along with the other checks in find_command
+    if !cooldown_exempt {
+        let cooldowns = &command.id.cooldowns;
+        cooldowns.lock().unwrap().start_cooldown(ctx.into());
+    }
+
+    // Persistent typing is broadcasted as long as this object is alive. serenity's Typing
+    // re-triggers the indicator in a background task every few seconds and stops when dropped
+    let _typing_broadcaster = match command.options.broadcast_typing {
+        Some(crate::TypingMode::Once) => {
+            let _: Result<_, _> = msg.channel_id.broadcast_typing(ctx.discord).await;
+            None
+        }
+        Some(crate::TypingMode::Persistent) => msg.channel_id.start_typing(&ctx.discord.http).ok(),
+        None => None,
     };
 
     (framework.options.pre_command)(crate::Context::Prefix(ctx)).await;
 
     // Execute command
-    let res = (command.action)(ctx, args).await.map_err(|e| {
+    #[cfg(feature = "tracing")]
+    let span = super::invocation_span(ctx.into(), command.name, "prefix");
+    #[cfg(feature = "tracing")]
+    span.record(
+        "command.args",
+        &if command.options.redact_args {
+            "<redacted>"
+        } else {
+            args
+        },
+    );
+    let action = (command.action)(ctx, args);
+    #[cfg(feature = "tracing")]
+    let action = tracing::Instrument::instrument(action, span.clone());
+    #[cfg(feature = "metrics")]
+    framework.metrics.record_invocation(&command.id);
+    #[cfg(feature = "metrics")]
+    let start_time = std::time::Instant::now();
+    // Catch panics to run the post command hook before resuming them
+    let action = futures::FutureExt::catch_unwind(std::panic::AssertUnwindSafe(action));
+    let timeout = command
+        .options
+        .timeout
+        .or(framework.options.prefix_options.command_timeout);
+    // None if the command timed out. The action future is dropped in that case
+    let res = match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, action).await.ok(),
+        None => Some(action.await),
+    };
+    let outcome = match &res {
+        Some(Ok(Ok(()))) => crate::CommandOutcome::Success,
+        Some(Ok(Err((e, _)))) => crate::CommandOutcome::Error(e),
+        Some(Err(_)) => crate::CommandOutcome::Panic,
+        None => crate::CommandOutcome::Timeout,
+    };
+    #[cfg(feature = "metrics")]
+    framework
+        .metrics
+        .record_outcome(&command.id, outcome, start_time.elapsed());
+    (framework.options.post_command)(crate::Context::Prefix(ctx), outcome).await;
+    let res = match res {
+        Some(res) => res.unwrap_or_else(|panic| std::panic::resume_unwind(panic)),
+        None => {
+            #[cfg(feature = "tracing")]
+            span.in_scope(|| tracing::warn!("command timed out"));
+            match (framework.options.prefix_options.on_command_timeout, timeout) {
+                (Some(callback), Some(timeout)) => callback(ctx, timeout)
+                    .await
+                    .map_err(|e| (e, crate::CommandErrorLocation::TimeoutCallback)),
+                _ => Ok(()),
+            }
+        }
+    };
+
+    // Only the error location is recorded. E has no Display bound, and adding one just with the
+    // tracing feature would break crates whose error type lacks it as soon as any other crate in
+    // the dependency graph enables the feature. The error itself is passed to on_error
+    #[cfg(feature = "tracing")]
+    if let Err((_, location)) = &res {
+        span.in_scope(|| tracing::error!(error.location = ?location, "command returned an error"));
+    }
+    let res = res.map_err(|(e, location)| {
         Some((
             e,
             crate::PrefixCommandErrorContext {
                 ctx,
                 command,
-                location: crate::CommandErrorLocation::Check,
+                location,
             },
         ))
     });
 
-    (framework.options.post_command)(crate::Context::Prefix(ctx)).await;
-
     res
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_strip_longest_literal_prefix() {
+        let prefixes = ["!", "!help", "?", "!!", "bot "];
+        for &(content, case_insensitive, expected) in &[
+            ("!help foo", false, Some(("!help", " foo"))),
+            ("!!ping", false, Some(("!!", "ping"))),
+            ("!ping", false, Some(("!", "ping"))),
+            ("?ping", false, Some(("?", "ping"))),
+            ("ping", false, None),
+            ("Bot Ping", false, None),
+            ("Bot Ping", true, Some(("Bot ", "Ping"))),
+            ("!HELP foo", true, Some(("!HELP", " foo"))),
+            ("bö", true, None),
+        ] {
+            assert_eq!(
+                strip_longest_literal_prefix(
+                    content,
+                    prefixes.iter().map(|&prefix| (prefix, case_insensitive))
+                ),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_strip_mixed_case_sensitivity_prefixes() {
+        let prefixes = [("!", false), ("luna ", true), ("Luna, ", false)];
+        for &(content, expected) in &[
+            ("!ping", Some(("!", "ping"))),
+            ("luna ping", Some(("luna ", "ping"))),
+            ("Luna ping", Some(("Luna ", "ping"))),
+            ("LuNa PiNg", Some(("LuNa ", "PiNg"))),
+            ("Luna, ping", Some(("Luna, ", "ping"))),
+            ("luna, ping", None),
+            ("lunaping", None),
+            ("lüna ping", None),
+        ] {
+            assert_eq!(
+                strip_longest_literal_prefix(content, prefixes.iter().copied()),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_strip_regex_prefix() {
+        let options = crate::PrefixFrameworkOptions::<(), ()> {
+            additional_prefixes: vec![crate::Prefix::Regex(
+                regex::Regex::new("^(hey |yo )").unwrap(),
+            )],
+            ..Default::default()
+        };
+
+        // The prefix is the exact text matched by the regex, not the pattern
+        assert_eq!(
+            strip_static_prefix(&options, "hey ping"),
+            Some(("hey ", "ping"))
+        );
+        assert_eq!(
+            strip_static_prefix(&options, "yo ping 1"),
+            Some(("yo ", "ping 1"))
+        );
+        assert_eq!(strip_static_prefix(&options, "oh hey ping"), None);
+        assert_eq!(strip_static_prefix(&options, "ping"), None);
+    }
+
+    #[test]
+    fn test_check_prefix_separator() {
+        // (prefix, content, require_space, expected)
+        for &(prefix, content, require_space, expected) in &[
+            ("!", "ping", None, Some("ping")),
+            ("!", " ping", None, Some(" ping")),
+            ("!", "ping", Some(false), Some("ping")),
+            ("!", " ping", Some(false), None),
+            ("!", "ping", Some(true), None),
+            ("!", " ping", Some(true), Some(" ping")),
+            // Whitespace in the prefix itself counts as separator
+            ("hey bot ", "ping", Some(true), Some("ping")),
+            ("hey bot ", "ping", Some(false), Some("ping")),
+            // Mentions are exempt
+            ("<@123>", " ping", Some(false), Some(" ping")),
+            ("<@!123>", "ping", Some(true), Some("ping")),
+        ] {
+            assert_eq!(
+                check_prefix_separator(prefix, content, require_space),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_trim_invisible_start() {
+        for &(content, expected) in &[
+            ("!ping", "!ping"),
+            ("\u{200B}!ping", "!ping"),
+            ("\u{FEFF}!ping", "!ping"),
+            (" \u{200B}\u{200D} \t!ping", "!ping"),
+            ("\u{2060}<@123> ping", "<@123> ping"),
+            // Only leading characters are removed
+            ("!\u{200B}ping", "!\u{200B}ping"),
+            ("\u{200B}", ""),
+        ] {
+            assert_eq!(trim_invisible_start(content), expected);
+        }
+    }
+
+    #[test]
+    fn test_strip_mention_prefix() {
+        let bot_id = serenity::UserId(123);
+        for &(content, expected) in &[
+            ("<@123> ping", Some(("<@123>", " ping"))),
+            ("<@!123> ping", Some(("<@!123>", " ping"))),
+            ("<@123>ping", Some(("<@123>", "ping"))),
+            ("<@!123>ping", Some(("<@!123>", "ping"))),
+            ("<@123>", Some(("<@123>", ""))),
+            ("<@!!123> ping", None),
+            ("<@1234> ping", None),
+            ("<@12> ping", None),
+            ("<@&123> ping", None),
+            ("hey <@123> ping", None),
+        ] {
+            assert_eq!(strip_mention_prefix(content, bot_id), expected);
+        }
+    }
+
+    #[test]
+    fn test_mentions_bot() {
+        let bot_id = serenity::UserId(123);
+        for &(content, expected) in &[
+            ("hey <@123> help me", true),
+            ("hey <@!123>", true),
+            ("<@123>", true),
+            ("hey <@1234> help me", false),
+            ("hey <@&123> help me", false),
+            ("hey @everyone", false),
+            ("a reply without any mention", false),
+        ] {
+            assert_eq!(mentions_bot(content, bot_id), expected);
+        }
+    }
+
+    #[test]
+    fn test_apply_prefix_precedence() {
+        let strip_static_prefix = |content: &'static str| {
+            move || {
+                content
+                    .strip_prefix('!')
+                    .map(|rest| content.split_at(content.len() - rest.len()))
+            }
+        };
+
+        // (content, stripped dynamic prefix, dynamic prefix, expected)
+        for &(content, stripped_dynamic_prefix, dynamic_prefix, expected) in &[
+            // Neither callback returns Some: static prefix is used
+            ("!ping", None, None, Some(("!", "ping"))),
+            ("?ping", None, None, None),
+            // Only dynamic prefix returns Some: it replaces the static prefix
+            ("?ping", None, Some("?"), Some(("?", "ping"))),
+            ("!ping", None, Some("?"), None),
+            // Only stripped dynamic prefix returns Some: it wins over the static prefix
+            ("!ping", Some(("!p", "ing")), None, Some(("!p", "ing"))),
+            // Both return Some: stripped dynamic prefix wins
+            ("?ping", Some(("?p", "ing")), Some("?"), Some(("?p", "ing"))),
+            ("!ping", Some(("!p", "ing")), Some("?"), Some(("!p", "ing"))),
+        ] {
+            assert_eq!(
+                apply_prefix_precedence(
+                    content,
+                    stripped_dynamic_prefix,
+                    dynamic_prefix,
+                    strip_static_prefix(content),
+                    false,
+                ),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_command_name_matches() {
+        for &(invoked_name, case_insensitive, expected) in &[
+            ("foo", true, true),
+            ("FOO", true, true),
+            ("Foo", true, true),
+            ("foo", false, false),
+            ("FOO", false, false),
+            ("Foo", false, true),
+            ("bar", false, true),
+            ("BAR", true, true),
+            ("BAR", false, false),
+            ("baz", true, false),
+        ] {
+            assert_eq!(
+                command_name_matches("bar", &["Foo"], invoked_name, case_insensitive, None),
+                expected,
+                "invoked as {:?}, case insensitive: {}",
+                invoked_name,
+                case_insensitive,
+            );
+        }
+    }
+
+    #[test]
+    fn test_command_name_normalizer() {
+        fn strip_accents(name: &str) -> std::borrow::Cow<'_, str> {
+            if name.contains(|c| c == 'é' || c == 'è') {
+                name.replace(|c| c == 'é' || c == 'è', "e").into()
+            } else {
+                name.into()
+            }
+        }
+
+        for &(invoked_name, case_insensitive, expected) in &[
+            ("fete", false, true),
+            ("fête", false, false),
+            ("féte", false, true),
+            ("fèté", false, true),
+            ("FÉTE", true, false),
+            ("FéTE", true, true),
+            ("creer", false, true),
+            ("créer", false, true),
+        ] {
+            assert_eq!(
+                command_name_matches(
+                    "fete",
+                    &["créer"],
+                    invoked_name,
+                    case_insensitive,
+                    Some(strip_accents)
+                ),
+                expected,
+                "invoked as {:?}, case insensitive: {}",
+                invoked_name,
+                case_insensitive,
+            );
+        }
+
+        assert_eq!(
+            closest_names(
+                ["préfixe", "aide"].iter().copied(),
+                "prefix",
+                1,
+                false,
+                Some(strip_accents)
+            ),
+            vec!["préfixe"]
+        );
+    }
+
+    #[test]
+    fn test_find_name_conflict() {
+        let (id_1, id_2, id_3) = (1 as *const (), 2 as *const (), 3 as *const ());
+        let commands: &[(&str, &[&str], i32, *const ())] = &[
+            ("ping", &["p"], 0, id_1),
+            ("help", &["h"], 0, id_2),
+            ("pong", &["P"], 0, id_3),
+            ("ping", &[], 0, id_1),
+        ];
+        assert_eq!(find_name_conflict(commands, false, None), None);
+        assert_eq!(find_name_conflict(commands, true, None), Some((0, 2, "P")));
+        assert_eq!(
+            find_name_conflict(
+                &[commands[0], commands[1], ("Help", &[], 0, id_3)],
+                true,
+                None
+            ),
+            Some((1, 2, "Help"))
+        );
+        assert_eq!(
+            find_name_conflict(&[commands[1], ("info", &["h"], 0, id_3)], false, None),
+            Some((0, 1, "h"))
+        );
+        // Name matches take precedence over alias matches, and priorities break ties
+        assert_eq!(
+            find_name_conflict(&[commands[1], ("h", &[], 0, id_3)], false, None),
+            None
+        );
+        assert_eq!(
+            find_name_conflict(&[commands[1], ("help", &["h"], 1, id_3)], false, None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resolution_order() {
+        assert_eq!(
+            name_match("stats", &["s"], "stats", false, None),
+            Some(NameMatch::Name)
+        );
+        assert_eq!(
+            name_match("stats", &["s"], "s", false, None),
+            Some(NameMatch::Alias)
+        );
+        assert_eq!(name_match("stats", &["s"], "stat", false, None), None);
+
+        use NameMatch::{Alias, Name};
+        // An exact name match beats an alias match registered earlier, regardless of priority
+        assert_eq!(
+            resolution_order(&[Some((Alias, 10)), Some((Name, 0))]),
+            vec![1, 0]
+        );
+        // Higher priority wins among matches of equal specificity
+        assert_eq!(
+            resolution_order(&[Some((Name, 0)), Some((Name, 5)), Some((Name, -1))]),
+            vec![1, 0, 2]
+        );
+        assert_eq!(
+            resolution_order(&[Some((Alias, 0)), Some((Alias, 1))]),
+            vec![1, 0]
+        );
+        // Otherwise, registration order decides
+        assert_eq!(
+            resolution_order(&[Some((Alias, 0)), None, Some((Name, 0)), Some((Alias, 0))]),
+            vec![2, 0, 3]
+        );
+        assert_eq!(resolution_order(&[None, None]), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_closest_names() {
+        let names = ["ban", "unban", "kick", "help", "bank"];
+        let closest = |typed_name, max_distance, case_insensitive| {
+            closest_names(
+                names.iter().copied(),
+                typed_name,
+                max_distance,
+                case_insensitive,
+                None,
+            )
+        };
+        assert_eq!(closest("bam", 2, false), vec!["ban", "bank"]);
+        assert_eq!(closest("bam", 3, false), vec!["ban", "bank", "unban"]);
+        assert_eq!(closest("bam", 1, false), vec!["ban"]);
+        assert_eq!(closest("BAM", 1, false), Vec::<&str>::new());
+        assert_eq!(closest("BAM", 1, true), vec!["ban"]);
+        assert_eq!(closest("xyzzy", 2, false), Vec::<&str>::new());
+        // Duplicate names are only suggested once, even if other names lie in between
+        let names = ["ban", "bad", "ban", "bat"];
+        assert_eq!(
+            closest_names(names.iter().copied(), "bam", 1, false, None),
+            vec!["ban", "bad", "bat"]
+        );
+        assert_eq!(crate::util::levenshtein_distance("kitten", "sitting"), 3);
+        assert_eq!(crate::util::levenshtein_distance("", "abc"), 3);
+        assert_eq!(crate::util::levenshtein_distance("abc", "abc"), 0);
+    }
+}
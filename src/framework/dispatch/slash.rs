@@ -1,10 +1,13 @@
 use crate::serenity_prelude as serenity;
 
+/// Also returns the path of the command (see [`crate::ApplicationContext::invoked_command_path`])
+/// and the options of the innermost subcommand
 fn find_matching_application_command<'a, 'b, U, E>(
     framework: &'a crate::Framework<U, E>,
     interaction: &'b serenity::ApplicationCommandInteractionData,
 ) -> Option<(
     crate::ApplicationCommand<'a, U, E>,
+    Vec<&'static str>,
     &'b [serenity::ApplicationCommandInteractionDataOption],
 )> {
     let commands = &framework.options.application_options.commands;
@@ -19,6 +22,7 @@ fn find_matching_application_command<'a, 'b, U, E>(
             if cmd.name == interaction.name && interaction.kind == application_command_type {
                 Some((
                     crate::ApplicationCommand::ContextMenu(cmd),
+                    vec![cmd.name],
                     &*interaction.options,
                 ))
             } else {
@@ -31,7 +35,11 @@ fn find_matching_application_command<'a, 'b, U, E>(
                 if cmd.name == interaction.name
                     && interaction.kind == serenity::ApplicationCommandType::ChatInput
                 {
-                    Some((crate::ApplicationCommand::Slash(cmd), &*interaction.options))
+                    Some((
+                        crate::ApplicationCommand::Slash(cmd),
+                        vec![cmd.name],
+                        &*interaction.options,
+                    ))
                 } else {
                     None
                 }
@@ -39,7 +47,7 @@ fn find_matching_application_command<'a, 'b, U, E>(
             // TODO: check name field perhaps?
             crate::SlashCommandMeta::CommandGroup {
                 subcommands,
-                name: _,
+                name: group_name,
                 description: _,
                 id: _,
             } => {
@@ -60,7 +68,11 @@ fn find_matching_application_command<'a, 'b, U, E>(
                 subcommands.iter().find_map(|cmd| match cmd {
                     crate::SlashCommandMeta::Command(cmd) => {
                         if cmd.name == interaction.name {
-                            Some((crate::ApplicationCommand::Slash(cmd), &*interaction.options))
+                            Some((
+                                crate::ApplicationCommand::Slash(cmd),
+                                vec![*group_name, cmd.name],
+                                &*interaction.options,
+                            ))
                         } else {
                             None
                         }
@@ -68,7 +80,7 @@ fn find_matching_application_command<'a, 'b, U, E>(
                     // TODO: check name field perhaps?
                     crate::SlashCommandMeta::CommandGroup {
                         subcommands,
-                        name: _,
+                        name: subgroup_name,
                         description: _,
                         id: _,
                     } => {
@@ -89,6 +101,7 @@ fn find_matching_application_command<'a, 'b, U, E>(
                                 if cmd.name == interaction.name {
                                     Some((
                                         crate::ApplicationCommand::Slash(cmd),
+                                        vec![*group_name, *subgroup_name, cmd.name],
                                         &*interaction.options,
                                     ))
                                 } else {
@@ -107,6 +120,8 @@ fn find_matching_application_command<'a, 'b, U, E>(
     })
 }
 
+/// Finds the invoked command and runs its checks. Also returns whether the invocation is exempt
+/// from the command's cooldown; the cooldown is not started yet
 pub async fn extract_command_and_run_checks<'a, U, E>(
     framework: &'a crate::Framework<U, E>,
     ctx: &'a serenity::Context,
@@ -116,10 +131,11 @@ pub async fn extract_command_and_run_checks<'a, U, E>(
     (
         crate::ApplicationContext<'a, U, E>,
         &'a [serenity::ApplicationCommandInteractionDataOption],
+        bool,
     ),
     Option<(E, crate::ApplicationCommandErrorContext<'a, U, E>)>,
 > {
-    let (command, leaf_interaction_options) =
+    let (command, path, leaf_interaction_options) =
         find_matching_application_command(framework, interaction.data()).ok_or_else(|| {
             println!(
                 "Warning: received unknown interaction \"{}\"",
@@ -134,94 +150,261 @@ pub async fn extract_command_and_run_checks<'a, U, E>(
         framework,
         interaction,
         command,
+        invoked_command_path: crate::util::intern_command_path(&path),
         has_sent_initial_response,
     };
 
-    // Make sure that user has required permissions
-    if !super::check_required_permissions_and_owners_only(
-        crate::Context::Application(ctx),
-        command.id().required_permissions,
-        command.id().owners_only,
-    )
-    .await
-    {
-        (framework
-            .options
-            .application_options
-            .missing_permissions_handler)(ctx)
-        .await;
-        return Err(None);
+    let checks_result = run_checks(framework, ctx).await;
+    // Autocomplete requests aren't invocations, so they don't run the post command hook
+    let is_invocation = matches!(
+        interaction,
+        crate::ApplicationCommandOrAutocompleteInteraction::ApplicationCommand(_)
+    );
+    if checks_result.is_err() && is_invocation {
+        let outcome = crate::CommandOutcome::Rejected;
+        (framework.options.post_command)(crate::Context::Application(ctx), outcome).await;
     }
+    let cooldown_exempt = checks_result?;
 
-    // Make sure the bot has the permissions it needs
-    let missing_bot_permissions =
-        super::check_missing_bot_permissions(ctx.into(), command.id().required_bot_permissions)
-            .await;
-    if !missing_bot_permissions.is_empty() {
-        (ctx.framework.options().missing_bot_permissions_handler)(
-            ctx.into(),
-            missing_bot_permissions,
-        )
-        .await
-        .map_err(|e| {
-            Some((
-                e,
-                crate::ApplicationCommandErrorContext {
-                    ctx,
-                    location: crate::CommandErrorLocation::MissingBotPermissionsCallback,
-                },
-            ))
-        })?;
-        return Err(None);
-    }
+    Ok((ctx, leaf_interaction_options, cooldown_exempt))
+}
 
-    // Only continue if command checks returns true
-    let checks_passing = (|| async {
-        let global_check_passes = match &framework.options.command_check {
-            Some(check) => check(crate::Context::Application(ctx)).await?,
-            None => true,
-        };
+/// Runs the checks of the invoked command (see [`crate::CheckKind`]). Returns whether the
+/// invocation is exempt from the command's cooldown if all checks passed.
+async fn run_checks<'a, U, E>(
+    framework: &'a crate::Framework<U, E>,
+    ctx: crate::ApplicationContext<'a, U, E>,
+) -> Result<bool, Option<(E, crate::ApplicationCommandErrorContext<'a, U, E>)>> {
+    let command = ctx.command;
+    // Run the checks in the configured order. The first failing check stops the evaluation
+    let cooldowns = &command.id().cooldowns;
+    let mut cooldown_exempt = false;
+    for check_kind in super::resolve_check_order(framework.options.check_order) {
+        match check_kind {
+            crate::CheckKind::OwnerOnly => {
+                // Make sure that owners_only commands are only invoked by owners
+                if !super::check_owners_only(ctx.into(), command.id().owners_only) {
+                    match framework.options.not_an_owner_handler {
+                        Some(callback) => callback(ctx.into()).await.map_err(|e| {
+                            Some((
+                                e,
+                                crate::ApplicationCommandErrorContext {
+                                    ctx,
+                                    location: crate::CommandErrorLocation::NotAnOwnerCallback,
+                                },
+                            ))
+                        })?,
+                        // Respond to the interaction anyways to not show "Interaction failed"
+                        None => {
+                            (framework
+                                .options
+                                .application_options
+                                .missing_permissions_handler)(ctx)
+                            .await
+                        }
+                    }
+                    return Err(None);
+                }
+            }
+            crate::CheckKind::GuildOnly => {
+                // Make sure that guild_only commands aren't invoked in DMs
+                if !super::check_guild_only(ctx.into(), command.id().guild_only) {
+                    match framework.options.guild_only_handler {
+                        Some(callback) => callback(ctx.into()).await.map_err(|e| {
+                            Some((
+                                e,
+                                crate::ApplicationCommandErrorContext {
+                                    ctx,
+                                    location: crate::CommandErrorLocation::GuildOnlyCallback,
+                                },
+                            ))
+                        })?,
+                        // Respond to the interaction anyways to not show "Interaction failed"
+                        None => {
+                            (framework
+                                .options
+                                .application_options
+                                .missing_permissions_handler)(ctx)
+                            .await
+                        }
+                    }
+                    return Err(None);
+                }
 
-        let command_specific_check_passes = match &command.options().check {
-            Some(check) => check(ctx).await?,
-            None => true,
-        };
+                // Make sure that dm_only commands aren't invoked in guilds
+                if !super::check_dm_only(ctx.into(), command.id().dm_only) {
+                    match framework.options.dm_only_handler {
+                        Some(callback) => callback(ctx.into()).await.map_err(|e| {
+                            Some((
+                                e,
+                                crate::ApplicationCommandErrorContext {
+                                    ctx,
+                                    location: crate::CommandErrorLocation::DmOnlyCallback,
+                                },
+                            ))
+                        })?,
+                        // Respond to the interaction anyways to not show "Interaction failed"
+                        None => {
+                            (framework
+                                .options
+                                .application_options
+                                .missing_permissions_handler)(ctx)
+                            .await
+                        }
+                    }
+                    return Err(None);
+                }
+            }
+            crate::CheckKind::Permissions => {
+                // Make sure that user has required permissions
+                let missing_user_permissions = super::check_missing_user_permissions(
+                    ctx.into(),
+                    command.id().required_permissions,
+                )
+                .await;
+                if !missing_user_permissions.is_empty() {
+                    let callback_error = |e| {
+                        Some((
+                            e,
+                            crate::ApplicationCommandErrorContext {
+                                ctx,
+                                location:
+                                    crate::CommandErrorLocation::MissingUserPermissionsCallback,
+                            },
+                        ))
+                    };
+                    match framework.options.missing_user_permissions_handler {
+                        Some(callback) => callback(ctx.into(), missing_user_permissions)
+                            .await
+                            .map_err(callback_error)?,
+                        None => {
+                            (framework
+                                .options
+                                .application_options
+                                .missing_permissions_handler)(ctx)
+                            .await
+                        }
+                    }
+                    return Err(None);
+                }
 
-        Ok(global_check_passes && command_specific_check_passes)
-    })()
-    .await
-    .map_err(|e| {
-        (
-            e,
-            crate::ApplicationCommandErrorContext {
-                ctx,
-                location: crate::CommandErrorLocation::Check,
-            },
-        )
-    })?;
-    if !checks_passing {
-        return Err(None);
-    }
+                // Make sure the bot has the permissions it needs
+                let missing_bot_permissions = super::check_missing_bot_permissions(
+                    ctx.into(),
+                    command.id().required_bot_permissions,
+                )
+                .await;
+                if !missing_bot_permissions.is_empty() {
+                    (ctx.framework.options().missing_bot_permissions_handler)(
+                        ctx.into(),
+                        missing_bot_permissions,
+                    )
+                    .await
+                    .map_err(|e| {
+                        Some((
+                            e,
+                            crate::ApplicationCommandErrorContext {
+                                ctx,
+                                location:
+                                    crate::CommandErrorLocation::MissingBotPermissionsCallback,
+                            },
+                        ))
+                    })?;
+                    return Err(None);
+                }
+            }
+            crate::CheckKind::Cooldown => {
+                cooldown_exempt = super::check_cooldown_exempt(ctx.into()).await;
+                if !cooldown_exempt {
+                    let cooldown_left = cooldowns.lock().unwrap().get_wait_time(ctx.into());
+                    if let Some(cooldown_left) = cooldown_left {
+                        if let Some(callback) = ctx.framework.options().cooldown_hit {
+                            callback(ctx.into(), cooldown_left).await.map_err(|e| {
+                                Some((
+                                    e,
+                                    crate::ApplicationCommandErrorContext {
+                                        ctx,
+                                        location: crate::CommandErrorLocation::CooldownCallback,
+                                    },
+                                ))
+                            })?;
+                        }
+                        return Err(None);
+                    }
+                }
+            }
+            crate::CheckKind::CustomCheck => {
+                // Only continue if the global check and then the command specific check return true
+                let check_error = |e| {
+                    Some((
+                        e,
+                        crate::ApplicationCommandErrorContext {
+                            ctx,
+                            location: crate::CommandErrorLocation::Check,
+                        },
+                    ))
+                };
+                if let Some(check) = framework.options.command_check {
+                    if !check(crate::Context::Application(ctx))
+                        .await
+                        .map_err(check_error)?
+                    {
+                        let callback_error = |e| {
+                            Some((
+                                e,
+                                crate::ApplicationCommandErrorContext {
+                                    ctx,
+                                    location:
+                                        crate::CommandErrorLocation::GlobalCheckFailureCallback,
+                                },
+                            ))
+                        };
+                        if let Some(callback) = framework.options.on_global_check_failure {
+                            callback(crate::Context::Application(ctx))
+                                .await
+                                .map_err(callback_error)?;
+                        }
+                        return Err(None);
+                    }
+                }
+                if let Some(check) = command.options().check {
+                    if !check(ctx).await.map_err(check_error)? {
+                        return Err(None);
+                    }
+                }
 
-    let cooldowns = &command.id().cooldowns;
-    let cooldown_left = cooldowns.lock().unwrap().get_wait_time(ctx.into());
-    if let Some(cooldown_left) = cooldown_left {
-        if let Some(callback) = ctx.framework.options().cooldown_hit {
-            callback(ctx.into(), cooldown_left).await.map_err(|e| {
-                Some((
-                    e,
-                    crate::ApplicationCommandErrorContext {
-                        ctx,
-                        location: crate::CommandErrorLocation::CooldownCallback,
-                    },
-                ))
-            })?;
+                if let Some(check) = command.options().check_with_reason {
+                    let check_result = check(ctx).await.map_err(|e| {
+                        Some((
+                            e,
+                            crate::ApplicationCommandErrorContext {
+                                ctx,
+                                location: crate::CommandErrorLocation::Check,
+                            },
+                        ))
+                    })?;
+                    if let crate::CheckResult::Reject { reason, silent } = check_result {
+                        if let (Some(callback), false) =
+                            (framework.options.on_check_failure, silent)
+                        {
+                            callback(ctx.into(), reason).await.map_err(|e| {
+                                Some((
+                                    e,
+                                    crate::ApplicationCommandErrorContext {
+                                        ctx,
+                                        location: crate::CommandErrorLocation::CheckFailureCallback,
+                                    },
+                                ))
+                            })?;
+                        }
+                        return Err(None);
+                    }
+                }
+            }
         }
-        return Err(None);
     }
-    cooldowns.lock().unwrap().start_cooldown(ctx.into());
 
-    Ok((ctx, leaf_interaction_options))
+    Ok(cooldown_exempt)
 }
 
 pub async fn dispatch_interaction<'a, U, E>(
@@ -231,7 +414,7 @@ pub async fn dispatch_interaction<'a, U, E>(
     // Need to pass this in from outside because of lifetime issues
     has_sent_initial_response: &'a std::sync::atomic::AtomicBool,
 ) -> Result<(), Option<(E, crate::ApplicationCommandErrorContext<'a, U, E>)>> {
-    let (ctx, options) = extract_command_and_run_checks(
+    let (ctx, options, cooldown_exempt) = extract_command_and_run_checks(
         framework,
         ctx,
         crate::ApplicationCommandOrAutocompleteInteraction::ApplicationCommand(interaction),
@@ -239,29 +422,112 @@ pub async fn dispatch_interaction<'a, U, E>(
     )
     .await?;
 
+    // Kept alive until the command finished, so that shutdown waits for it
+    let _invocation_guard = super::start_invocation(ctx.into())
+        .await
+        .map_err(|e| {
+            Some((
+                e,
+                crate::ApplicationCommandErrorContext {
+                    ctx,
+                    location: crate::CommandErrorLocation::ShuttingDownCallback,
+                },
+            ))
+        })?
+        .ok_or(None)?;
+
+    // Kept alive until the command finished, to free the slot even on errors or panics
+    let _concurrency_guard = super::acquire_concurrency_slot(ctx.into(), ctx.command.id())
+        .await
+        .map_err(|e| {
+            Some((
+                e,
+                crate::ApplicationCommandErrorContext {
+                    ctx,
+                    location: crate::CommandErrorLocation::MaxConcurrencyCallback,
+                },
+            ))
+        })?
+        .ok_or(None)?;
+
+    // Only start the cooldown once all checks passed and the invocation was accepted
+    if !cooldown_exempt {
+        let cooldowns = &ctx.command.id().cooldowns;
+        cooldowns.lock().unwrap().start_cooldown(ctx.into());
+    }
+
     (framework.options.pre_command)(crate::Context::Application(ctx)).await;
 
-    let action_result = match ctx.command {
-        crate::ApplicationCommand::Slash(cmd) => (cmd.action)(ctx, options).await,
-        crate::ApplicationCommand::ContextMenu(cmd) => match cmd.action {
-            crate::ContextMenuCommandAction::User(action) => match &interaction.data.target {
-                Some(serenity::ResolvedTarget::User(user, _)) => (action)(ctx, user.clone()).await,
-                _ => {
-                    println!("Warning: no user object sent in user context menu interaction");
-                    return Ok(());
-                }
-            },
-            crate::ContextMenuCommandAction::Message(action) => match &interaction.data.target {
-                Some(serenity::ResolvedTarget::Message(msg)) => (action)(ctx, msg.clone()).await,
-                _ => {
-                    println!("Warning: no message object sent in message context menu interaction");
-                    return Ok(());
+    // Yields None if Discord didn't send the context menu target
+    let action = async {
+        Some(match ctx.command {
+            crate::ApplicationCommand::Slash(cmd) => (cmd.action)(ctx, options).await,
+            crate::ApplicationCommand::ContextMenu(cmd) => match cmd.action {
+                crate::ContextMenuCommandAction::User(action) => match &interaction.data.target {
+                    Some(serenity::ResolvedTarget::User(user, _)) => {
+                        (action)(ctx, user.clone()).await
+                    }
+                    _ => {
+                        println!("Warning: no user object sent in user context menu interaction");
+                        return None;
+                    }
+                },
+                crate::ContextMenuCommandAction::Message(action) => {
+                    match &interaction.data.target {
+                        Some(serenity::ResolvedTarget::Message(msg)) => {
+                            (action)(ctx, msg.clone()).await
+                        }
+                        _ => {
+                            println!(
+                                "Warning: no message object sent in message context menu interaction"
+                            );
+                            return None;
+                        }
+                    }
                 }
             },
-        },
+        })
     };
-
-    (framework.options.post_command)(crate::Context::Application(ctx)).await;
+    #[cfg(feature = "tracing")]
+    let span = super::invocation_span(
+        ctx.into(),
+        ctx.command.slash_or_context_menu_name(),
+        "slash",
+    );
+    #[cfg(feature = "tracing")]
+    let action = tracing::Instrument::instrument(action, span.clone());
+    #[cfg(feature = "metrics")]
+    framework.metrics.record_invocation(ctx.command.id());
+    #[cfg(feature = "metrics")]
+    let start_time = std::time::Instant::now();
+    // Catch panics to run the post command hook before resuming them
+    let action_result =
+        match futures::FutureExt::catch_unwind(std::panic::AssertUnwindSafe(action)).await {
+            Ok(Some(x)) => Ok(x),
+            Ok(None) => return Ok(()),
+            Err(panic) => Err(panic),
+        };
+    let outcome = match &action_result {
+        Ok(Ok(())) => crate::CommandOutcome::Success,
+        Ok(Err(e)) => crate::CommandOutcome::Error(e),
+        Err(_) => crate::CommandOutcome::Panic,
+    };
+    #[cfg(feature = "metrics")]
+    framework
+        .metrics
+        .record_outcome(ctx.command.id(), outcome, start_time.elapsed());
+    (framework.options.post_command)(crate::Context::Application(ctx), outcome).await;
+    let action_result = action_result.unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+    // Only the error location is recorded, see the prefix dispatch for why the error isn't
+    #[cfg(feature = "tracing")]
+    if action_result.is_err() {
+        span.in_scope(|| {
+            tracing::error!(
+                error.location = ?crate::CommandErrorLocation::Body,
+                "command returned an error"
+            )
+        });
+    }
 
     action_result.map_err(|e| {
         Some((
@@ -281,7 +547,7 @@ pub async fn dispatch_autocomplete<'a, U, E>(
     // Need to pass this in from outside because of lifetime issues
     has_sent_initial_response: &'a std::sync::atomic::AtomicBool,
 ) -> Result<(), Option<(E, crate::ApplicationCommandErrorContext<'a, U, E>)>> {
-    let (ctx, options) = extract_command_and_run_checks(
+    let (ctx, options, cooldown_exempt) = extract_command_and_run_checks(
         framework,
         ctx,
         crate::ApplicationCommandOrAutocompleteInteraction::Autocomplete(interaction),
@@ -289,6 +555,11 @@ pub async fn dispatch_autocomplete<'a, U, E>(
     )
     .await?;
 
+    if !cooldown_exempt {
+        let cooldowns = &ctx.command.id().cooldowns;
+        cooldowns.lock().unwrap().start_cooldown(ctx.into());
+    }
+
     let command = match ctx.command {
         crate::ApplicationCommand::Slash(x) => x,
         crate::ApplicationCommand::ContextMenu(_) => return Err(None),
@@ -306,7 +577,7 @@ pub async fn dispatch_autocomplete<'a, U, E>(
                 location: crate::CommandErrorLocation::Autocomplete,
             };
 
-            if let Some(on_error) = error_ctx.ctx.command.options().on_error {
+            if let Some(on_error) = &error_ctx.ctx.command.options().on_error {
                 on_error(e, error_ctx).await;
             } else {
                 (framework.options.on_error)(e, crate::ErrorContext::Autocomplete(error_ctx)).await;
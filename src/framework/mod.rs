@@ -15,13 +15,34 @@ pub struct Framework<U, E> {
     // TODO: wrap in RwLock to allow changing framework options while running? Could also replace
     // the edit tracking cache interior mutability
     options: crate::FrameworkOptions<U, E>,
+    // Prefix commands added after framework construction. Kept separate from the options so that
+    // the statically registered commands can still be borrowed without locking. The list itself is
+    // behind an Arc so that dispatch can take a snapshot without copying it
+    runtime_prefix_commands:
+        std::sync::RwLock<std::sync::Arc<Vec<std::sync::Arc<crate::PrefixCommandMeta<U, E>>>>>,
     application_id: serenity::ApplicationId,
+    #[cfg(feature = "metrics")]
+    metrics: crate::metrics::MetricsRecorder,
+    // Set by Self::shutdown. New invocations are rejected while set
+    shutting_down: std::sync::atomic::AtomicBool,
+    // Number of command invocations which are currently running
+    in_flight_invocations: std::sync::atomic::AtomicUsize,
+    // Handlers of message component interactions, registered by commands
+    component_handlers: std::sync::Mutex<crate::ComponentRegistry<U, E>>,
 
     // Will be initialized to Some on construction, and then taken out on startup
     client: std::sync::Mutex<Option<serenity::Client>>,
     // Initialized to Some during construction; so shouldn't be None at any observable point
     shard_manager:
         std::sync::Mutex<Option<std::sync::Arc<tokio::sync::Mutex<serenity::ShardManager>>>>,
+    // Initialized to Some during construction, like the shard manager. Offline frameworks used
+    // for testing get the mocked HTTP client and cache
+    http_and_cache: std::sync::Mutex<
+        Option<(
+            std::sync::Arc<serenity::Http>,
+            std::sync::Arc<serenity::Cache>,
+        )>,
+    >,
     // Filled with Some on construction. Taken out and executed on first Ready gateway event
     user_data_setup: std::sync::Mutex<
         Option<
@@ -54,6 +75,14 @@ impl<U, E> Framework<U, E> {
     /// user ID or connected guilds can be made available to the user data setup function. The user
     /// data setup is not allowed to return Result because there would be no reasonable
     /// course of action on error.
+    ///
+    /// # Panics
+    ///
+    /// Panics if two different prefix commands on the same level of the command tree share a name,
+    /// or share an alias, and have the same [`crate::PrefixCommandOptions::priority`], because
+    /// dispatch couldn't tell them apart. Names are compared like in dispatch, i.e. respecting
+    /// [`crate::PrefixFrameworkOptions::case_insensitive_commands`] and
+    /// [`crate::PrefixFrameworkOptions::command_name_normalizer`].
     pub async fn new<F>(
         application_id: serenity::ApplicationId,
         client_builder: serenity::ClientBuilder,
@@ -72,6 +101,8 @@ impl<U, E> Framework<U, E> {
         U: Send + Sync + 'static,
         E: Send + 'static,
     {
+        validate(&options);
+
         let self_1 = std::sync::Arc::new(Self {
             user_data: once_cell::sync::OnceCell::new(),
             user_data_setup: std::sync::Mutex::new(Some(Box::new(user_data_setup))),
@@ -80,8 +111,15 @@ impl<U, E> Framework<U, E> {
             // client is created
             client: std::sync::Mutex::new(None),
             options,
+            runtime_prefix_commands: std::sync::RwLock::new(std::sync::Arc::new(Vec::new())),
             application_id,
+            #[cfg(feature = "metrics")]
+            metrics: Default::default(),
+            shutting_down: std::sync::atomic::AtomicBool::new(false),
+            in_flight_invocations: std::sync::atomic::AtomicUsize::new(0),
+            component_handlers: std::sync::Mutex::new(crate::ComponentRegistry::new()),
             shard_manager: std::sync::Mutex::new(None),
+            http_and_cache: std::sync::Mutex::new(None),
         });
         let self_2 = self_1.clone();
 
@@ -96,11 +134,45 @@ impl<U, E> Framework<U, E> {
             .await?;
 
         *self_1.shard_manager.lock().unwrap() = Some(client.shard_manager.clone());
+        *self_1.http_and_cache.lock().unwrap() = Some((
+            client.cache_and_http.http.clone(),
+            client.cache_and_http.cache.clone(),
+        ));
         *self_1.client.lock().unwrap() = Some(client);
 
         Ok(self_1)
     }
 
+    /// Creates a framework without a Discord client, with the user data already set up. Used by
+    /// [`crate::testing::MockContext`]
+    ///
+    /// [`Self::http`] and [`Self::cache`] return the given mocked HTTP client and cache.
+    #[cfg(feature = "testing")]
+    pub(crate) fn new_offline(
+        options: crate::FrameworkOptions<U, E>,
+        user_data: U,
+        http: std::sync::Arc<serenity::Http>,
+        cache: std::sync::Arc<serenity::Cache>,
+    ) -> Self {
+        validate(&options);
+
+        Self {
+            user_data: once_cell::sync::OnceCell::from(user_data),
+            user_data_setup: std::sync::Mutex::new(None),
+            client: std::sync::Mutex::new(None),
+            options,
+            runtime_prefix_commands: std::sync::RwLock::new(std::sync::Arc::new(Vec::new())),
+            application_id: serenity::ApplicationId(0),
+            #[cfg(feature = "metrics")]
+            metrics: Default::default(),
+            shutting_down: std::sync::atomic::AtomicBool::new(false),
+            in_flight_invocations: std::sync::atomic::AtomicUsize::new(0),
+            component_handlers: std::sync::Mutex::new(crate::ComponentRegistry::new()),
+            shard_manager: std::sync::Mutex::new(None),
+            http_and_cache: std::sync::Mutex::new(Some((http, cache))),
+        }
+    }
+
     /// Start the framework.
     ///
     /// Takes a `serenity::ClientBuilder`, in which you need to supply the bot token, as well as
@@ -154,6 +226,253 @@ impl<U, E> Framework<U, E> {
             .expect("fatal: shard manager not stored in framework initialization")
     }
 
+    /// Returns serenity's HTTP client, for making Discord API requests from places that only have
+    /// access to the framework, like background tasks.
+    ///
+    /// Available as soon as the framework is constructed; there's no need to wait for the bot to
+    /// be ready.
+    pub fn http(&self) -> std::sync::Arc<serenity::Http> {
+        self.http_and_cache().0
+    }
+
+    /// Returns serenity's cache, for reading guilds, channels and users from places that only have
+    /// access to the framework, like background tasks.
+    ///
+    /// Available as soon as the framework is constructed, but the cache is only filled as gateway
+    /// events come in, so it is mostly empty before the bot is ready.
+    pub fn cache(&self) -> std::sync::Arc<serenity::Cache> {
+        self.http_and_cache().1
+    }
+
+    fn http_and_cache(
+        &self,
+    ) -> (
+        std::sync::Arc<serenity::Http>,
+        std::sync::Arc<serenity::Cache>,
+    ) {
+        self.http_and_cache
+            .lock()
+            .unwrap()
+            .clone()
+            .expect("fatal: cache and HTTP client not stored in framework initialization")
+    }
+
+    /// Returns a snapshot of the invocation metrics of all commands which have been invoked so far
+    #[cfg(feature = "metrics")]
+    pub fn stats(&self) -> crate::CommandStats {
+        self.metrics.snapshot()
+    }
+
+    /// Forget the cached [dynamic prefix](crate::PrefixFrameworkOptions::dynamic_prefix) of the
+    /// given guild, so that it will be requested again on the next message.
+    ///
+    /// No-op if [`crate::PrefixFrameworkOptions::dynamic_prefix_cache`] is not set.
+    pub fn invalidate_dynamic_prefix(&self, guild_id: serenity::GuildId) {
+        if let Some(cache) = &self.options.prefix_options.dynamic_prefix_cache {
+            cache.invalidate(guild_id);
+        }
+    }
+
+    /// Stops processing messages in the given channel, see
+    /// [`crate::PrefixFrameworkOptions::blocked_channels`]. Returns whether the channel wasn't
+    /// blocked already.
+    pub fn block_channel(&self, channel_id: serenity::ChannelId) -> bool {
+        let blocked_channels = &self.options.prefix_options.blocked_channels;
+        blocked_channels.write().unwrap().insert(channel_id)
+    }
+
+    /// Resumes processing messages in the given channel. Returns whether the channel was blocked.
+    pub fn unblock_channel(&self, channel_id: serenity::ChannelId) -> bool {
+        let blocked_channels = &self.options.prefix_options.blocked_channels;
+        blocked_channels.write().unwrap().remove(&channel_id)
+    }
+
+    /// Stops processing messages in all channels of the given category, see
+    /// [`crate::PrefixFrameworkOptions::blocked_categories`]. Returns whether the category wasn't
+    /// blocked already.
+    pub fn block_category(&self, category_id: serenity::ChannelId) -> bool {
+        let blocked_categories = &self.options.prefix_options.blocked_categories;
+        blocked_categories.write().unwrap().insert(category_id)
+    }
+
+    /// Resumes processing messages in the channels of the given category. Returns whether the
+    /// category was blocked.
+    pub fn unblock_category(&self, category_id: serenity::ChannelId) -> bool {
+        let blocked_categories = &self.options.prefix_options.blocked_categories;
+        blocked_categories.write().unwrap().remove(&category_id)
+    }
+
+    /// Stops accepting new command invocations and waits until the running ones have finished, for
+    /// example before the process exits on SIGTERM. Returns false if invocations were still running
+    /// after the timeout.
+    ///
+    /// Invocations which arrive afterwards are passed to
+    /// [`crate::FrameworkOptions::shutting_down_handler`] instead of running. Events are still
+    /// received and other callbacks still run; to disconnect from Discord afterwards, use
+    /// [`Self::shard_manager`]:
+    /// ```rust,no_run
+    /// # async fn _test(framework: poise::Framework<(), ()>) {
+    /// framework.shutdown(std::time::Duration::from_secs(30)).await;
+    /// framework.shard_manager().lock().await.shutdown_all().await;
+    /// # }
+    /// ```
+    pub async fn shutdown(&self, timeout: std::time::Duration) -> bool {
+        use std::sync::atomic::Ordering;
+
+        self.shutting_down.store(true, Ordering::SeqCst);
+        let wait_for_invocations = async {
+            while self.in_flight_invocations.load(Ordering::SeqCst) > 0 {
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            }
+        };
+        tokio::time::timeout(timeout, wait_for_invocations)
+            .await
+            .is_ok()
+    }
+
+    /// Whether [`Self::shutdown`] was called
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// Registers a command invocation as running until the returned guard is dropped. Returns None
+    /// if the framework is shutting down and the invocation must not run.
+    pub(crate) fn start_invocation(&self) -> Option<InvocationGuard<'_>> {
+        use std::sync::atomic::Ordering;
+
+        // Registered before checking the flag, so that shutdown can't miss this invocation
+        let guard = InvocationGuard {
+            in_flight_invocations: &self.in_flight_invocations,
+        };
+        guard.in_flight_invocations.fetch_add(1, Ordering::SeqCst);
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return None;
+        }
+        Some(guard)
+    }
+
+    /// Registers a handler for message component interactions with the given custom ID, like
+    /// clicks on a button. The handler is called for every such interaction until the timeout
+    /// elapses, receiving the user data, and its errors are passed to
+    /// [`crate::FrameworkOptions::on_error`] as [`crate::ErrorContext::Component`].
+    ///
+    /// Registering a handler for a custom ID that already has one replaces it. Interactions are
+    /// routed regardless of who triggered them; check
+    /// [`ComponentContext::interaction`](crate::ComponentContext::interaction) if only certain
+    /// users may use the component. The handler must respond to the interaction, or Discord shows
+    /// an error to the user.
+    ///
+    /// Handlers are kept in memory only, so they are lost when the bot restarts.
+    pub fn register_component<F>(
+        &self,
+        custom_id: impl Into<String>,
+        timeout: std::time::Duration,
+        handler: F,
+    ) where
+        F: for<'a> Fn(crate::ComponentContext<'a, U, E>) -> BoxFuture<'a, Result<(), E>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let expires_at = std::time::Instant::now() + timeout;
+        self.component_handlers.lock().unwrap().insert(
+            custom_id.into(),
+            std::sync::Arc::new(handler),
+            expires_at,
+        );
+    }
+
+    /// Removes the handler registered for the given custom ID via [`Self::register_component`]
+    /// before its timeout elapsed. Returns whether a handler was removed.
+    pub fn unregister_component(&self, custom_id: &str) -> bool {
+        self.component_handlers.lock().unwrap().remove(custom_id)
+    }
+
+    /// Returns the component handler for the given custom ID, unless it timed out
+    pub(crate) fn component_handler(
+        &self,
+        custom_id: &str,
+    ) -> Option<crate::ComponentHandler<U, E>> {
+        self.component_handlers
+            .lock()
+            .unwrap()
+            .get(custom_id, std::time::Instant::now())
+    }
+
+    /// Returns how long until the given command's cooldowns for the given bucket key have expired,
+    /// or None if the command may be invoked right away. Useful for "try again in 3s" messages.
+    ///
+    /// This only inspects the cooldowns and doesn't start them. To build the bucket key from a
+    /// command invocation, use `CooldownContext::from(ctx)`.
+    pub fn cooldown_remaining(
+        &self,
+        command_id: &crate::CommandId,
+        bucket_key: &crate::CooldownContext,
+    ) -> Option<std::time::Duration> {
+        command_id
+            .cooldowns
+            .lock()
+            .unwrap()
+            .get_wait_time_for(bucket_key)
+    }
+
+    /// Registers a prefix command while the framework is running, for example to load commands
+    /// from a plugin.
+    ///
+    /// Runtime commands are considered after the commands in
+    /// [`crate::PrefixFrameworkOptions::commands`], so they can't shadow statically registered
+    /// commands. Each message is dispatched against a snapshot of the runtime commands taken when
+    /// dispatch starts: a command added or removed while a message is being processed only takes
+    /// effect from the next message on, and a removed command finishes any in-flight invocations.
+    ///
+    /// Returns an error describing the conflict if the command or one of its aliases is invoked by
+    /// the same name as an already registered command, static or runtime, because the new command
+    /// would be shadowed by it. Unlike for static commands, priorities don't resolve such
+    /// conflicts. Subcommands are checked like for static commands.
+    ///
+    /// Runtime commands are not included in [`Self::commands`], but are found by
+    /// [`Self::find_prefix_command`] and [`Self::prefix_commands_iter`].
+    pub fn add_prefix_command(
+        &self,
+        command: crate::PrefixCommandMeta<U, E>,
+    ) -> Result<(), String> {
+        let mut commands = self.runtime_prefix_commands.write().unwrap();
+        if let Some(conflict) = dispatch::find_runtime_command_conflict(
+            &self.options.prefix_options,
+            &commands,
+            &command,
+        ) {
+            return Err(conflict);
+        }
+
+        // Only copies the list if a snapshot of it is currently in use
+        std::sync::Arc::make_mut(&mut commands).push(std::sync::Arc::new(command));
+        Ok(())
+    }
+
+    /// Removes a prefix command previously registered with [`Self::add_prefix_command`]. The
+    /// command is identified by its [`crate::CommandId`] instance, not by name.
+    ///
+    /// Returns whether a command was removed. Commands from
+    /// [`crate::PrefixFrameworkOptions::commands`] can't be removed.
+    pub fn remove_prefix_command(&self, id: &crate::CommandId) -> bool {
+        let mut commands = self.runtime_prefix_commands.write().unwrap();
+        let num_commands_before = commands.len();
+        std::sync::Arc::make_mut(&mut commands)
+            .retain(|command| !std::ptr::eq(&*command.command.id, id));
+        commands.len() != num_commands_before
+    }
+
+    /// Returns a snapshot of the prefix commands registered with [`Self::add_prefix_command`].
+    ///
+    /// Taking a snapshot is cheap: the list is only copied when it's modified while a snapshot is
+    /// alive.
+    pub fn runtime_prefix_commands(
+        &self,
+    ) -> std::sync::Arc<Vec<std::sync::Arc<crate::PrefixCommandMeta<U, E>>>> {
+        std::sync::Arc::clone(&self.runtime_prefix_commands.read().unwrap())
+    }
+
     /// Yields an iterator over all unique commands in this framework. Different command
     /// types are grouped together if they belong to the same command definition.
     ///
@@ -194,7 +513,93 @@ impl<U, E> Framework<U, E> {
         map.into_iter().map(|(_k, v)| v)
     }
 
-    async fn get_user_data(&self) -> &U {
+    /// Yields all prefix commands, including subcommands, along with the command names that
+    /// invoke them, for example `["config", "set"]`.
+    ///
+    /// Commands are yielded depth-first, i.e. each command is directly followed by its
+    /// subcommands. `runtime_commands` should be a snapshot obtained from
+    /// [`Self::runtime_prefix_commands`]; its commands are yielded after the static ones.
+    pub fn prefix_commands_iter<'a>(
+        &'a self,
+        runtime_commands: &'a [std::sync::Arc<crate::PrefixCommandMeta<U, E>>],
+    ) -> impl Iterator<Item = (Vec<&'static str>, &'a crate::PrefixCommand<U, E>)> {
+        fn flatten<'a, U, E>(
+            command_meta: &'a crate::PrefixCommandMeta<U, E>,
+            parent_path: &[&'static str],
+            output: &mut Vec<(Vec<&'static str>, &'a crate::PrefixCommand<U, E>)>,
+        ) {
+            let mut path = parent_path.to_vec();
+            path.push(command_meta.command.name);
+            output.push((path.clone(), &command_meta.command));
+            for subcommand in &command_meta.subcommands {
+                flatten(subcommand, &path, output);
+            }
+        }
+
+        let mut commands = Vec::new();
+        let static_commands = self.options.prefix_options.commands.iter();
+        let runtime_commands = runtime_commands.iter().map(|command_meta| &**command_meta);
+        for command_meta in static_commands.chain(runtime_commands) {
+            flatten(command_meta, &[], &mut commands);
+        }
+        commands.into_iter()
+    }
+
+    /// Looks up a prefix command by its full path of command names, for example
+    /// `["config", "set"]` for the `set` subcommand of `config`. Aliases,
+    /// [`crate::PrefixFrameworkOptions::case_insensitive_commands`] and
+    /// [`crate::PrefixFrameworkOptions::command_name_normalizer`] are respected like in dispatch.
+    ///
+    /// `runtime_commands` should be a snapshot obtained from [`Self::runtime_prefix_commands`].
+    /// Like in dispatch, they are only considered if the first segment doesn't name a static
+    /// command.
+    ///
+    /// Returns None if the path is empty or any segment doesn't resolve.
+    pub fn find_prefix_command<'a>(
+        &'a self,
+        runtime_commands: &'a [std::sync::Arc<crate::PrefixCommandMeta<U, E>>],
+        path: &[&str],
+    ) -> Option<&'a crate::PrefixCommand<U, E>> {
+        let prefix_options = &self.options.prefix_options;
+        let static_commands = &prefix_options.commands;
+        let name = path.first()?;
+        if dispatch::find_command_by_path(prefix_options, static_commands, &[*name]).is_some() {
+            return dispatch::find_command_by_path(prefix_options, static_commands, path);
+        }
+        runtime_commands.iter().find_map(|command_meta| {
+            dispatch::find_command_by_path(
+                prefix_options,
+                std::slice::from_ref(&**command_meta),
+                path,
+            )
+        })
+    }
+
+    /// Returns all unique top-level commands (see [`Self::commands`]) grouped by their
+    /// [`crate::CommandId::category`].
+    ///
+    /// Categories are ordered by their first appearance in the command list. Commands without a
+    /// category are grouped under `None`, which always comes last.
+    pub fn commands_by_category(
+        &self,
+    ) -> Vec<(
+        Option<&'static str>,
+        Vec<crate::CommandDefinitionRef<'_, U, E>>,
+    )> {
+        let mut categories = crate::util::OrderedMap::new();
+        for command in self.commands() {
+            categories
+                .get_or_insert_with(command.id.category, Vec::new)
+                .push(command);
+        }
+
+        // The sort is stable, so the categories otherwise stay in order
+        let mut categories = categories.0;
+        categories.sort_by_key(|(category, _)| category.is_none());
+        categories
+    }
+
+    pub(crate) async fn get_user_data(&self) -> &U {
         // We shouldn't get a Message event before a Ready event. But if we do, wait until
         // the Ready event does come and the resulting data has arrived.
         loop {
@@ -205,3 +610,27 @@ impl<U, E> Framework<U, E> {
         }
     }
 }
+
+/// Checks the framework options for mistakes which can't be caught at compile time, like two
+/// prefix commands sharing a name
+fn validate<U, E>(options: &crate::FrameworkOptions<U, E>) {
+    let prefix_options = &options.prefix_options;
+    if let Some(conflict) =
+        dispatch::find_command_name_conflict(prefix_options, &prefix_options.commands, &[])
+    {
+        panic!("Conflicting command names: {}", conflict);
+    }
+}
+
+/// A running command invocation, see [`Framework::start_invocation`]. Unregisters the invocation
+/// when dropped, which also happens if it errors, panics or is cancelled.
+pub(crate) struct InvocationGuard<'a> {
+    in_flight_invocations: &'a std::sync::atomic::AtomicUsize,
+}
+
+impl Drop for InvocationGuard<'_> {
+    fn drop(&mut self) {
+        self.in_flight_invocations
+            .fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+    }
+}
@@ -28,6 +28,23 @@ pub use reply::*;
 mod cooldown;
 pub use cooldown::*;
 
+mod concurrency;
+pub use concurrency::*;
+
+mod duration;
+pub use duration::*;
+
+mod component;
+pub use component::*;
+
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "metrics")]
+pub use metrics::*;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
 pub(crate) mod util;
 
 pub mod builtins;
@@ -0,0 +1,183 @@
+//! Optional command invocation metrics, enabled with the `metrics` feature
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Upper bounds of the [`DurationHistogram`] buckets. Durations above the last bound are counted
+/// in an additional overflow bucket.
+pub const DURATION_BUCKET_BOUNDS: &[std::time::Duration] = &[
+    std::time::Duration::from_millis(10),
+    std::time::Duration::from_millis(50),
+    std::time::Duration::from_millis(100),
+    std::time::Duration::from_millis(250),
+    std::time::Duration::from_millis(500),
+    std::time::Duration::from_secs(1),
+    std::time::Duration::from_millis(2500),
+    std::time::Duration::from_secs(5),
+    std::time::Duration::from_secs(10),
+];
+
+/// Snapshot of the invocation metrics of all commands, as returned by
+/// [`crate::Framework::stats`]
+#[derive(Clone, Debug, Default)]
+pub struct CommandStats {
+    /// Metrics per command, keyed by [`crate::CommandId::identifying_name`]. Commands which were
+    /// never invoked are absent.
+    pub commands: std::collections::HashMap<String, CommandMetrics>,
+}
+
+/// Invocation metrics of a single command
+#[derive(Clone, Debug, Default)]
+pub struct CommandMetrics {
+    /// How often the command action was started. Invocations rejected by checks or cooldowns are
+    /// not counted.
+    pub invocations: u64,
+    /// How many invocations returned `Ok`
+    pub successes: u64,
+    /// How many invocations returned `Err`
+    pub errors: u64,
+    /// How many invocations panicked
+    pub panics: u64,
+    /// Execution durations of all finished invocations
+    pub durations: DurationHistogram,
+}
+
+/// Histogram of command execution durations with fixed [buckets](DURATION_BUCKET_BOUNDS)
+#[derive(Clone, Debug, Default)]
+pub struct DurationHistogram {
+    /// Number of durations per bucket. Has one more entry than [`DURATION_BUCKET_BOUNDS`]; the
+    /// last entry counts durations above all bounds.
+    ///
+    /// The counts are not cumulative, i.e. each duration is counted in exactly one bucket
+    pub bucket_counts: Vec<u64>,
+    /// Sum of all recorded durations
+    pub total_duration: std::time::Duration,
+}
+
+/// Lock-free counters backing [`CommandMetrics`]
+#[derive(Default)]
+struct AtomicCommandMetrics {
+    invocations: AtomicU64,
+    successes: AtomicU64,
+    errors: AtomicU64,
+    panics: AtomicU64,
+    bucket_counts: [AtomicU64; 10],
+    total_duration_micros: AtomicU64,
+}
+
+impl AtomicCommandMetrics {
+    fn snapshot(&self) -> CommandMetrics {
+        CommandMetrics {
+            invocations: self.invocations.load(Ordering::Relaxed),
+            successes: self.successes.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            panics: self.panics.load(Ordering::Relaxed),
+            durations: DurationHistogram {
+                bucket_counts: self
+                    .bucket_counts
+                    .iter()
+                    .map(|count| count.load(Ordering::Relaxed))
+                    .collect(),
+                total_duration: std::time::Duration::from_micros(
+                    self.total_duration_micros.load(Ordering::Relaxed),
+                ),
+            },
+        }
+    }
+}
+
+/// Returns the index of the histogram bucket which the given duration falls into
+fn bucket_index(duration: std::time::Duration) -> usize {
+    DURATION_BUCKET_BOUNDS
+        .iter()
+        .position(|&bound| duration <= bound)
+        .unwrap_or_else(|| DURATION_BUCKET_BOUNDS.len())
+}
+
+/// Stores the metrics of all commands. Each command's counters are created on its first
+/// invocation and updated atomically afterwards, so the lock is only held for writing once per
+/// command.
+#[derive(Default)]
+pub(crate) struct MetricsRecorder {
+    commands:
+        std::sync::RwLock<std::collections::HashMap<String, std::sync::Arc<AtomicCommandMetrics>>>,
+}
+
+impl MetricsRecorder {
+    fn counters(&self, command_id: &crate::CommandId) -> std::sync::Arc<AtomicCommandMetrics> {
+        if let Some(counters) = self
+            .commands
+            .read()
+            .unwrap()
+            .get(&command_id.identifying_name)
+        {
+            return counters.clone();
+        }
+
+        self.commands
+            .write()
+            .unwrap()
+            .entry(command_id.identifying_name.clone())
+            .or_default()
+            .clone()
+    }
+
+    /// Records that the command action is about to run
+    pub(crate) fn record_invocation(&self, command_id: &crate::CommandId) {
+        self.counters(command_id)
+            .invocations
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records the outcome and execution duration of a finished command action
+    pub(crate) fn record_outcome<E>(
+        &self,
+        command_id: &crate::CommandId,
+        outcome: crate::CommandOutcome<'_, E>,
+        duration: std::time::Duration,
+    ) {
+        let counters = self.counters(command_id);
+        let outcome_counter = match outcome {
+            crate::CommandOutcome::Success => &counters.successes,
+            crate::CommandOutcome::Error(_) | crate::CommandOutcome::Timeout => &counters.errors,
+            crate::CommandOutcome::Panic => &counters.panics,
+            // The action didn't run at all
+            crate::CommandOutcome::Rejected => return,
+        };
+        outcome_counter.fetch_add(1, Ordering::Relaxed);
+        counters.bucket_counts[bucket_index(duration)].fetch_add(1, Ordering::Relaxed);
+        counters
+            .total_duration_micros
+            .fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> CommandStats {
+        CommandStats {
+            commands: self
+                .commands
+                .read()
+                .unwrap()
+                .iter()
+                .map(|(name, counters)| (name.clone(), counters.snapshot()))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bucket_index() {
+        assert_eq!(bucket_index(std::time::Duration::from_millis(0)), 0);
+        assert_eq!(bucket_index(std::time::Duration::from_millis(10)), 0);
+        assert_eq!(bucket_index(std::time::Duration::from_millis(11)), 1);
+        assert_eq!(bucket_index(std::time::Duration::from_millis(700)), 5);
+        assert_eq!(bucket_index(std::time::Duration::from_secs(10)), 8);
+        assert_eq!(bucket_index(std::time::Duration::from_secs(60)), 9);
+        assert_eq!(
+            AtomicCommandMetrics::default().bucket_counts.len(),
+            DURATION_BUCKET_BOUNDS.len() + 1
+        );
+    }
+}
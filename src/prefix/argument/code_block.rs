@@ -122,6 +122,59 @@ impl<'a> PopArgument<'a> for CodeBlock {
     }
 }
 
+/// Strips the surrounding triple backtick fences, along with the language code, from a multi-line
+/// code block. Text which isn't wrapped in a code block is returned unchanged.
+///
+/// Useful with `#[rest]` parameters, which capture the remaining arguments verbatim, including
+/// code blocks:
+///
+/// ```rust
+/// assert_eq!(poise::strip_code_fences("```rust\nlet x = 5;\n```"), "let x = 5;");
+/// assert_eq!(poise::strip_code_fences("let x = 5;"), "let x = 5;");
+/// ```
+///
+/// Unlike parsing a [`CodeBlock`], this doesn't fail on text without code block, and the code is
+/// borrowed from the input.
+pub fn strip_code_fences(text: &str) -> &str {
+    let code_block = text.trim();
+    let code_block = match code_block
+        .strip_prefix("```")
+        .and_then(|x| x.strip_suffix("```"))
+    {
+        Some(x) => x,
+        None => return text,
+    };
+
+    // Same language code and empty line rules as in CodeBlock::pop_from
+    let code_block = match code_block.find('\n') {
+        Some(first_newline) if !code_block[..first_newline].contains(char::is_whitespace) => {
+            &code_block[(first_newline + 1)..]
+        }
+        _ => code_block,
+    };
+    code_block.trim_start_matches('\n').trim_end_matches('\n')
+}
+
+#[cfg(test)]
+#[test]
+fn test_strip_code_fences() {
+    for &(text, code) in &[
+        (
+            "```rust\nfn main() {\n    println!(\"`hi`\");\n}\n```",
+            "fn main() {\n    println!(\"`hi`\");\n}",
+        ),
+        ("  ```\n\nfirst\nsecond\n\n```\n", "first\nsecond"),
+        ("```x = 1```", "x = 1"),
+        ("```py print(1)\n```", "py print(1)"),
+        ("``````", ""),
+        ("```", "```"),
+        ("`inline`", "`inline`"),
+        ("no code block", "no code block"),
+    ] {
+        assert_eq!(strip_code_fences(text), code);
+    }
+}
+
 #[cfg(test)]
 #[test]
 fn test_pop_code_block() {
@@ -30,6 +30,19 @@ pub trait PopArgument<'a>: Sized {
     /// Parse [`Self`] from the front of the given string and return a tuple of the remaining string
     /// and [`Self`].
     fn pop_from(args: &ArgString<'a>) -> Result<(ArgString<'a>, Self), Self::Err>;
+
+    /// Like [`Self::pop_from`], but respecting the quote pairs and argument delimiters configured
+    /// for the command. Used by [`crate::command`] generated code.
+    ///
+    /// Defaults to [`Self::pop_from`]. Only types which split the arguments themselves, like
+    /// [`String`], need to implement this.
+    fn pop_from_with_syntax(
+        args: &ArgString<'a>,
+        syntax: ArgumentSyntax,
+    ) -> Result<(ArgString<'a>, Self), Self::Err> {
+        let _ = syntax;
+        Self::pop_from(args)
+    }
 }
 
 /// Parse a value out of a string by popping off the front of the string. Discord message context
@@ -80,19 +93,262 @@ impl std::fmt::Display for TooManyArguments {
 
 impl std::error::Error for TooManyArguments {}
 
-/// The error type returned from [parse_prefix_args!]. It contains a `Box<dyn Error>`
+/// Error thrown if the number of arguments doesn't fit the command's parameters, i.e. required
+/// parameters were left unfilled or arguments remained after all parameters were filled.
+///
+/// Stored as the [`ArgumentParseError::source`] of argument parse errors of commands created with
+/// [`crate::command`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WrongArgumentCount {
+    /// Number of required parameters
+    pub min: usize,
+    /// Number of parameters, or None if a parameter takes any number of arguments, like `Vec` or
+    /// `#[rest]` parameters
+    pub max: Option<usize>,
+    /// Number of arguments given, not counting flags
+    pub got: usize,
+}
+
+impl std::fmt::Display for WrongArgumentCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let plural = |n| if n == 1 { "" } else { "s" };
+        match self.max {
+            Some(max) if max == self.min => write!(f, "Expected {} argument{}", max, plural(max))?,
+            Some(max) => write!(f, "Expected {} to {} arguments", self.min, max)?,
+            None => write!(
+                f,
+                "Expected at least {} argument{}",
+                self.min,
+                plural(self.min)
+            )?,
+        }
+        write!(f, ", but got {}", self.got)
+    }
+}
+
+impl std::error::Error for WrongArgumentCount {}
+
+impl WrongArgumentCount {
+    /// Counts the arguments and checks them against the parameters. `too_many_arguments` says
+    /// whether parsing failed due to [`TooManyArguments`]; otherwise, only missing arguments are
+    /// detected, because a failure with enough arguments is a genuine parse error.
+    fn detect(
+        parameters: &[crate::ParameterMeta],
+        args: &str,
+        too_many_arguments: bool,
+        syntax: ArgumentSyntax,
+    ) -> Option<Self> {
+        let positional = || parameters.iter().filter(|p| !p.is_flag);
+        let min = positional().filter(|p| p.required).count();
+        let max = if positional().any(|p| p.greedy) {
+            None
+        } else {
+            Some(positional().count())
+        };
+
+        let mut got = 0;
+        let mut args = ArgString(syntax.trim_separators(args));
+        while let Ok((rest, token)) = String::pop_from_with_syntax(&args, syntax) {
+            let is_flag = parameters
+                .iter()
+                .any(|p| p.is_flag && token.eq_ignore_ascii_case(p.name));
+            if !is_flag {
+                got += 1;
+            }
+            args = ArgString(syntax.trim_separators(rest.0));
+        }
+
+        let too_many = too_many_arguments && max.map_or(false, |max| got > max);
+        if too_many || got < min {
+            Some(Self { min, max, got })
+        } else {
+            None
+        }
+    }
+}
+
+/// Numeric parameter types whose value can be checked against `#[min]` and `#[max]` bounds.
+///
+/// Implemented for the primitive integer and float types, and for `Option`s of them (`None` is
+/// always in range).
+pub trait NumericArgument {
+    /// Returns the value for comparison with the bounds, or None if there is nothing to check
+    fn numeric_value(&self) -> Option<NumericValue>;
+}
+
+macro_rules! impl_numeric_argument {
+    ($variant:ident: $($t:ty)*) => { $(
+        impl NumericArgument for $t {
+            fn numeric_value(&self) -> Option<NumericValue> {
+                Some(NumericValue::$variant((*self).into()))
+            }
+        }
+    )* };
+}
+impl_numeric_argument!(Integer: u8 u16 u32 u64 i8 i16 i32 i64 i128);
+impl_numeric_argument!(Float: f32 f64);
+
+impl NumericArgument for u128 {
+    fn numeric_value(&self) -> Option<NumericValue> {
+        // Values which don't fit are above any bound anyway
+        Some(NumericValue::Integer(
+            std::convert::TryFrom::try_from(*self).unwrap_or(i128::MAX),
+        ))
+    }
+}
+
+impl NumericArgument for usize {
+    fn numeric_value(&self) -> Option<NumericValue> {
+        Some(NumericValue::Integer(*self as i128))
+    }
+}
+
+impl NumericArgument for isize {
+    fn numeric_value(&self) -> Option<NumericValue> {
+        Some(NumericValue::Integer(*self as i128))
+    }
+}
+
+impl<T: NumericArgument> NumericArgument for Option<T> {
+    fn numeric_value(&self) -> Option<NumericValue> {
+        self.as_ref().and_then(T::numeric_value)
+    }
+}
+
+/// Value of a [`NumericArgument`]. Integers are compared with the bounds exactly, without
+/// rounding them to a float first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumericValue {
+    /// Value of an integer type. `u128` values above [`i128::MAX`] are stored as [`i128::MAX`]
+    Integer(i128),
+    /// Value of a float type
+    Float(f64),
+}
+
+impl std::fmt::Display for NumericValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Integer(x) => write!(f, "{}", x),
+            Self::Float(x) => write!(f, "{}", x),
+        }
+    }
+}
+
+/// Error thrown if a numeric argument lies outside the bounds set via `#[min]` and `#[max]`.
+/// Both bounds are inclusive.
+///
+/// Stored as the [`ArgumentParseError::source`] of argument parse errors of commands created with
+/// [`crate::command`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutOfRange {
+    /// The lower bound, if any
+    pub min: Option<i64>,
+    /// The upper bound, if any
+    pub max: Option<i64>,
+    /// The value that was given
+    pub got: NumericValue,
+}
+
+impl OutOfRange {
+    /// Checks the value against the given inclusive bounds. Called by [`crate::command`] generated
+    /// code after parsing the arguments
+    pub fn check(
+        value: &impl NumericArgument,
+        min: Option<i64>,
+        max: Option<i64>,
+    ) -> Result<(), Self> {
+        let got = match value.numeric_value() {
+            Some(got) => got,
+            None => return Ok(()),
+        };
+        let out_of_range = match got {
+            NumericValue::Integer(x) => {
+                min.map_or(false, |min| x < i128::from(min))
+                    || max.map_or(false, |max| x > i128::from(max))
+            }
+            NumericValue::Float(x) => {
+                min.map_or(false, |min| x < min as f64)
+                    || max.map_or(false, |max| x > max as f64)
+                    // NaN compares false with everything, so it needs to be rejected explicitly
+                    || (x.is_nan() && (min.is_some() || max.is_some()))
+            }
+        };
+        if out_of_range {
+            Err(Self { min, max, got })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl std::fmt::Display for OutOfRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => write!(f, "Expected a number from {} to {}", min, max)?,
+            (Some(min), None) => write!(f, "Expected a number of at least {}", min)?,
+            (None, Some(max)) => write!(f, "Expected a number of at most {}", max)?,
+            (None, None) => f.write_str("Expected a number")?,
+        }
+        write!(f, ", but got {}", self.got)
+    }
+}
+
+impl std::error::Error for OutOfRange {}
+
+/// The error type returned from [parse_prefix_args!]. Contains the underlying parse error along
+/// with the position of the argument that failed to parse.
 #[derive(Debug)]
-pub struct ArgumentParseError(pub Box<dyn std::error::Error + Send + Sync>);
+pub struct ArgumentParseError {
+    /// Zero-based index of the argument that failed to parse. If too many arguments were passed,
+    /// this is the number of parameters
+    pub arg_index: usize,
+    /// Name of the parameter that failed to parse. Filled in by [`crate::command`]; `None` if
+    /// [parse_prefix_args!] was invoked directly or too many arguments were passed
+    pub arg_name: Option<&'static str>,
+    /// The remaining input at the point where parsing failed, starting with the offending argument.
+    /// If the argument parsed but was out of range (see [`OutOfRange`]), this is the full input
+    pub input: String,
+    /// The error returned by the argument's parser
+    pub source: Box<dyn std::error::Error + Send + Sync>,
+}
+
+impl ArgumentParseError {
+    /// Replaces the source with [`WrongArgumentCount`] if the error was caused by too few or too
+    /// many arguments. Called by [`crate::command`] generated code
+    #[doc(hidden)]
+    pub fn detect_wrong_argument_count(
+        &mut self,
+        parameters: &[crate::ParameterMeta],
+        args: &str,
+        syntax: ArgumentSyntax,
+    ) {
+        let too_many_arguments = self.source.is::<TooManyArguments>();
+        if let Some(e) = WrongArgumentCount::detect(parameters, args, too_many_arguments, syntax) {
+            self.source = Box::new(e);
+        }
+    }
+}
 
 impl std::fmt::Display for ArgumentParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Failed to parse argument: {}", self.0)
+        // The error concerns all arguments rather than a single one
+        if self.source.is::<WrongArgumentCount>() {
+            return self.source.fmt(f);
+        }
+        match self.arg_name {
+            Some(arg_name) => write!(
+                f,
+                "Failed to parse argument `{}`: {}",
+                arg_name, self.source
+            ),
+            None => write!(f, "Failed to parse argument: {}", self.source),
+        }
     }
 }
 
 impl std::error::Error for ArgumentParseError {
     fn cause(&self) -> Option<&dyn std::error::Error> {
-        Some(&*self.0)
+        Some(&*self.source)
     }
 }
 
@@ -119,6 +375,7 @@ pub trait PrefixArgumentHack<'a, T> {
         args: &ArgString<'a>,
         ctx: &serenity::Context,
         msg: &serenity::Message,
+        syntax: ArgumentSyntax,
     ) -> Result<(ArgString<'a>, T), Self::Err>;
 }
 
@@ -161,13 +418,15 @@ impl<'a, T: serenity::ArgumentConvert + Send> PrefixArgumentHack<'a, T>
         args: &ArgString<'a>,
         ctx: &serenity::Context,
         msg: &serenity::Message,
+        syntax: ArgumentSyntax,
     ) -> Result<(ArgString<'a>, T), Self::Err> {
-        let (args, string) = String::pop_from(args).map_err(MaybeEmptyError::EmptyArgs)?;
+        let (args, string) =
+            String::pop_from_with_syntax(args, syntax).map_err(MaybeEmptyError::EmptyArgs)?;
         let object = T::convert(ctx, msg.guild_id, Some(msg.channel_id), &string)
             .await
             .map_err(MaybeEmptyError::ParseError)?;
 
-        Ok((ArgString(args.0.trim_start()), object))
+        Ok((ArgString(syntax.trim_separators(args.0)), object))
     }
 }
 
@@ -182,9 +441,178 @@ impl<'a, T: crate::PopArgument<'a> + Sync> PrefixArgumentHack<'a, T>
         args: &ArgString<'a>,
         _: &serenity::Context,
         _: &serenity::Message,
+        syntax: ArgumentSyntax,
     ) -> Result<(ArgString<'a>, T), Self::Err> {
-        let (args, object) = T::pop_from(args)?;
+        let (args, object) = T::pop_from_with_syntax(args, syntax)?;
 
-        Ok((ArgString(args.0.trim_start()), object))
+        Ok((ArgString(syntax.trim_separators(args.0)), object))
     }
 }
+
+#[cfg(test)]
+#[test]
+fn test_wrong_argument_count() {
+    let param = |name, required, is_flag, greedy| crate::ParameterMeta {
+        name,
+        required,
+        description: None,
+        is_flag,
+        greedy,
+        min: None,
+        max: None,
+    };
+    let params = [
+        param("user", true, false, false),
+        param("silent", false, true, false),
+        param("days", false, false, false),
+    ];
+    let count = |min, max, got| Some(WrongArgumentCount { min, max, got });
+
+    assert_eq!(
+        WrongArgumentCount::detect(&params, "", false, ArgumentSyntax::default()),
+        count(1, Some(2), 0)
+    );
+    assert_eq!(
+        WrongArgumentCount::detect(&params, "silent", false, ArgumentSyntax::default()),
+        count(1, Some(2), 0)
+    );
+    assert_eq!(
+        WrongArgumentCount::detect(&params, "a b c", true, ArgumentSyntax::default()),
+        count(1, Some(2), 3)
+    );
+    // Quoted arguments count once, flags not at all
+    assert_eq!(
+        WrongArgumentCount::detect(
+            &params,
+            r#"silent "a b" c"#,
+            true,
+            ArgumentSyntax::default()
+        ),
+        None
+    );
+    // Enough arguments means that the failure was a genuine parse error
+    assert_eq!(
+        WrongArgumentCount::detect(&params, "a", false, ArgumentSyntax::default()),
+        None
+    );
+
+    let params = [
+        param("user", true, false, false),
+        param("reason", false, false, true),
+    ];
+    assert_eq!(
+        WrongArgumentCount::detect(&params, "a b c d", true, ArgumentSyntax::default()),
+        None
+    );
+    assert_eq!(
+        WrongArgumentCount::detect(&params, "", false, ArgumentSyntax::default()),
+        count(1, None, 0)
+    );
+
+    assert_eq!(
+        WrongArgumentCount {
+            min: 1,
+            max: Some(1),
+            got: 3
+        }
+        .to_string(),
+        "Expected 1 argument, but got 3"
+    );
+    assert_eq!(
+        WrongArgumentCount {
+            min: 1,
+            max: Some(2),
+            got: 0
+        }
+        .to_string(),
+        "Expected 1 to 2 arguments, but got 0"
+    );
+    assert_eq!(
+        WrongArgumentCount {
+            min: 2,
+            max: None,
+            got: 1
+        }
+        .to_string(),
+        "Expected at least 2 arguments, but got 1"
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_out_of_range() {
+    use NumericValue::{Float, Integer};
+    let err = |min, max, got| Err(OutOfRange { min, max, got });
+
+    // Below min and above max
+    assert_eq!(
+        OutOfRange::check(&0_u32, Some(1), Some(10)),
+        err(Some(1), Some(10), Integer(0))
+    );
+    assert_eq!(
+        OutOfRange::check(&11_i64, Some(1), Some(10)),
+        err(Some(1), Some(10), Integer(11))
+    );
+    assert_eq!(
+        OutOfRange::check(&-5_i32, Some(-4), None),
+        err(Some(-4), None, Integer(-5))
+    );
+    assert_eq!(
+        OutOfRange::check(&10.5_f64, None, Some(10)),
+        err(None, Some(10), Float(10.5))
+    );
+    assert_eq!(
+        OutOfRange::check(&Some(20_u8), None, Some(10)),
+        err(None, Some(10), Integer(20))
+    );
+    assert!(OutOfRange::check(&f64::NAN, Some(0), None).is_err());
+
+    // Boundary values are inclusive
+    assert_eq!(OutOfRange::check(&1_u32, Some(1), Some(10)), Ok(()));
+    assert_eq!(OutOfRange::check(&10_u32, Some(1), Some(10)), Ok(()));
+    assert_eq!(OutOfRange::check(&-4_i32, Some(-4), None), Ok(()));
+    assert_eq!(OutOfRange::check(&10.0_f32, None, Some(10)), Ok(()));
+    // Absent optional arguments and missing bounds are never out of range
+    assert_eq!(OutOfRange::check(&None::<u32>, Some(1), Some(10)), Ok(()));
+    assert_eq!(OutOfRange::check(&u64::MAX, None, None), Ok(()));
+
+    // Large integers are compared exactly. As f64, both sides would round to the same value
+    assert_eq!(
+        OutOfRange::check(&(i64::MAX as u64 + 1), None, Some(i64::MAX)),
+        err(None, Some(i64::MAX), Integer(i64::MAX as i128 + 1))
+    );
+    assert_eq!(
+        OutOfRange::check(&i64::MAX, None, Some(i64::MAX - 1)),
+        err(None, Some(i64::MAX - 1), Integer(i64::MAX as i128))
+    );
+    assert_eq!(OutOfRange::check(&i64::MIN, Some(i64::MIN), None), Ok(()));
+    assert!(OutOfRange::check(&u128::MAX, None, Some(i64::MAX)).is_err());
+
+    assert_eq!(
+        OutOfRange {
+            min: Some(1),
+            max: Some(10),
+            got: Integer(0)
+        }
+        .to_string(),
+        "Expected a number from 1 to 10, but got 0"
+    );
+    assert_eq!(
+        OutOfRange {
+            min: None,
+            max: Some(10),
+            got: Float(10.5)
+        }
+        .to_string(),
+        "Expected a number of at most 10, but got 10.5"
+    );
+    assert_eq!(
+        OutOfRange {
+            min: None,
+            max: Some(0),
+            got: Integer(u64::MAX.into())
+        }
+        .to_string(),
+        "Expected a number of at most 0, but got 18446744073709551615"
+    );
+}
@@ -1,53 +1,70 @@
+// Expands to the number of arguments parsed so far, i.e. the index of the argument currently being
+// parsed
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _prefix_arg_index {
+    ( $( $preamble:tt )* ) => {
+        <[&str]>::len(&[ $( stringify!($preamble) ),* ])
+    };
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _parse_prefix {
     // All arguments have been consumed
-    ( $ctx:ident $msg:ident $args:ident => [ $error:ident $( $name:ident )* ] ) => {
+    ( $ctx:ident $msg:ident $syntax:ident $args:ident => [ $error:ident $( $name:ident )* ] ) => {
         if $args.0.is_empty() {
             return Ok(( $( $name, )* ));
         }
+        if $error.2.is::<$crate::TooManyArguments>() {
+            $error = (
+                $crate::_prefix_arg_index!($( $name )*),
+                $args.0,
+                Box::new($crate::TooManyArguments),
+            );
+        }
     };
 
     // Consume Option<T> greedy-first
-    ( $ctx:ident $msg:ident $args:ident => [ $error:ident $($preamble:tt)* ]
+    ( $ctx:ident $msg:ident $syntax:ident $args:ident => [ $error:ident $($preamble:tt)* ]
         (Option<$type:ty $(,)?>)
         $( $rest:tt )*
     ) => {
-        match (&PhantomData::<$type>).pop(&$args, $ctx, $msg).await {
+        match (&PhantomData::<$type>).pop(&$args, $ctx, $msg, $syntax).await {
             Ok(($args, token)) => {
                 let token: Option<$type> = Some(token);
-                $crate::_parse_prefix!($ctx $msg $args => [ $error $($preamble)* token ] $($rest)* );
+                $crate::_parse_prefix!($ctx $msg $syntax $args => [ $error $($preamble)* token ] $($rest)* );
             },
-            Err(e) => $error = Box::new(e),
+            Err(e) => $error = ($crate::_prefix_arg_index!($($preamble)*), $args.0, Box::new(e)),
         }
         let token: Option<$type> = None;
-        $crate::_parse_prefix!($ctx $msg $args => [ $error $($preamble)* token ] $($rest)* );
+        $crate::_parse_prefix!($ctx $msg $syntax $args => [ $error $($preamble)* token ] $($rest)* );
     };
 
     // Consume Option<T> lazy-first
-    ( $ctx:ident $msg:ident $args:ident => [ $error:ident $($preamble:tt)* ]
+    ( $ctx:ident $msg:ident $syntax:ident $args:ident => [ $error:ident $($preamble:tt)* ]
         (#[lazy] Option<$type:ty $(,)?>)
         $( $rest:tt )*
     ) => {
         let token: Option<$type> = None;
-        $crate::_parse_prefix!($ctx $msg $args => [ $error $($preamble)* token ] $($rest)* );
-        match (&PhantomData::<$type>).pop(&$args, $ctx, $msg).await {
+        $crate::_parse_prefix!($ctx $msg $syntax $args => [ $error $($preamble)* token ] $($rest)* );
+        match (&PhantomData::<$type>).pop(&$args, $ctx, $msg, $syntax).await {
             Ok(($args, token)) => {
                 let token: Option<$type> = Some(token);
-                $crate::_parse_prefix!($ctx $msg $args => [ $error $($preamble)* token ] $($rest)* );
+                $crate::_parse_prefix!($ctx $msg $syntax $args => [ $error $($preamble)* token ] $($rest)* );
             },
-            Err(e) => $error = Box::new(e),
+            Err(e) => $error = ($crate::_prefix_arg_index!($($preamble)*), $args.0, Box::new(e)),
         }
     };
 
     // Consume #[rest] Option<T> until the end of the input
-    ( $ctx:ident $msg:ident $args:ident => [ $error:ident $($preamble:tt)* ]
+    ( $ctx:ident $msg:ident $syntax:ident $args:ident => [ $error:ident $($preamble:tt)* ]
         (#[rest] Option<$type:ty $(,)?>)
         $( $rest:tt )*
     ) => {
         if $args.0.trim_start().is_empty() {
             let token: Option<$type> = None;
-            $crate::_parse_prefix!($ctx $msg $args => [ $error $($preamble)* token ]);
+            $crate::_parse_prefix!($ctx $msg $syntax $args => [ $error $($preamble)* token ]);
         } else {
             match <$type as $crate::serenity_prelude::ArgumentConvert>::convert(
                 $ctx, $msg.guild_id, Some($msg.channel_id), $args.0.trim_start()
@@ -55,15 +72,15 @@ macro_rules! _parse_prefix {
                 Ok(token) => {
                     let $args = $crate::ArgString("");
                     let token = Some(token);
-                    $crate::_parse_prefix!($ctx $msg $args => [ $error $($preamble)* token ]);
+                    $crate::_parse_prefix!($ctx $msg $syntax $args => [ $error $($preamble)* token ]);
                 },
-                Err(e) => $error = Box::new(e),
+                Err(e) => $error = ($crate::_prefix_arg_index!($($preamble)*), $args.0, Box::new(e)),
             }
         }
     };
 
     // Consume Vec<T> greedy-first
-    ( $ctx:ident $msg:ident $args:ident => [ $error:ident $($preamble:tt)* ]
+    ( $ctx:ident $msg:ident $syntax:ident $args:ident => [ $error:ident $($preamble:tt)* ]
         (Vec<$type:ty $(,)?>)
         $( $rest:tt )*
     ) => {
@@ -72,14 +89,14 @@ macro_rules! _parse_prefix {
 
         let mut running_args = $args.clone();
         loop {
-            match (&PhantomData::<$type>).pop(&running_args, $ctx, $msg).await {
+            match (&PhantomData::<$type>).pop(&running_args, $ctx, $msg, $syntax).await {
                 Ok((popped_args, token)) => {
                     tokens.push(token);
                     token_rest_args.push(popped_args.clone());
                     running_args = popped_args;
                 },
                 Err(e) => {
-                    $error = Box::new(e);
+                    $error = ($crate::_prefix_arg_index!($($preamble)*), running_args.0, Box::new(e));
                     break;
                 }
 
@@ -88,7 +105,7 @@ macro_rules! _parse_prefix {
 
         // This will run at least once
         while let Some(token_rest_args) = token_rest_args.pop() {
-            $crate::_parse_prefix!($ctx $msg token_rest_args => [ $error $($preamble)* tokens ] $($rest)* );
+            $crate::_parse_prefix!($ctx $msg $syntax token_rest_args => [ $error $($preamble)* tokens ] $($rest)* );
             tokens.pop();
         }
     };
@@ -97,7 +114,7 @@ macro_rules! _parse_prefix {
     // inconsistency and also the further implementation work makes it not worth it.
 
     // Consume #[rest] T as the last argument
-    ( $ctx:ident $msg:ident $args:ident => [ $error:ident $($preamble:tt)* ]
+    ( $ctx:ident $msg:ident $syntax:ident $args:ident => [ $error:ident $($preamble:tt)* ]
         // question to my former self: why the $(poise::)* ?
         (#[rest] $(poise::)* $type:ty)
     ) => {
@@ -106,36 +123,40 @@ macro_rules! _parse_prefix {
         ).await {
             Ok(token) => {
                 let $args = $crate::ArgString("");
-                $crate::_parse_prefix!($ctx $msg $args => [ $error $($preamble)* token ]);
+                $crate::_parse_prefix!($ctx $msg $syntax $args => [ $error $($preamble)* token ]);
             },
-            Err(e) => $error = Box::new(e),
+            Err(e) => $error = ($crate::_prefix_arg_index!($($preamble)*), $args.0, Box::new(e)),
         }
     };
 
     // Consume #[flag] FLAGNAME
-    ( $ctx:ident $msg:ident $args:ident => [ $error:ident $($preamble:tt)* ]
+    ( $ctx:ident $msg:ident $syntax:ident $args:ident => [ $error:ident $($preamble:tt)* ]
         (#[flag] $name:literal)
         $( $rest:tt )*
     ) => {
-        if let Ok(($args, token)) = (&PhantomData::<String>).pop(&$args, $ctx, $msg).await {
+        if let Ok(($args, token)) = (&PhantomData::<String>).pop(&$args, $ctx, $msg, $syntax).await {
             if token.eq_ignore_ascii_case($name) {
-                $crate::_parse_prefix!($ctx $msg $args => [ $error $($preamble)* true ] $($rest)* );
+                $crate::_parse_prefix!($ctx $msg $syntax $args => [ $error $($preamble)* true ] $($rest)* );
             }
         }
-        $error = concat!("Must use either `", $name, "` or nothing as a modifier").into();
-        $crate::_parse_prefix!($ctx $msg $args => [ $error $($preamble)* false ] $($rest)* );
+        $error = (
+            $crate::_prefix_arg_index!($($preamble)*),
+            $args.0,
+            concat!("Must use either `", $name, "` or nothing as a modifier").into(),
+        );
+        $crate::_parse_prefix!($ctx $msg $syntax $args => [ $error $($preamble)* false ] $($rest)* );
     };
 
     // Consume T
-    ( $ctx:ident $msg:ident $args:ident => [ $error:ident $($preamble:tt)* ]
+    ( $ctx:ident $msg:ident $syntax:ident $args:ident => [ $error:ident $($preamble:tt)* ]
         ($type:ty)
         $( $rest:tt )*
     ) => {
-        match (&PhantomData::<$type>).pop(&$args, $ctx, $msg).await {
+        match (&PhantomData::<$type>).pop(&$args, $ctx, $msg, $syntax).await {
             Ok(($args, token)) => {
-                $crate::_parse_prefix!($ctx $msg $args => [ $error $($preamble)* token ] $($rest)* );
+                $crate::_parse_prefix!($ctx $msg $syntax $args => [ $error $($preamble)* token ] $($rest)* );
             },
-            Err(e) => $error = Box::new(e),
+            Err(e) => $error = ($crate::_prefix_arg_index!($($preamble)*), $args.0, Box::new(e)),
         }
     };
 
@@ -150,6 +171,10 @@ Macro for parsing an argument string into multiple parameter types.
 An invocation of this macro is generated by the [`crate::command`] macro, so you usually don't need
 to use this macro directly.
 
+The arguments are split at whitespace and grouped by double quotes. To use other quote pairs and
+delimiters, pass an [`crate::ArgumentSyntax`] after the arguments:
+`parse_prefix_args!(ctx, msg, args, syntax => ...)`.
+
 ```rust
 # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
 # use poise::serenity_prelude as serenity;
@@ -196,6 +221,17 @@ macro_rules! parse_prefix_args {
     ($ctx:expr, $msg:expr, $args:expr => $(
         $( #[$attr:ident] )?
         ( $($type:tt)* )
+    ),* $(,)? ) => {
+        $crate::parse_prefix_args!(
+            $ctx, $msg, $args, $crate::ArgumentSyntax::default() => $(
+                $( #[$attr] )?
+                ( $($type)* )
+            ),*
+        )
+    };
+    ($ctx:expr, $msg:expr, $args:expr, $syntax:expr => $(
+        $( #[$attr:ident] )?
+        ( $($type:tt)* )
     ),* $(,)? ) => {
         async {
             use std::marker::PhantomData;
@@ -203,17 +239,26 @@ macro_rules! parse_prefix_args {
 
             let ctx = $ctx;
             let msg = $msg;
+            let syntax: $crate::ArgumentSyntax = $syntax;
             let args = $crate::ArgString($args);
 
-            let mut error = Box::new($crate::TooManyArguments) as Box<dyn std::error::Error + Send + Sync>;
+            // Index of the failed argument, the remaining input at that point, and the error
+            let mut error: (usize, &str, Box<dyn std::error::Error + Send + Sync>) =
+                (0, args.0, Box::new($crate::TooManyArguments));
 
             $crate::_parse_prefix!(
-                ctx msg args => [error]
+                ctx msg syntax args => [error]
                 $(
                     ($( #[$attr] )? $($type)*)
                 )*
             );
-            Err($crate::ArgumentParseError(error))
+            let (arg_index, input, source) = error;
+            Err($crate::ArgumentParseError {
+                arg_index,
+                arg_name: None,
+                input: input.trim_start().to_owned(),
+                source,
+            })
         }
     };
 }
@@ -293,6 +338,12 @@ mod test {
                 .unwrap(),
             ("a".into(), "b c".into()),
         );
+        assert_eq!(
+            parse_prefix_args!(&ctx, &msg, "py ```py\nprint(\"a  b\")\n\nx = '`'\n```" => (String), #[rest] (String))
+                .await
+                .unwrap(),
+            ("py".into(), "```py\nprint(\"a  b\")\n\nx = '`'\n```".into()),
+        );
         assert_eq!(
             parse_prefix_args!(&ctx, &msg, "hello" => #[flag] ("hello"), #[rest] (String))
                 .await
@@ -305,5 +356,113 @@ mod test {
                 .unwrap(),
             (false, "helloo".into())
         );
+
+        // Configured quote pairs and delimiters are understood, and #[rest] parameters get the
+        // remaining text verbatim
+        let syntax = crate::ArgumentSyntax {
+            quote_pairs: &[('"', '"'), ('“', '”')],
+            delimiters: &[','],
+        };
+        assert_eq!(
+            parse_prefix_args!(&ctx, &msg, r#"“a b”,c, 5" tall, “x”"#, syntax => (String), (String), #[rest] (String))
+                .await
+                .unwrap(),
+            ("a b".into(), "c".into(), r#"5" tall, “x”"#.into()),
+        );
+        assert_eq!(
+            parse_prefix_args!(&ctx, &msg, "a,,b c" , syntax => (Vec<String>))
+                .await
+                .unwrap(),
+            (vec!["a".into(), "b".into(), "c".into()],),
+        );
+
+        // Trailing Option parameters are None if omitted, and report parse errors if present
+        assert_eq!(
+            parse_prefix_args!(&ctx, &msg, "5" => (u32), (Option<String>))
+                .await
+                .unwrap(),
+            (5, None),
+        );
+        assert_eq!(
+            parse_prefix_args!(&ctx, &msg, "5 spamming" => (u32), (Option<String>))
+                .await
+                .unwrap(),
+            (5, Some("spamming".into())),
+        );
+        assert_eq!(
+            parse_prefix_args!(&ctx, &msg, "5 " => (u32), #[rest] (Option<String>))
+                .await
+                .unwrap(),
+            (5, None),
+        );
+        assert_eq!(
+            parse_prefix_args!(&ctx, &msg, "5 spamming links" => (u32), #[rest] (Option<String>))
+                .await
+                .unwrap(),
+            (5, Some("spamming links".into())),
+        );
+        let error = parse_prefix_args!(&ctx, &msg, "5 x" => (u32), (Option<u32>))
+            .await
+            .unwrap_err();
+        assert!(!error.source.is::<crate::TooManyArguments>());
+        assert_eq!((error.arg_index, error.input.as_str()), (1, "x"));
+        let error = parse_prefix_args!(&ctx, &msg, "5 6 7" => (u32), (Option<u32>))
+            .await
+            .unwrap_err();
+        assert!(error.source.is::<crate::TooManyArguments>());
+
+        // Vec parameters take as many arguments as parse successfully, and no more
+        assert_eq!(
+            parse_prefix_args!(&ctx, &msg, "reason" => (Vec<u32>), #[rest] (String))
+                .await
+                .unwrap(),
+            (vec![], "reason".into()),
+        );
+        assert_eq!(
+            parse_prefix_args!(&ctx, &msg, "1 reason" => (Vec<u32>), #[rest] (String))
+                .await
+                .unwrap(),
+            (vec![1], "reason".into()),
+        );
+        assert_eq!(
+            parse_prefix_args!(&ctx, &msg, "1 2 3 a long reason" => (Vec<u32>), #[rest] (String))
+                .await
+                .unwrap(),
+            (vec![1, 2, 3], "a long reason".into()),
+        );
+        assert_eq!(
+            parse_prefix_args!(&ctx, &msg, "1 2 x 3" => (Vec<u32>), (String), (u32))
+                .await
+                .unwrap(),
+            (vec![1, 2], "x".into(), 3),
+        );
+        assert_eq!(
+            parse_prefix_args!(&ctx, &msg, "" => (Vec<u32>))
+                .await
+                .unwrap(),
+            (vec![],),
+        );
+        // If the following parameters don't parse otherwise, the Vec gives back arguments
+        assert_eq!(
+            parse_prefix_args!(&ctx, &msg, "1 2 3" => (Vec<u32>), (u32))
+                .await
+                .unwrap(),
+            (vec![1, 2], 3),
+        );
+        // The reported error is where the Vec stopped parsing
+        let error = parse_prefix_args!(&ctx, &msg, "1 x y" => (Vec<u32>), (String))
+            .await
+            .unwrap_err();
+        assert_eq!((error.arg_index, error.input.as_str()), (0, "x y"));
+
+        let error = parse_prefix_args!(&ctx, &msg, "a b" => (String), (u32))
+            .await
+            .unwrap_err();
+        assert_eq!((error.arg_index, error.input.as_str()), (1, "b"));
+        let error = parse_prefix_args!(&ctx, &msg, "a b" => (String))
+            .await
+            .unwrap_err();
+        assert!(error.source.is::<crate::TooManyArguments>());
+        assert_eq!((error.arg_index, error.input.as_str()), (1, "b"));
     }
 }
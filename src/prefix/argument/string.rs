@@ -11,6 +11,56 @@ impl std::fmt::Display for EmptyArgs {
 
 impl std::error::Error for EmptyArgs {}
 
+/// Characters which group and separate the arguments of a prefix command. Used by the argument
+/// parsers which split the arguments themselves, like [`String`], via
+/// [`PopArgument::pop_from_with_syntax`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ArgumentSyntax {
+    /// Pairs of opening and closing quote characters which group arguments with whitespace, see
+    /// [`crate::PrefixFrameworkOptions::quote_pairs`]
+    pub quote_pairs: &'static [(char, char)],
+    /// Characters which separate arguments in addition to whitespace, see
+    /// [`crate::PrefixCommandOptions::argument_delimiters`]
+    pub delimiters: &'static [char],
+}
+
+impl Default for ArgumentSyntax {
+    /// Double quotes group arguments, and only whitespace separates them
+    fn default() -> Self {
+        Self {
+            quote_pairs: &[('"', '"')],
+            delimiters: &[],
+        }
+    }
+}
+
+impl ArgumentSyntax {
+    /// Whether the character separates arguments
+    pub fn is_separator(&self, c: char) -> bool {
+        c.is_whitespace() || self.delimiters.contains(&c)
+    }
+
+    /// Strips the separators between arguments from the start of the string
+    pub fn trim_separators<'a>(&self, args: &'a str) -> &'a str {
+        args.trim_start_matches(|c| self.is_separator(c))
+    }
+}
+
+/// Whether the text contains the given closing quote character, skipping escaped characters
+fn has_closing_quote(text: &str, closing_quote: char) -> bool {
+    let mut escaping = false;
+    for c in text.chars() {
+        if escaping {
+            escaping = false;
+        } else if c == '\\' {
+            escaping = true;
+        } else if c == closing_quote {
+            return true;
+        }
+    }
+    false
+}
+
 impl<'a> PopArgument<'a> for String {
     type Err = EmptyArgs;
 
@@ -31,18 +81,37 @@ impl<'a> PopArgument<'a> for String {
     /// );
     /// ```
     fn pop_from(args: &ArgString<'a>) -> Result<(ArgString<'a>, Self), Self::Err> {
-        // TODO: consider changing the behavior to parse quotes literally if they're in the middle
-        // of the string:
-        // - `"hello world"` => `hello world`
-        // - `"hello "world"` => `"hello "world`
-        // - `"hello" world"` => `hello`
+        Self::pop_from_with_syntax(args, ArgumentSyntax::default())
+    }
 
+    /// Like [`Self::pop_from`], but grouping words with the given quote pairs and splitting on the
+    /// given delimiters as well. Quotes without a matching closing quote are taken literally.
+    ///
+    /// ```rust
+    /// # use poise::{ArgString, ArgumentSyntax, PopArgument as _};
+    /// let syntax = ArgumentSyntax {
+    ///     quote_pairs: &[('"', '"'), ('“', '”')],
+    ///     delimiters: &[','],
+    /// };
+    /// assert_eq!(
+    ///     String::pop_from_with_syntax(&ArgString("“first, arg”,second"), syntax).unwrap(),
+    ///     (ArgString(",second"), "first, arg".into())
+    /// );
+    /// assert_eq!(
+    ///     String::pop_from_with_syntax(&ArgString(r#"5" tall"#), syntax).unwrap().1,
+    ///     r#"5""#
+    /// );
+    /// ```
+    fn pop_from_with_syntax(
+        args: &ArgString<'a>,
+        syntax: ArgumentSyntax,
+    ) -> Result<(ArgString<'a>, Self), Self::Err> {
         if args.0.is_empty() {
             return Err(EmptyArgs);
         }
 
         let mut output = String::new();
-        let mut inside_string = false;
+        let mut closing_quote = None;
         let mut escaping = false;
 
         let mut chars = args.0.chars();
@@ -52,14 +121,24 @@ impl<'a> PopArgument<'a> for String {
             if escaping {
                 output.push(c);
                 escaping = false;
-            } else if !inside_string && c.is_whitespace() {
-                break;
-            } else if c == '"' {
-                inside_string = !inside_string;
             } else if c == '\\' {
                 escaping = true;
+            } else if let Some(closing) = closing_quote {
+                if c == closing {
+                    closing_quote = None;
+                } else {
+                    output.push(c);
+                }
+            } else if syntax.is_separator(c) {
+                break;
             } else {
-                output.push(c);
+                let after = &chars.as_str()[c.len_utf8()..];
+                match syntax.quote_pairs.iter().find(|&&(open, _)| open == c) {
+                    Some(&(_, closing)) if has_closing_quote(after, closing) => {
+                        closing_quote = Some(closing);
+                    }
+                    _ => output.push(c),
+                }
             }
 
             chars.next();
@@ -81,13 +160,43 @@ fn test_pop_string() {
     for &(string, arg) in &[
         (r#"AA BB"#, r#"AA"#),
         (r#""AA BB""#, r#"AA BB"#),
-        (r#""AA BB"#, r#"AA BB"#),
+        (r#""AA BB"#, r#""AA"#),
         (r#""AA "BB"#, r#"AA BB"#),
-        (r#"""""A""A" "B"""B"#, r#"AA BB"#),
+        (r#"""""A""A" "B"""B"#, r#"AA B"B"#),
         (r#"\"AA BB\""#, r#""AA"#),
         (r#"\"AA\ BB\""#, r#""AA BB""#),
         (r#""\"AA BB\"""#, r#""AA BB""#),
+        (r#"5" tall"#, r#"5""#),
     ] {
         assert_eq!(String::pop_from(&ArgString(string)).unwrap().1, arg);
     }
 }
+
+#[cfg(test)]
+#[test]
+fn test_pop_string_with_syntax() {
+    let syntax = ArgumentSyntax {
+        quote_pairs: &[('"', '"'), ('“', '”'), ('\'', '\'')],
+        delimiters: &[','],
+    };
+    for &(string, arg, rest) in &[
+        ("plain args", "plain", " args"),
+        ("a,b", "a", ",b"),
+        (r#""a, b",c"#, "a, b", ",c"),
+        ("“a b” c", "a b", " c"),
+        ("'a b' c", "a b", " c"),
+        (r#"“say "hi"” c"#, r#"say "hi""#, " c"),
+        ("don't stop", "don't", " stop"),
+        ("“unclosed arg", "“unclosed", " arg"),
+        (r#"\“a b”"#, "“a", " b”"),
+        (r#"“a \” b”"#, "a ” b", ""),
+        (r#"a\,b,c"#, "a,b", ",c"),
+    ] {
+        assert_eq!(
+            String::pop_from_with_syntax(&ArgString(string), syntax).unwrap(),
+            (ArgString(rest), arg.into())
+        );
+    }
+
+    assert_eq!(syntax.trim_separators(", ,a"), "a");
+}
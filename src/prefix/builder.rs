@@ -0,0 +1,401 @@
+use super::*;
+use crate::{serenity_prelude as serenity, BoxFuture};
+
+/// A builder for [`PrefixFrameworkOptions`], as an alternative to struct literal syntax with
+/// `..Default::default()`. Every option not set on the builder keeps its default value.
+///
+/// ```rust
+/// # type Error = Box<dyn std::error::Error + Send + Sync>;
+/// let prefix_options = poise::PrefixFrameworkOptions::<(), Error>::builder()
+///     .prefix("~")
+///     .literal_prefix("hey bot,")
+///     .mention_as_prefix(false)
+///     .case_insensitive_commands(false)
+///     .build();
+/// ```
+pub struct PrefixFrameworkOptionsBuilder<U, E> {
+    options: PrefixFrameworkOptions<U, E>,
+}
+
+impl<U: Send + Sync, E> Default for PrefixFrameworkOptionsBuilder<U, E> {
+    fn default() -> Self {
+        Self {
+            options: PrefixFrameworkOptions::default(),
+        }
+    }
+}
+
+impl<U: Send + Sync, E> PrefixFrameworkOptions<U, E> {
+    /// Creates a [`PrefixFrameworkOptionsBuilder`] starting from the default options
+    pub fn builder() -> PrefixFrameworkOptionsBuilder<U, E> {
+        PrefixFrameworkOptionsBuilder::default()
+    }
+}
+
+impl<U, E> PrefixFrameworkOptionsBuilder<U, E> {
+    /// Finishes the builder
+    pub fn build(self) -> PrefixFrameworkOptions<U, E> {
+        self.options
+    }
+
+    /// See [`PrefixFrameworkOptions::prefix`]
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.options.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Adds a command to [`PrefixFrameworkOptions::commands`]
+    pub fn command(mut self, command: PrefixCommandMeta<U, E>) -> Self {
+        self.options.commands.push(command);
+        self
+    }
+
+    /// Adds multiple commands to [`PrefixFrameworkOptions::commands`]
+    pub fn commands(mut self, commands: impl IntoIterator<Item = PrefixCommandMeta<U, E>>) -> Self {
+        self.options.commands.extend(commands);
+        self
+    }
+
+    /// Adds a prefix to [`PrefixFrameworkOptions::literal_prefixes`]
+    pub fn literal_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.options.literal_prefixes.push(prefix.into());
+        self
+    }
+
+    /// Adds a prefix to [`PrefixFrameworkOptions::additional_prefixes`]
+    pub fn additional_prefix(mut self, prefix: Prefix) -> Self {
+        self.options.additional_prefixes.push(prefix);
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::dynamic_prefix`]
+    pub fn dynamic_prefix(
+        mut self,
+        dynamic_prefix: for<'a> fn(
+            &'a serenity::Context,
+            &'a serenity::Message,
+            &'a U,
+        ) -> BoxFuture<'a, Option<std::borrow::Cow<'a, str>>>,
+    ) -> Self {
+        self.options.dynamic_prefix = Some(dynamic_prefix);
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::dynamic_prefix_cache`]
+    pub fn dynamic_prefix_cache(mut self, cache: DynamicPrefixCache) -> Self {
+        self.options.dynamic_prefix_cache = Some(cache);
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::stripped_dynamic_prefix`]
+    pub fn stripped_dynamic_prefix(
+        mut self,
+        stripped_dynamic_prefix: for<'a> fn(
+            &'a serenity::Context,
+            &'a serenity::Message,
+            &'a U,
+        ) -> BoxFuture<'a, Option<(&'a str, &'a str)>>,
+    ) -> Self {
+        self.options.stripped_dynamic_prefix = Some(stripped_dynamic_prefix);
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::custom_dispatch`]
+    pub fn custom_dispatch(
+        mut self,
+        custom_dispatch: for<'a> fn(
+            PrefixContext<'a, U, E>,
+        ) -> BoxFuture<
+            'a,
+            Option<(&'a PrefixCommandMeta<U, E>, &'a str)>,
+        >,
+    ) -> Self {
+        self.options.custom_dispatch = Some(custom_dispatch);
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::mention_as_prefix`]
+    pub fn mention_as_prefix(mut self, mention_as_prefix: bool) -> Self {
+        self.options.mention_as_prefix = mention_as_prefix;
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::mention_as_prefix_for`]
+    pub fn mention_as_prefix_for(
+        mut self,
+        callback: for<'a> fn(
+            &'a serenity::Context,
+            &'a serenity::Message,
+            &'a U,
+        ) -> BoxFuture<'a, bool>,
+    ) -> Self {
+        self.options.mention_as_prefix_for = Some(callback);
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::ignore_prefix_in_dms`]
+    pub fn ignore_prefix_in_dms(mut self, ignore_prefix_in_dms: bool) -> Self {
+        self.options.ignore_prefix_in_dms = ignore_prefix_in_dms;
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::invoke_on_mention`]
+    pub fn invoke_on_mention(
+        mut self,
+        callback: fn(PrefixContext<'_, U, E>) -> BoxFuture<'_, Result<(), E>>,
+    ) -> Self {
+        self.options.invoke_on_mention = Some(callback);
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::non_command_message`]
+    pub fn non_command_message(
+        mut self,
+        callback: fn(PrefixContext<'_, U, E>) -> BoxFuture<'_, Result<(), E>>,
+    ) -> Self {
+        self.options.non_command_message = Some(callback);
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::on_unknown_command`]
+    pub fn on_unknown_command(
+        mut self,
+        callback: for<'a> fn(
+            PrefixContext<'a, U, E>,
+            &'a str,
+            Vec<&'static str>,
+        ) -> BoxFuture<'a, ()>,
+    ) -> Self {
+        self.options.on_unknown_command = Some(callback);
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::unknown_command_max_distance`]
+    pub fn unknown_command_max_distance(mut self, max_distance: usize) -> Self {
+        self.options.unknown_command_max_distance = max_distance;
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::edit_tracker`]
+    pub fn edit_tracker(mut self, edit_tracker: std::sync::RwLock<EditTracker>) -> Self {
+        self.options.edit_tracker = Some(edit_tracker);
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::on_response_edited`]
+    pub fn on_response_edited(
+        mut self,
+        callback: for<'a> fn(
+            PrefixContext<'a, U, E>,
+            &'a serenity::Message,
+            &'a serenity::Message,
+        ) -> BoxFuture<'a, ()>,
+    ) -> Self {
+        self.options.on_response_edited = Some(callback);
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::execute_untracked_edits`]
+    pub fn execute_untracked_edits(mut self, execute_untracked_edits: bool) -> Self {
+        self.options.execute_untracked_edits = execute_untracked_edits;
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::ignore_edit_tracker_cache`]
+    pub fn ignore_edit_tracker_cache(mut self, ignore_edit_tracker_cache: bool) -> Self {
+        self.options.ignore_edit_tracker_cache = ignore_edit_tracker_cache;
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::on_edit_tracker_cache_miss`]
+    pub fn on_edit_tracker_cache_miss(
+        mut self,
+        callback: for<'a> fn(
+            &'a serenity::Context,
+            &'a serenity::MessageUpdateEvent,
+            &'a U,
+        ) -> BoxFuture<'a, Option<serenity::Message>>,
+    ) -> Self {
+        self.options.on_edit_tracker_cache_miss = Some(callback);
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::command_timeout`]
+    pub fn command_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.options.command_timeout = Some(timeout);
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::on_command_timeout`]. Pass None to silently ignore timeouts.
+    pub fn on_command_timeout(
+        mut self,
+        callback: Option<
+            fn(PrefixContext<'_, U, E>, std::time::Duration) -> BoxFuture<'_, Result<(), E>>,
+        >,
+    ) -> Self {
+        self.options.on_command_timeout = callback;
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::execute_self_messages`]
+    pub fn execute_self_messages(mut self, execute_self_messages: bool) -> Self {
+        self.options.execute_self_messages = execute_self_messages;
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::reply_to_invocation`]
+    pub fn reply_to_invocation(mut self, reply_to_invocation: bool) -> Self {
+        self.options.reply_to_invocation = reply_to_invocation;
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::mention_replied_user`]
+    pub fn mention_replied_user(mut self, mention_replied_user: bool) -> Self {
+        self.options.mention_replied_user = mention_replied_user;
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::quote_pairs`]
+    pub fn quote_pairs(mut self, quote_pairs: &'static [(char, char)]) -> Self {
+        self.options.quote_pairs = quote_pairs;
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::subcommand_required_handler`]. Pass None to silently ignore
+    /// such invocations.
+    pub fn subcommand_required_handler(
+        mut self,
+        handler: Option<
+            for<'a> fn(
+                PrefixContext<'a, U, E>,
+                &'a PrefixCommandMeta<U, E>,
+            ) -> BoxFuture<'a, Result<(), E>>,
+        >,
+    ) -> Self {
+        self.options.subcommand_required_handler = handler;
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::command_enabled`]
+    pub fn command_enabled(
+        mut self,
+        callback: for<'a> fn(
+            crate::Context<'a, U, E>,
+            &'a PrefixCommand<U, E>,
+        ) -> BoxFuture<'a, bool>,
+    ) -> Self {
+        self.options.command_enabled = Some(callback);
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::command_disabled_handler`]
+    pub fn command_disabled_handler(
+        mut self,
+        callback: fn(PrefixContext<'_, U, E>) -> BoxFuture<'_, Result<(), E>>,
+    ) -> Self {
+        self.options.command_disabled_handler = Some(callback);
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::case_insensitive_commands`]
+    pub fn case_insensitive_commands(mut self, case_insensitive_commands: bool) -> Self {
+        self.options.case_insensitive_commands = case_insensitive_commands;
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::command_name_normalizer`]
+    pub fn command_name_normalizer(
+        mut self,
+        normalizer: fn(&str) -> std::borrow::Cow<'_, str>,
+    ) -> Self {
+        self.options.command_name_normalizer = Some(normalizer);
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::case_insensitive_prefixes`]
+    pub fn case_insensitive_prefixes(mut self, case_insensitive_prefixes: bool) -> Self {
+        self.options.case_insensitive_prefixes = case_insensitive_prefixes;
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::require_space_after_prefix`]. Pass None to accept both.
+    pub fn require_space_after_prefix(mut self, require_space: Option<bool>) -> Self {
+        self.options.require_space_after_prefix = require_space;
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::trim_invisible_prefix`]
+    pub fn trim_invisible_prefix(mut self, trim_invisible_prefix: bool) -> Self {
+        self.options.trim_invisible_prefix = trim_invisible_prefix;
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::help_when_mentioned`]
+    pub fn help_when_mentioned(mut self, help_when_mentioned: bool) -> Self {
+        self.options.help_when_mentioned = help_when_mentioned;
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::help_command`]
+    pub fn help_command(mut self, help_command: PrefixCommandMeta<U, E>) -> Self {
+        self.options.help_command = Some(help_command);
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::help_config`]
+    pub fn help_config(mut self, help_config: crate::builtins::HelpConfig) -> Self {
+        self.options.help_config = help_config;
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::private_reply_reaction`]. Pass None to disable the reaction.
+    pub fn private_reply_reaction(mut self, reaction: Option<serenity::ReactionType>) -> Self {
+        self.options.private_reply_reaction = reaction;
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::private_reply_channel_fallback`]
+    pub fn private_reply_channel_fallback(mut self, private_reply_channel_fallback: bool) -> Self {
+        self.options.private_reply_channel_fallback = private_reply_channel_fallback;
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::fallback_to_dm_on_send_failure`]
+    pub fn fallback_to_dm_on_send_failure(mut self, fallback_to_dm_on_send_failure: bool) -> Self {
+        self.options.fallback_to_dm_on_send_failure = fallback_to_dm_on_send_failure;
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::locale`]
+    pub fn locale(
+        mut self,
+        locale: for<'a> fn(PrefixContext<'a, U, E>) -> BoxFuture<'a, Option<String>>,
+    ) -> Self {
+        self.options.locale = Some(locale);
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::blocked_channels`]
+    pub fn blocked_channels(
+        mut self,
+        channels: impl IntoIterator<Item = serenity::ChannelId>,
+    ) -> Self {
+        self.options.blocked_channels = std::sync::RwLock::new(channels.into_iter().collect());
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::blocked_categories`]
+    pub fn blocked_categories(
+        mut self,
+        categories: impl IntoIterator<Item = serenity::ChannelId>,
+    ) -> Self {
+        self.options.blocked_categories = std::sync::RwLock::new(categories.into_iter().collect());
+        self
+    }
+
+    /// See [`PrefixFrameworkOptions::help_uncategorized_label`]
+    pub fn help_uncategorized_label(mut self, label: impl Into<String>) -> Self {
+        self.options.help_uncategorized_label = label.into();
+        self
+    }
+}
@@ -0,0 +1,73 @@
+//! Caching layer for [`crate::PrefixFrameworkOptions::dynamic_prefix`]
+
+use crate::serenity_prelude as serenity;
+use std::time::{Duration, Instant};
+
+/// Caches the return values of [`crate::PrefixFrameworkOptions::dynamic_prefix`] per guild, so
+/// that the callback (which may do a database request) isn't invoked on every single message.
+///
+/// Entries expire after the configured time-to-live. Expiry is checked lazily when an entry is
+/// accessed; there is no background task.
+///
+/// Messages outside guilds are never cached.
+pub struct DynamicPrefixCache {
+    ttl: Duration,
+    cache:
+        std::sync::Mutex<std::collections::HashMap<serenity::GuildId, (Option<String>, Instant)>>,
+}
+
+impl DynamicPrefixCache {
+    /// Create a cache whose entries expire after the given duration
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            cache: std::sync::Mutex::new(std::collections::HashMap::new()),
+        }
+    }
+
+    /// Returns the cached dynamic prefix of the given guild, or None if there is no fresh cache
+    /// entry.
+    ///
+    /// The inner Option is the cached return value of the dynamic prefix callback.
+    pub fn get(&self, guild_id: serenity::GuildId) -> Option<Option<String>> {
+        let mut cache = self.cache.lock().unwrap();
+
+        let (prefix, inserted_at) = cache.get(&guild_id)?;
+        if inserted_at.elapsed() < self.ttl {
+            return Some(prefix.clone());
+        }
+
+        cache.remove(&guild_id);
+        None
+    }
+
+    /// Store the dynamic prefix of the given guild
+    pub fn insert(&self, guild_id: serenity::GuildId, prefix: Option<String>) {
+        self.cache
+            .lock()
+            .unwrap()
+            .insert(guild_id, (prefix, Instant::now()));
+    }
+
+    /// Forget the cached dynamic prefix of the given guild, for example because it was changed.
+    pub fn invalidate(&self, guild_id: serenity::GuildId) {
+        self.cache.lock().unwrap().remove(&guild_id);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_dynamic_prefix_cache() {
+    let guild_id = serenity::GuildId(1);
+
+    let cache = DynamicPrefixCache::new(Duration::from_secs(3600));
+    assert_eq!(cache.get(guild_id), None);
+    cache.insert(guild_id, Some("!".into()));
+    assert_eq!(cache.get(guild_id), Some(Some("!".into())));
+    cache.invalidate(guild_id);
+    assert_eq!(cache.get(guild_id), None);
+
+    let cache = DynamicPrefixCache::new(Duration::from_secs(0));
+    cache.insert(guild_id, None);
+    assert_eq!(cache.get(guild_id), None);
+}
@@ -6,5 +6,11 @@ pub use argument::*;
 mod track_edits;
 pub use track_edits::*;
 
+mod dynamic_prefix_cache;
+pub use dynamic_prefix_cache::*;
+
 mod structs;
 pub use structs::*;
+
+mod builder;
+pub use builder::*;
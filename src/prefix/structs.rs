@@ -10,8 +10,15 @@ pub struct PrefixContext<'a, U, E> {
     pub discord: &'a serenity::Context,
     /// The invoking user message
     pub msg: &'a serenity::Message,
-    /// Prefix used by the user to invoke this command
+    /// Prefix used by the user to invoke this command. For [`Prefix::Regex`] prefixes, this is
+    /// the text matched by the regex
     pub prefix: &'a str,
+    /// Byte index in the content of [`Self::msg`] where the command arguments start, i.e. after
+    /// the prefix, the command name and any subcommand names.
+    ///
+    /// Useful to access the raw arguments with their original whitespace:
+    /// `&ctx.msg.content[ctx.args_offset..]`
+    pub args_offset: usize,
     /// Read-only reference to the framework
     ///
     /// Useful if you need the list of commands, for example for a custom help command
@@ -21,8 +28,15 @@ pub struct PrefixContext<'a, U, E> {
     /// Optional to allow using [`PrefixContext`] in places where there is no command. For example
     /// Etternabot's message listener
     pub command: Option<&'a PrefixCommand<U, E>>,
+    /// Names of the invoked command's parent commands followed by its own name. Empty if there
+    /// is no command. See [`crate::Context::invoked_command_path`]
+    pub invoked_command_path: &'a [&'static str],
     /// Your custom user data
     pub data: &'a U,
+    /// Whether this invocation was triggered by an edit of the invoking message rather than by a
+    /// new message, for example with [`PrefixFrameworkOptions::execute_untracked_edits`] or
+    /// [`PrefixCommandOptions::track_edits`]
+    pub invoked_by_edit: bool,
 }
 // manual Copy+Clone implementations because Rust is getting confused about the type parameter
 impl<U, E> Clone for PrefixContext<'_, U, E> {
@@ -36,24 +50,154 @@ impl<U, E> crate::_GetGenerics for PrefixContext<'_, U, E> {
     type E = E;
 }
 
+impl<U, E> PrefixContext<'_, U, E> {
+    /// Returns the quote pairs and argument delimiters which the arguments of this invocation are
+    /// parsed with, see [`PrefixFrameworkOptions::quote_pairs`] and
+    /// [`PrefixCommandOptions::argument_delimiters`]
+    pub fn argument_syntax(&self) -> crate::ArgumentSyntax {
+        crate::ArgumentSyntax {
+            quote_pairs: self.framework.options().prefix_options.quote_pairs,
+            delimiters: match self.command {
+                Some(command) => command.options.argument_delimiters,
+                None => &[],
+            },
+        }
+    }
+
+    /// If the invoking message is a reply, returns the message it replies to. Useful for
+    /// confirmation prompts, where the user replies to the bot's question.
+    ///
+    /// Uses the referenced message sent along by Discord if available, and fetches it via HTTP
+    /// otherwise. Returns None if the invoking message isn't a reply.
+    pub async fn replied_to_message(&self) -> Result<Option<serenity::Message>, serenity::Error> {
+        if let Some(referenced_message) = &self.msg.referenced_message {
+            return Ok(Some((**referenced_message).clone()));
+        }
+
+        let reference = match &self.msg.message_reference {
+            Some(x) => x,
+            None => return Ok(None),
+        };
+        let message_id = match reference.message_id {
+            Some(x) => x,
+            None => return Ok(None),
+        };
+        let message = reference
+            .channel_id
+            .message(self.discord, message_id)
+            .await?;
+        Ok(Some(message))
+    }
+
+    /// Returns the ID of the shard which received the invoking message
+    pub fn shard_id(&self) -> serenity::ShardId {
+        serenity::ShardId(self.discord.shard_id)
+    }
+
+    /// Returns the guild ID of the invoking message, or None if it was sent in DMs
+    pub fn guild_id(&self) -> Option<serenity::GuildId> {
+        self.msg.guild_id
+    }
+
+    /// Returns the guild of the invoking message from the cache, or None if it was sent in DMs or
+    /// the guild isn't cached. In the latter case, the guild can be fetched via HTTP with
+    /// [`serenity::GuildId::to_partial_guild`].
+    ///
+    /// Warning: clones the entire Guild instance out of the cache
+    pub fn guild(&self) -> Option<serenity::Guild> {
+        self.guild_id()?.to_guild_cached(self.discord)
+    }
+}
+
 /// Optional settings for a [`PrefixCommand`].
 pub struct PrefixCommandOptions<U, E> {
     /// Multiline description with detailed usage instructions. Displayed in the command specific
     /// help: `~help command_name`
-    // TODO: fix the inconsistency that this is String and everywhere else it's &'static str
-    pub multiline_help: Option<fn() -> String>,
+    ///
+    /// Receives the context of the invocation which shows the help, so the text can be dynamic,
+    /// for example mention the [prefix](crate::Context::prefix) that was used or be translated
+    /// into the [locale](crate::Context::locale) of the user. Note that the help may be shown by
+    /// a slash command as well, in which case the context is an application context.
+    pub multiline_help: Option<fn(crate::Context<'_, U, E>) -> String>,
     /// Alternative triggers for the command
     pub aliases: &'static [&'static str],
+    /// Example invocations without prefix, like `"ban @user spamming"`. Displayed in the command
+    /// specific help, prefixed with the current prefix
+    pub examples: &'static [&'static str],
+    /// Decides between commands on the same level which are invoked by the same name, or by the
+    /// same alias: the one with the higher priority is tried first. See
+    /// [`PrefixFrameworkOptions::commands`] for the full resolution order.
+    ///
+    /// Such commands must have different priorities, or framework construction panics.
+    ///
+    /// Defaults to 0
+    pub priority: i32,
     /// Falls back to the framework-specified value on None. See there for documentation.
-    pub on_error: Option<fn(E, PrefixCommandErrorContext<'_, U, E>) -> BoxFuture<'_, ()>>,
+    ///
+    /// May capture state; see [`Self::set_on_error`].
+    pub on_error: Option<
+        std::sync::Arc<
+            dyn for<'a> Fn(E, PrefixCommandErrorContext<'a, U, E>) -> BoxFuture<'a, ()>
+                + Send
+                + Sync,
+        >,
+    >,
     /// If this function returns false, this command will not be executed.
     pub check: Option<fn(PrefixContext<'_, U, E>) -> BoxFuture<'_, Result<bool, E>>>,
+    /// Like [`Self::check`], but can explain why the command was rejected. The reason is passed to
+    /// [`crate::FrameworkOptions::on_check_failure`] unless the rejection is silent.
+    pub check_with_reason:
+        Option<fn(PrefixContext<'_, U, E>) -> BoxFuture<'_, Result<crate::CheckResult, E>>>,
     /// Whether to enable edit tracking for commands by default.
     ///
     /// Note: only has an effect if `Framework::edit_tracker` is set.
     pub track_edits: bool,
-    /// Whether to broadcast a typing indicator while executing this commmand.
-    pub broadcast_typing: bool,
+    /// Characters which separate arguments, in addition to whitespace. Delimiters inside quotes
+    /// are not treated as separators, and consecutive delimiters collapse.
+    ///
+    /// `#[rest]` parameters get the remaining text verbatim, including delimiters.
+    ///
+    /// Defaults to `&[' ', '\t', '\n']`, i.e. only whitespace
+    pub argument_delimiters: &'static [char],
+    /// What to do if this command has required parameters, but is invoked without any arguments
+    pub on_missing_required_args: MissingArgBehavior,
+    /// If true, this command can't be invoked by itself; one of its subcommands must be given.
+    /// Otherwise, [`PrefixFrameworkOptions::subcommand_required_handler`] is called.
+    pub subcommand_required: bool,
+    /// Whether to delete the bot's response when the user deletes the message that invoked this
+    /// command.
+    ///
+    /// Note: only has an effect if [`PrefixFrameworkOptions::edit_tracker`] is set, and only for
+    /// invocations that are still in the edit tracker cache.
+    pub delete_response_on_trigger_delete: bool,
+    /// Whether and how to broadcast a typing indicator while executing this commmand. None
+    /// disables the typing indicator.
+    pub broadcast_typing: Option<TypingMode>,
+    /// If true, the arguments of invocations of this command are recorded as `<redacted>` in the
+    /// tracing span around the invocation (requires the `tracing` feature). Useful for commands
+    /// which take secrets like passwords or API keys.
+    ///
+    /// Note that this doesn't stop the invoking message itself from showing up elsewhere, for
+    /// example in argument parse errors.
+    ///
+    /// Defaults to false, i.e. the arguments are recorded as typed
+    pub redact_args: bool,
+    /// Overrides [`PrefixFrameworkOptions::reply_to_invocation`] for this command if Some
+    pub reply_to_invocation: Option<bool>,
+    /// Overrides [`PrefixFrameworkOptions::execute_self_messages`] for this command if Some, for
+    /// example to let a single command process the bot's own messages.
+    ///
+    /// Only messages of the bot itself are affected; other bots' messages are handled as usual.
+    /// For subcommands, the parent commands must accept the bot's own messages as well.
+    pub allow_self_author: Option<bool>,
+    /// Maximum duration of a single invocation of this command. If the command takes longer, it
+    /// is cancelled and [`PrefixFrameworkOptions::on_command_timeout`] is called. Overrides
+    /// [`PrefixFrameworkOptions::command_timeout`] if Some.
+    ///
+    /// The command future is dropped at whichever `.await` point it is suspended, so side effects
+    /// may be left half-done, for example only some of multiple messages sent. Blocking code
+    /// that never yields to the async runtime can't be cancelled.
+    pub timeout: Option<std::time::Duration>,
 }
 
 impl<U, E> Default for PrefixCommandOptions<U, E> {
@@ -61,32 +205,168 @@ impl<U, E> Default for PrefixCommandOptions<U, E> {
         Self {
             multiline_help: None,
             check: None,
+            check_with_reason: None,
             on_error: None,
             aliases: &[],
+            examples: &[],
+            priority: 0,
             track_edits: false,
-            broadcast_typing: false,
+            argument_delimiters: &[' ', '\t', '\n'],
+            on_missing_required_args: MissingArgBehavior::Error,
+            subcommand_required: false,
+            delete_response_on_trigger_delete: false,
+            broadcast_typing: None,
+            redact_args: false,
+            reply_to_invocation: None,
+            allow_self_author: None,
+            timeout: None,
         }
     }
 }
 
+impl<U, E> PrefixCommandOptions<U, E> {
+    /// Sets [`Self::on_error`] to the given closure, which may capture state.
+    pub fn set_on_error<F>(&mut self, on_error: F)
+    where
+        F: for<'a> Fn(E, PrefixCommandErrorContext<'a, U, E>) -> BoxFuture<'a, ()>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.on_error = Some(std::sync::Arc::new(on_error));
+    }
+}
+
+/// What to do when a prefix command with required parameters is invoked without any arguments, for
+/// example `~ban` without a target
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MissingArgBehavior {
+    /// Fail with an [`crate::ArgumentParseError`], like with any other invalid input
+    Error,
+    /// Reply with the command's [multiline help](PrefixCommandOptions::multiline_help), or its
+    /// usage string if it has none, instead of invoking the command
+    ShowHelp,
+    /// Invoke the command with `Default::default()` for every parameter.
+    ///
+    /// Since this requires all parameter types to implement `Default`, it only takes effect when
+    /// set via the `on_missing_required_args` attribute of [`crate::command`]. Otherwise, it
+    /// behaves like [`Self::Error`].
+    InvokeWithDefaults,
+}
+
+impl Default for MissingArgBehavior {
+    fn default() -> Self {
+        Self::Error
+    }
+}
+
+/// How a prefix command broadcasts a typing indicator, see
+/// [`PrefixCommandOptions::broadcast_typing`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TypingMode {
+    /// Trigger the typing indicator once when the command starts. Discord shows it for about ten
+    /// seconds, or until the bot sends a message
+    Once,
+    /// Re-trigger the typing indicator every few seconds until the command finishes, so it stays
+    /// visible for long-running commands
+    Persistent,
+}
+
+impl Default for TypingMode {
+    fn default() -> Self {
+        Self::Once
+    }
+}
+
 /// Definition of a single command, excluding metadata which doesn't affect the command itself such
 /// as category.
 pub struct PrefixCommand<U, E> {
     /// Main name of the command. Aliases can be set in [`PrefixCommandOptions::aliases`].
     pub name: &'static str,
     /// Callback to execute when this command is invoked.
-    pub action: for<'a> fn(PrefixContext<'a, U, E>, args: &'a str) -> BoxFuture<'a, Result<(), E>>,
+    ///
+    /// Errors are returned along with their location, which is either
+    /// [`crate::CommandErrorLocation::ArgumentParse`] or [`crate::CommandErrorLocation::Body`].
+    pub action: for<'a> fn(
+        PrefixContext<'a, U, E>,
+        args: &'a str,
+    ) -> BoxFuture<'a, Result<(), (E, crate::CommandErrorLocation)>>,
     /// The command ID, shared across all command types that belong to the same implementation
     pub id: std::sync::Arc<crate::CommandId>,
+    /// Metadata about the command's parameters, in order
+    pub parameters: &'static [ParameterMeta],
     /// Optional data to change this command's behavior.
     pub options: PrefixCommandOptions<U, E>,
 }
 
+impl<U, E> PrefixCommand<U, E> {
+    /// Generates a usage string from [`Self::parameters`], for example `~ban <user> [reason]`.
+    ///
+    /// Required parameters are wrapped in `<>` and optional ones in `[]`. Flags are displayed as
+    /// their literal name, since that is what the user needs to type.
+    pub fn usage_string(&self, prefix: &str) -> String {
+        let mut usage = format!("{}{}", prefix, self.name);
+        for parameter in self.parameters {
+            let (open, close) = if parameter.required {
+                ('<', '>')
+            } else {
+                ('[', ']')
+            };
+            usage += &format!(" {}{}{}", open, parameter.name, close);
+        }
+        usage
+    }
+}
+
+/// Metadata about a single parameter of a prefix command
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ParameterMeta {
+    /// Name of the parameter
+    pub name: &'static str,
+    /// Whether the parameter must be given, i.e. is not an `Option`, `Vec` or flag
+    pub required: bool,
+    /// Description of the parameter, if set via `#[description = "..."]`
+    pub description: Option<&'static str>,
+    /// Whether the parameter is a `#[flag]`
+    pub is_flag: bool,
+    /// Whether the parameter takes any number of arguments, i.e. is a `Vec` or `#[rest]`
+    pub greedy: bool,
+    /// Inclusive lower bound of a numeric parameter, if set via `#[min = ...]`. Arguments below it
+    /// are rejected with [`crate::OutOfRange`]
+    pub min: Option<i64>,
+    /// Inclusive upper bound of a numeric parameter, if set via `#[max = ...]`. Arguments above it
+    /// are rejected with [`crate::OutOfRange`]
+    pub max: Option<i64>,
+}
+
 /// Includes a command, plus metadata like associated sub-commands or category.
 pub struct PrefixCommandMeta<U, E> {
     /// Core command data
     pub command: PrefixCommand<U, E>,
     /// Possible subcommands
+    ///
+    /// When this command is invoked, the first word of the arguments is matched against the names
+    /// and aliases of the subcommands. If a subcommand matches, it is resolved recursively with the
+    /// rest of the arguments. If none matches, this command is run with the full arguments,
+    /// unless [`PrefixCommandOptions::subcommand_required`] is set.
+    ///
+    /// For example, `~config set x` runs the `set` subcommand with the arguments `x`, while
+    /// `~config` or `~config foo` run `config` itself.
+    ///
+    /// Subcommands inherit the checks of their parent commands: when dispatching to a subcommand,
+    /// all checks of each parent (see [`crate::CheckKind`]) are evaluated first, starting at the
+    /// top-level command, and then the subcommand's own checks. So a check declared once on the
+    /// parent gates the whole command group. If any of them rejects the invocation, neither the
+    /// subcommand nor the parent runs.
+    ///
+    /// Checks which don't belong to a specific command, i.e.
+    /// [`PrefixFrameworkOptions::command_enabled`] and [`crate::FrameworkOptions::command_check`],
+    /// only run once per invocation, for the top-level command.
+    ///
+    /// Cooldowns are stored per [`crate::CommandId`]. If a parent and its subcommands share the
+    /// same `CommandId` instance, they share their cooldowns. Otherwise, their cooldowns are
+    /// independent: the parent's cooldown is checked for subcommand invocations, but only started
+    /// by invocations of the parent itself.
     pub subcommands: Vec<PrefixCommandMeta<U, E>>,
 }
 
@@ -114,20 +394,67 @@ impl<U, E> Clone for PrefixCommandErrorContext<'_, U, E> {
 pub enum Prefix {
     /// A case-sensitive string literal prefix (passed to [`str::strip_prefix`])
     Literal(&'static str),
-    /// Regular expression which matches the prefix
+    /// Like [`Self::Literal`], but owned, for prefixes which are computed at runtime. See
+    /// [`Self::owned`]
+    OwnedLiteral(String),
+    /// A string literal prefix whose ASCII characters are compared case-insensitively,
+    /// regardless of [`PrefixFrameworkOptions::case_insensitive_prefixes`]. For example,
+    /// `CaseInsensitiveLiteral("luna ")` matches `Luna ping` and `LUNA ping`
+    CaseInsensitiveLiteral(&'static str),
+    /// Regular expression which matches the prefix. It only matches if the match starts at the
+    /// beginning of the message; [`PrefixContext::prefix`] is then set to the matched text
     Regex(regex::Regex),
 }
 
+impl Prefix {
+    /// Creates a literal prefix from a string that isn't `'static`, for example one loaded from a
+    /// config file
+    pub fn owned(prefix: impl Into<String>) -> Self {
+        Self::OwnedLiteral(prefix.into())
+    }
+
+    /// Returns the literal prefix string, or None if this is a regex prefix
+    pub fn as_literal(&self) -> Option<&str> {
+        match self {
+            Self::Literal(prefix) => Some(prefix),
+            Self::OwnedLiteral(prefix) => Some(prefix),
+            Self::CaseInsensitiveLiteral(prefix) => Some(prefix),
+            Self::Regex(_) => None,
+        }
+    }
+}
+
 /// Prefix-specific framework configuration
 pub struct PrefixFrameworkOptions<U, E> {
     /// The main bot prefix. Can be set to None if the bot supports only
     /// [dynamic prefixes](Self::dynamic_prefix).
+    ///
+    /// Treated as the first entry of [`Self::literal_prefixes`].
     pub prefix: Option<String>,
     /// List of bot commands.
+    ///
+    /// Unless [`Self::custom_dispatch`] resolves the command, the first word after the prefix is
+    /// resolved against these commands as follows:
+    /// 1. Commands whose name matches the word are tried before commands of which an alias
+    ///    matches. Among those, commands with a higher [`PrefixCommandOptions::priority`] are tried
+    ///    first, and then commands registered earlier.
+    /// 2. The first of these commands which is enabled and passes its checks is selected. If a
+    ///    command is rejected, the next one is tried.
+    /// 3. The next word is resolved against the subcommands of the selected command in the same
+    ///    way. If no subcommand matches, the selected command runs with the rest of the message as
+    ///    arguments. If a subcommand matches but rejects the invocation, nothing runs.
+    /// 4. If none of these commands matched, the same happens with the commands added via
+    ///    [`crate::Framework::add_prefix_command`].
     pub commands: Vec<PrefixCommandMeta<U, E>>,
+    /// List of additional literal bot prefixes, for example `vec!["!".into(), "?".into()]`
+    ///
+    /// Literal prefixes (this list, [`Self::prefix`] and [`Prefix::Literal`],
+    /// [`Prefix::OwnedLiteral`] and [`Prefix::CaseInsensitiveLiteral`] entries in
+    /// [`Self::additional_prefixes`]) are checked before regex prefixes. If multiple literal
+    /// prefixes match, the longest one is stripped. For example, with the prefixes `!` and `!help`,
+    /// the message `!help foo` is stripped to `foo`. Equally long prefixes are checked in order.
+    pub literal_prefixes: Vec<String>,
     /// List of additional bot prefixes
-    // TODO: maybe it would be nicer to have separate fields for literal and regex prefixes
-    // That way, you don't need to wrap every single literal prefix in a long path which looks ugly
     pub additional_prefixes: Vec<Prefix>,
     /// Callback invoked on evevry message to return a prefix.
     ///
@@ -135,18 +462,37 @@ pub struct PrefixFrameworkOptions<U, E> {
     /// and the returned prefix will be used for checking, but if None is returned, the static
     /// prefix and additional prefixes will be checked instead.
     ///
+    /// If [`Self::stripped_dynamic_prefix`] is set as well, it takes precedence: this callback is
+    /// only invoked if [`Self::stripped_dynamic_prefix`] returned None.
+    ///
     /// Override this field for a simple dynamic prefixe which changes depending on the guild or user.
+    ///
+    /// The prefix is returned as a [`std::borrow::Cow`], so that prefixes which are already stored
+    /// somewhere can be returned without allocation, for example `Cow::Borrowed("!")`.
+    ///
+    /// To avoid invoking this callback on every message, see [`Self::dynamic_prefix_cache`].
     pub dynamic_prefix: Option<
         for<'a> fn(
             &'a serenity::Context,
             &'a serenity::Message,
             &'a U,
-        ) -> BoxFuture<'a, Option<String>>,
+        ) -> BoxFuture<'a, Option<std::borrow::Cow<'a, str>>>,
     >,
+    /// If Some, return values of [`Self::dynamic_prefix`] are cached per guild, and the callback
+    /// is only invoked on cache miss or expiry.
+    ///
+    /// Use [`crate::Framework::invalidate_dynamic_prefix`] to clear a guild's cached prefix, for
+    /// example when it was changed by a command.
+    pub dynamic_prefix_cache: Option<super::DynamicPrefixCache>,
     /// Callback invoked on every message to strip the prefix off an incoming message.
     ///
     /// Override this field for dynamic prefixes which change depending on guild or user.
     ///
+    /// This callback is checked first, before [`Self::dynamic_prefix`] and the static prefixes.
+    /// If it returns Some, the other prefixes are not considered. If it returns None, prefix
+    /// resolution continues with [`Self::dynamic_prefix`], then the static prefixes, and finally
+    /// [`Self::mention_as_prefix`].
+    ///
     /// Return value is a tuple of the prefix and the rest of the message:
     /// ```rust,ignore
     /// if msg.content.starts_with(my_cool_prefix) {
@@ -160,11 +506,93 @@ pub struct PrefixFrameworkOptions<U, E> {
             &'a U,
         ) -> BoxFuture<'a, Option<(&'a str, &'a str)>>,
     >,
-    /// Treat a bot mention (a ping) like a prefix
+    /// Escape hatch to resolve the invoked command yourself, for command syntaxes which normal
+    /// name matching can't express. Called for every message with a prefix, after the prefix was
+    /// stripped. [`PrefixContext::prefix`] is the stripped prefix and [`PrefixContext::command`]
+    /// is None.
+    ///
+    /// Return the command to run along with its arguments, or None to fall back to normal name
+    /// matching. The arguments should be the end of the message content, like in
+    /// `Some((command, &ctx.msg.content[start..]))`, so that argument positions stay correct.
+    ///
+    /// This bypasses name matching entirely, including aliases, subcommand resolution and
+    /// [`PrefixCommandOptions::allow_self_author`]. The rest of the invocation pipeline still
+    /// applies to the returned command: [`Self::command_enabled`], checks, cooldowns, concurrency
+    /// limits, edit tracking and error handling.
+    pub custom_dispatch: Option<
+        for<'a> fn(
+            PrefixContext<'a, U, E>,
+        ) -> BoxFuture<'a, Option<(&'a PrefixCommandMeta<U, E>, &'a str)>>,
+    >,
+    /// Treat a bot mention (a ping) like a prefix. Checked after all other prefixes, even if
+    /// [`Self::dynamic_prefix`] returned Some. See also [`Self::mention_as_prefix_for`]
     pub mention_as_prefix: bool,
+    /// Decides per message whether a bot mention is treated like a prefix, for example to honor a
+    /// per-guild setting. Overrides [`Self::mention_as_prefix`] if set.
+    ///
+    /// Only called for messages which start with a bot mention and don't match any other prefix.
+    pub mention_as_prefix_for: Option<
+        for<'a> fn(&'a serenity::Context, &'a serenity::Message, &'a U) -> BoxFuture<'a, bool>,
+    >,
+    /// If true, a message consisting of nothing but a bot mention invokes [`Self::help_command`]
+    /// without arguments. This works even if mentions aren't otherwise treated as prefix (see
+    /// [`Self::mention_as_prefix`]). Has no effect if [`Self::help_command`] is None.
+    ///
+    /// A mention followed by anything else, like `@bot ping`, is dispatched as usual.
+    pub help_when_mentioned: bool,
+    /// The bot's general help command. Currently used for [`Self::help_when_mentioned`].
+    ///
+    /// The command is invoked directly, regardless of its name, but its checks still apply. It
+    /// doesn't need to be in [`Self::commands`] as well, but usually is, so that it can be invoked
+    /// by name too.
+    pub help_command: Option<PrefixCommandMeta<U, E>>,
+    /// If true, messages in DMs which don't start with any prefix are treated as if they did, i.e.
+    /// the whole message is matched against the commands. Prefixes, including bot mentions if
+    /// [`Self::mention_as_prefix`] is set, are still stripped if present.
+    ///
+    /// DMs that don't match any command are silently ignored; in particular,
+    /// [`Self::on_unknown_command`] is not called for them.
+    pub ignore_prefix_in_dms: bool,
+    /// Called when a message mentions the bot anywhere, but doesn't invoke any command, for
+    /// example "hey @bot help me". [`PrefixContext::command`] is None and
+    /// [`PrefixContext::prefix`] is empty.
+    ///
+    /// Only direct mentions of the bot user in the message content count; @everyone and role
+    /// mentions are ignored, and so are replies to the bot's messages which ping the bot.
+    pub invoke_on_mention: Option<fn(PrefixContext<'_, U, E>) -> BoxFuture<'_, Result<(), E>>>,
+    /// Called for every new message which doesn't invoke a command, for example to award XP or to
+    /// respond to keywords. [`PrefixContext::command`] is None. [`PrefixContext::prefix`] is the
+    /// stripped prefix, or empty if the message doesn't start with a prefix.
+    ///
+    /// Not called if the message names a command which rejected the invocation, for example due
+    /// to failed checks, and not called for message edits. Messages from the bot itself are only
+    /// passed if [`Self::execute_self_messages`] is set.
+    pub non_command_message: Option<fn(PrefixContext<'_, U, E>) -> BoxFuture<'_, Result<(), E>>>,
+    /// Called when a message starts with a prefix, but the command name doesn't match any command
+    /// or alias. Receives the typed command name and up to three similarly named commands or
+    /// aliases, closest first, for "did you mean" suggestions.
+    ///
+    /// See also [`Self::unknown_command_max_distance`].
+    pub on_unknown_command: Option<
+        for<'a> fn(PrefixContext<'a, U, E>, &'a str, Vec<&'static str>) -> BoxFuture<'a, ()>,
+    >,
+    /// The maximum Levenshtein distance between the typed command name and a command name or
+    /// alias for it to be suggested to [`Self::on_unknown_command`]. Defaults to 2
+    pub unknown_command_max_distance: usize,
     /// If Some, the framework will react to message edits by editing the corresponding bot response
     /// with the new result.
     pub edit_tracker: Option<std::sync::RwLock<super::EditTracker>>,
+    /// Called after edit tracking edited a response, because the user edited their invocation.
+    /// Receives the context of the re-run invocation, whose [`PrefixContext::msg`] is the edited
+    /// invocation, the response before the edit and the response after the edit. Useful for
+    /// logging and debugging.
+    pub on_response_edited: Option<
+        for<'a> fn(
+            PrefixContext<'a, U, E>,
+            &'a serenity::Message,
+            &'a serenity::Message,
+        ) -> BoxFuture<'a, ()>,
+    >,
     /// If the user makes a typo in their message and a subsequent edit creates a valid invocation,
     /// the bot will execute the command if this attribute is set. [`Self::edit_tracker`] does not
     /// need to be set for this.
@@ -176,40 +604,244 @@ pub struct PrefixFrameworkOptions<U, E> {
     pub execute_untracked_edits: bool,
     /// Wether or not to ignore message edits on messages outside the cache.
     /// This can happen if the message edit happens while the command is being invoked, or the
-    /// original message wasn't a command.
+    /// original message wasn't a command. See also [`Self::on_edit_tracker_cache_miss`]
     pub ignore_edit_tracker_cache: bool,
+    /// Called when a message that is not in the edit tracker cache is edited. Overrides
+    /// [`Self::ignore_edit_tracker_cache`] if set.
+    ///
+    /// Return the message to re-dispatch as a command, or None to ignore the edit. For example,
+    /// the callback can log the edit, or fetch the full message via HTTP and return it. The message
+    /// update event on its own lacks most fields, like the author, unless they were changed.
+    ///
+    /// Keep in mind that this is called for edits of _any_ uncached message the bot can see, which
+    /// includes all messages that weren't commands. Fetching each of them via HTTP costs one
+    /// request per edit and quickly runs into rate limits on busy servers, so consider filtering
+    /// cheaply first, for example by checking whether [`serenity::MessageUpdateEvent::content`]
+    /// starts with a prefix.
+    pub on_edit_tracker_cache_miss: Option<
+        for<'a> fn(
+            &'a serenity::Context,
+            &'a serenity::MessageUpdateEvent,
+            &'a U,
+        ) -> BoxFuture<'a, Option<serenity::Message>>,
+    >,
 
-    /// Whether commands in messages emitted by the bot itself should be executed as well.
+    /// Default maximum duration of a single command invocation, for commands without
+    /// [`PrefixCommandOptions::timeout`]. None, the default, means no timeout.
+    pub command_timeout: Option<std::time::Duration>,
+    /// Called when a command was cancelled because it exceeded its timeout (see
+    /// [`Self::command_timeout`]). The timeout is given as an argument.
+    pub on_command_timeout:
+        Option<fn(PrefixContext<'_, U, E>, std::time::Duration) -> BoxFuture<'_, Result<(), E>>>,
+    /// Whether commands in messages emitted by the bot itself should be executed as well. Can be
+    /// overridden per command with [`PrefixCommandOptions::allow_self_author`].
     pub execute_self_messages: bool,
-    /// Whether command names should be compared case-insensitively.
+    /// Whether responses are sent as Discord replies to the invoking message, which makes it clear
+    /// which message the bot responded to in busy channels. Can be overridden per command with
+    /// [`PrefixCommandOptions::reply_to_invocation`].
+    ///
+    /// Edit tracked responses keep referencing the invoking message when they are edited.
+    ///
+    /// Defaults to false
+    pub reply_to_invocation: bool,
+    /// Whether replies (see [`Self::reply_to_invocation`]) ping the author of the invoking
+    /// message. Takes precedence over the `replied_user` setting of the
+    /// [allowed mentions](crate::FrameworkOptions::allowed_mentions).
+    ///
+    /// Defaults to true
+    pub mention_replied_user: bool,
+    /// Pairs of opening and closing quote characters which group command arguments with
+    /// whitespace, for example `('“', '”')` for the smart quotes that mobile keyboards insert.
+    ///
+    /// Quotes without a matching closing quote are treated as normal characters. `#[rest]`
+    /// parameters get the remaining text verbatim, including quotes.
+    ///
+    /// Single quotes (`('\'', '\'')`) are not included by default, because apostrophes like in
+    /// "don't" would be mistaken for quotes.
+    ///
+    /// Defaults to `&[('"', '"'), ('“', '”')]`
+    pub quote_pairs: &'static [(char, char)],
+    /// Called when a command with [`PrefixCommandOptions::subcommand_required`] is invoked
+    /// without a subcommand. The command's meta struct, which contains the list of subcommands, is
+    /// given as an argument.
+    pub subcommand_required_handler: Option<
+        for<'a> fn(
+            PrefixContext<'a, U, E>,
+            &'a PrefixCommandMeta<U, E>,
+        ) -> BoxFuture<'a, Result<(), E>>,
+    >,
+    /// Decides whether a command is enabled for an invocation, for example backed by a per-guild
+    /// configuration table so that guild admins can disable individual commands. Unlike
+    /// [`PrefixCommandOptions::check`], this applies to all commands uniformly.
+    ///
+    /// Evaluated once per invocation, before the checks of the top-level command. For an invoked
+    /// subcommand, it receives the top-level command, so disabling a command disables its
+    /// subcommands as well. Disabled commands are skipped and [`Self::command_disabled_handler`]
+    /// is called. [`crate::builtins::help`] omits disabled commands as well.
+    pub command_enabled: Option<
+        for<'a> fn(crate::Context<'a, U, E>, &'a PrefixCommand<U, E>) -> BoxFuture<'a, bool>,
+    >,
+    /// Called when an invoked command is disabled according to [`Self::command_enabled`], for
+    /// example to send a notice. [`PrefixContext::command`] is the disabled command.
+    pub command_disabled_handler:
+        Option<fn(PrefixContext<'_, U, E>) -> BoxFuture<'_, Result<(), E>>>,
+    /// Whether command names and aliases should be compared case-insensitively.
     pub case_insensitive_commands: bool,
+    /// Applied to the typed command name as well as to the names and aliases of all commands
+    /// before comparing them, for example to strip accents or normalize unicode. The comparison
+    /// respects [`Self::case_insensitive_commands`] afterwards.
+    ///
+    /// Also applies to the suggestions passed to [`Self::on_unknown_command`].
+    pub command_name_normalizer: Option<fn(&str) -> std::borrow::Cow<'_, str>>,
+    /// Whether literal prefixes and dynamic prefixes should be compared case-insensitively. Only
+    /// ASCII characters are compared case-insensitively, and the rest of the message keeps its
+    /// original casing.
+    ///
+    /// Bot mentions (see [`Self::mention_as_prefix`]) are unaffected because they don't contain
+    /// letters. [`Prefix::Regex`] prefixes are unaffected too; use the `(?i)` flag in the regex
+    /// for case-insensitive matching.
+    ///
+    /// To make only individual prefixes case-insensitive, use [`Prefix::CaseInsensitiveLiteral`]
+    /// instead.
+    pub case_insensitive_prefixes: bool,
+    /// Whether whitespace is required between the prefix and the command name. If Some(true),
+    /// `! ping` invokes `ping` but `!ping` doesn't; if Some(false), it's the other way around. If
+    /// None, the default, both are accepted.
+    ///
+    /// The check happens after the longest matching prefix is stripped. So with the prefixes `!`
+    /// and `!!` and Some(false), `!!ping` is stripped to `ping` and invokes it, but with only the
+    /// prefix `!`, it is stripped to `!ping`, which doesn't name a command. Whitespace at the end
+    /// of a prefix, like in `hey bot `, counts as separator. Bot mentions (see
+    /// [`Self::mention_as_prefix`]) are exempt from this check.
+    ///
+    /// Messages that fail the check are treated like messages without a prefix.
+    pub require_space_after_prefix: Option<bool>,
+    /// Whether leading whitespace and invisible characters, like zero-width spaces or a byte order
+    /// mark, are ignored when matching the prefix. Some mobile clients and copy-pasting prepend
+    /// these to messages, which otherwise makes the prefix mysteriously not match.
+    ///
+    /// Applies to static, dynamic and mention prefixes, but not to
+    /// [`Self::stripped_dynamic_prefix`], which receives the message as is.
+    pub trim_invisible_prefix: bool,
+    /// Formatting of [`crate::builtins::help_command`]
+    pub help_config: crate::builtins::HelpConfig,
+    /// Reaction added to the invoking message after a [private reply](crate::say_private_reply)
+    /// was sent via DM, to tell the user to check their DMs. Set to None to disable.
+    pub private_reply_reaction: Option<serenity::ReactionType>,
+    /// Whether a [private reply](crate::say_private_reply) is sent to the invocation channel
+    /// instead if the user doesn't accept DMs from the bot. If false, the error is returned.
+    pub private_reply_channel_fallback: bool,
+    /// If true, responses which can't be sent to the invocation channel because Discord responds
+    /// with 403 Forbidden, usually because the bot lacks the Send Messages permission there, are
+    /// sent to the invoking user via DM instead. With the `tracing` feature, a warning is logged
+    /// when this happens.
+    ///
+    /// Applies to responses sent via [`crate::send_reply`] and functions built on it, like
+    /// [`crate::Context::say`], but not to [`crate::send_reply_in`]. DM responses are not edit
+    /// tracked.
+    pub fallback_to_dm_on_send_failure: bool,
+    /// Determines the locale for a prefix command invocation, like `de` or `en-US`, for example
+    /// from a per-guild language setting. Prefix messages don't carry the user's locale, unlike
+    /// application command interactions. See [`crate::Context::locale`].
+    pub locale: Option<for<'a> fn(PrefixContext<'a, U, E>) -> BoxFuture<'a, Option<String>>>,
+    /// Channels in which messages are ignored entirely, for example a serious discussion channel.
+    /// Messages by [owners](crate::FrameworkOptions::owners) are still processed.
+    ///
+    /// The check happens before prefix matching, so neither commands nor callbacks like
+    /// [`Self::non_command_message`] run. Can be changed at runtime via
+    /// [`crate::Framework::block_channel`] and [`crate::Framework::unblock_channel`].
+    pub blocked_channels: std::sync::RwLock<std::collections::HashSet<serenity::ChannelId>>,
+    /// Like [`Self::blocked_channels`], but blocks all channels in the given categories. The
+    /// category of a channel is looked up in the cache, so this requires the guild to be cached.
+    ///
+    /// Can be changed at runtime via [`crate::Framework::block_category`] and
+    /// [`crate::Framework::unblock_category`].
+    pub blocked_categories: std::sync::RwLock<std::collections::HashSet<serenity::ChannelId>>,
+    /// Heading of the commands without a [category](crate::CommandId::category) in
+    /// [`crate::builtins::help`] and [`crate::builtins::help_command`].
+    ///
+    /// Defaults to "Commands"
+    pub help_uncategorized_label: String,
     /* // TODO: implement
-    /// Whether to invoke help command when someone sends a message with just a bot mention
-    pub help_when_mentioned: bool,
-    /// The bot's general help command. Currently used for [`Self::help_when_mentioned`].
-    pub help_commmand: Option<PrefixCommand<U, E>>,
-    // /// The bot's help command for individial commands. Currently used when a command group without
-    // /// any specific subcommand is invoked. This command is expected to take the command name as a
-    // /// single parameter
-    // pub command_specific_help_commmand: Option<PrefixCommand<U, E>>, */
+    /// The bot's help command for individial commands. Currently used when a command group without
+    /// any specific subcommand is invoked. This command is expected to take the command name as a
+    /// single parameter
+    pub command_specific_help_commmand: Option<PrefixCommand<U, E>>, */
 }
 
-impl<U, E> Default for PrefixFrameworkOptions<U, E> {
+impl<U: Send + Sync, E> Default for PrefixFrameworkOptions<U, E> {
     fn default() -> Self {
         Self {
             prefix: None,
             commands: Vec::new(),
+            literal_prefixes: Vec::new(),
             additional_prefixes: Vec::new(),
             dynamic_prefix: None,
+            dynamic_prefix_cache: None,
             stripped_dynamic_prefix: None,
+            custom_dispatch: None,
             mention_as_prefix: true,
+            mention_as_prefix_for: None,
+            help_when_mentioned: false,
+            help_command: None,
+            ignore_prefix_in_dms: false,
+            invoke_on_mention: None,
+            non_command_message: None,
+            on_unknown_command: None,
+            unknown_command_max_distance: 2,
             edit_tracker: None,
+            on_response_edited: None,
             execute_untracked_edits: true,
             ignore_edit_tracker_cache: false,
+            on_edit_tracker_cache_miss: None,
+            command_timeout: None,
+            on_command_timeout: Some(|ctx, timeout| {
+                Box::pin(async move {
+                    let msg = format!(
+                        "The command took longer than {} seconds and was cancelled",
+                        timeout.as_secs()
+                    );
+                    let _: Result<_, _> = crate::say_reply(ctx.into(), msg).await;
+
+                    Ok(())
+                })
+            }),
             execute_self_messages: false,
+            reply_to_invocation: false,
+            mention_replied_user: true,
+            quote_pairs: &[('"', '"'), ('“', '”')],
+            subcommand_required_handler: Some(|ctx, command_meta| {
+                Box::pin(async move {
+                    let subcommands = command_meta
+                        .subcommands
+                        .iter()
+                        .map(|subcommand| format!("`{}`", subcommand.command.name))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let msg = format!(
+                        "You must specify one of the following subcommands: {}",
+                        subcommands
+                    );
+                    let _: Result<_, _> = crate::say_reply(ctx.into(), msg).await;
+
+                    Ok(())
+                })
+            }),
+            command_enabled: None,
+            command_disabled_handler: None,
             case_insensitive_commands: true,
-            // help_when_mentioned: true,
-            // help_commmand: None,
+            command_name_normalizer: None,
+            case_insensitive_prefixes: false,
+            require_space_after_prefix: None,
+            trim_invisible_prefix: false,
+            help_config: Default::default(),
+            private_reply_reaction: Some(serenity::ReactionType::Unicode("✅".into())),
+            private_reply_channel_fallback: true,
+            fallback_to_dm_on_send_failure: false,
+            locale: None,
+            blocked_channels: Default::default(),
+            blocked_categories: Default::default(),
+            help_uncategorized_label: "Commands".to_owned(),
             // command_specific_help_commmand: None,
         }
     }
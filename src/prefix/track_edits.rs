@@ -46,11 +46,29 @@ fn update_message(message: &mut serenity::Message, update: serenity::MessageUpda
     // }
 }
 
+/// A user message and the associated bot response
+struct TrackedMessage {
+    user_msg: serenity::Message,
+    bot_response: serenity::Message,
+    /// The further messages of a response that was split into multiple messages, see
+    /// [`crate::say_chunked_reply`]
+    chunk_responses: Vec<serenity::Message>,
+    /// Whether to delete the bot response when the user message is deleted
+    delete_response_on_trigger_delete: bool,
+}
+
 /// Stores messages and the associated bot responses in order to implement poise's edit tracking
 /// feature.
 pub struct EditTracker {
     max_duration: std::time::Duration,
-    cache: Vec<(serenity::Message, serenity::Message)>,
+    max_entries: Option<usize>,
+    /// Ordered from least recently used to most recently used
+    cache: Vec<TrackedMessage>,
+    min_edit_interval: Option<std::time::Duration>,
+    /// For each recently edited message, the number and time of its latest edit. Used to coalesce
+    /// rapid edits
+    latest_edits: std::collections::HashMap<serenity::MessageId, (u64, std::time::Instant)>,
+    num_edits: u64,
 }
 
 impl EditTracker {
@@ -62,10 +80,92 @@ impl EditTracker {
     pub fn for_timespan(duration: std::time::Duration) -> std::sync::RwLock<Self> {
         std::sync::RwLock::new(Self {
             max_duration: duration,
+            max_entries: None,
+            cache: Vec::new(),
+            min_edit_interval: None,
+            latest_edits: std::collections::HashMap::new(),
+            num_edits: 0,
+        })
+    }
+
+    /// Like [`Self::for_timespan`], but additionally tracks at most `max_entries` messages. When
+    /// the capacity is exceeded, the least recently used entry is evicted.
+    ///
+    /// Once an entry is evicted, edits to its message are treated like edits of any other uncached
+    /// message, see [`crate::PrefixFrameworkOptions::ignore_edit_tracker_cache`] and
+    /// [`crate::PrefixFrameworkOptions::on_edit_tracker_cache_miss`].
+    pub fn for_timespan_with_capacity(
+        duration: std::time::Duration,
+        max_entries: usize,
+    ) -> std::sync::RwLock<Self> {
+        std::sync::RwLock::new(Self {
+            max_duration: duration,
+            max_entries: Some(max_entries),
             cache: Vec::new(),
+            min_edit_interval: None,
+            latest_edits: std::collections::HashMap::new(),
+            num_edits: 0,
         })
     }
 
+    /// Coalesces rapid edits of the same message: after an edit, the command is only re-run if
+    /// no further edit of the message arrives within the given interval. The re-run then uses
+    /// the latest message content.
+    ///
+    /// This delays every edit-triggered re-run by the interval. None, the default, disables
+    /// coalescing.
+    ///
+    /// ```rust
+    /// let edit_tracker = poise::EditTracker::for_timespan(std::time::Duration::from_secs(3600));
+    /// edit_tracker
+    ///     .write()
+    ///     .unwrap()
+    ///     .set_min_edit_interval(Some(std::time::Duration::from_secs(2)));
+    /// ```
+    pub fn set_min_edit_interval(&mut self, min_edit_interval: Option<std::time::Duration>) {
+        self.min_edit_interval = min_edit_interval;
+    }
+
+    /// Records an edit of the given message for coalescing. Returns how long to wait for further
+    /// edits, and the edit number to pass to [`Self::is_latest_edit`] afterwards, or None if edits
+    /// shouldn't be coalesced.
+    pub(crate) fn register_edit(
+        &mut self,
+        user_msg_id: serenity::MessageId,
+    ) -> Option<(std::time::Duration, u64)> {
+        let min_edit_interval = self.min_edit_interval?;
+        self.num_edits += 1;
+        self.latest_edits
+            .insert(user_msg_id, (self.num_edits, std::time::Instant::now()));
+        Some((min_edit_interval, self.num_edits))
+    }
+
+    /// Whether no edit of the given message was registered after the given edit
+    pub(crate) fn is_latest_edit(&self, user_msg_id: serenity::MessageId, edit: u64) -> bool {
+        match self.latest_edits.get(&user_msg_id) {
+            Some(&(latest_edit, _)) => latest_edit == edit,
+            None => true,
+        }
+    }
+
+    /// Whether the given user message is in the cache
+    pub(crate) fn is_tracked(&self, user_msg_id: serenity::MessageId) -> bool {
+        self.cache
+            .iter()
+            .any(|entry| entry.user_msg.id == user_msg_id)
+    }
+
+    /// Finds the entry of the given user message and marks it as most recently used
+    fn touch(&mut self, user_msg_id: serenity::MessageId) -> Option<&mut TrackedMessage> {
+        let index = self
+            .cache
+            .iter()
+            .position(|entry| entry.user_msg.id == user_msg_id)?;
+        let entry = self.cache.remove(index);
+        self.cache.push(entry);
+        self.cache.last_mut()
+    }
+
     /// Returns a copy of a newly up-to-date cached message, or a brand new generated message when
     /// not in cache. Also returns a bool with `true` if this message was previously tracked
     ///
@@ -75,12 +175,8 @@ impl EditTracker {
         user_msg_update: &serenity::MessageUpdateEvent,
         ignore_edit_tracker_cache: bool,
     ) -> Option<(serenity::Message, bool)> {
-        match self
-            .cache
-            .iter_mut()
-            .find(|(user_msg, _)| user_msg.id == user_msg_update.id)
-        {
-            Some((user_msg, _)) => {
+        match self.touch(user_msg_update.id) {
+            Some(TrackedMessage { user_msg, .. }) => {
                 // If message content wasn't touched, don't re-run command
                 // Note: this may be Some, but still identical to previous content. We want to
                 // re-run the command in that case too; because that means the user explicitly
@@ -105,17 +201,43 @@ impl EditTracker {
         }
     }
 
+    /// Whether the given message update stems from the bot editing its own message, for example a
+    /// tracked command response. Such updates must never re-run commands, or the bot could end up
+    /// in an edit loop.
+    ///
+    /// The author is often missing from message updates, so messages which are known to be from
+    /// the bot via the cache are detected as well.
+    pub(crate) fn is_own_edit(
+        &self,
+        msg_id: serenity::MessageId,
+        author_id: Option<serenity::UserId>,
+        bot_id: serenity::UserId,
+    ) -> bool {
+        author_id == Some(bot_id)
+            || self.cache.iter().any(|entry| {
+                entry.bot_response.id == msg_id
+                    || (entry.user_msg.id == msg_id && entry.user_msg.author.id == bot_id)
+            })
+    }
+
     /// Forget all of the messages that are older than the specified duration.
     pub fn purge(&mut self) {
         let max_duration = self.max_duration;
-        self.cache.retain(|(user_msg, _)| {
-            let last_update = user_msg.edited_timestamp.unwrap_or(user_msg.timestamp);
+        self.cache.retain(|entry| {
+            let last_update = entry
+                .user_msg
+                .edited_timestamp
+                .unwrap_or(entry.user_msg.timestamp);
             if let Ok(age) = (chrono::Utc::now() - last_update).to_std() {
                 age < max_duration
             } else {
                 false
             }
         });
+
+        let min_edit_interval = self.min_edit_interval.unwrap_or_default();
+        self.latest_edits
+            .retain(|_, (_, edit_time)| edit_time.elapsed() <= min_edit_interval);
     }
 
     /// Given a message by a user, find the corresponding bot response, if one exists and is cached.
@@ -123,17 +245,121 @@ impl EditTracker {
         &mut self,
         user_msg_id: serenity::MessageId,
     ) -> Option<&mut serenity::Message> {
-        let (_, bot_response) = self
+        let entry = self.touch(user_msg_id)?;
+        Some(&mut entry.bot_response)
+    }
+
+    /// Given a message by a user, find the further messages of the corresponding chunked bot
+    /// response, if a bot response is cached
+    pub(crate) fn find_chunk_responses(
+        &mut self,
+        user_msg_id: serenity::MessageId,
+    ) -> Option<&mut Vec<serenity::Message>> {
+        let entry = self.touch(user_msg_id)?;
+        Some(&mut entry.chunk_responses)
+    }
+
+    /// Forgets the given deleted user message. Returns the associated bot response if it should be
+    /// deleted along with it, i.e. if the invoked command had
+    /// [`crate::PrefixCommandOptions::delete_response_on_trigger_delete`] set.
+    ///
+    /// Messages that aren't in the cache are ignored, since there is no way to find their bot
+    /// response.
+    ///
+    /// Of a response split into multiple messages by [`crate::say_chunked_reply`], only the first
+    /// message is returned. The framework deletes the others as well.
+    pub fn process_message_delete(
+        &mut self,
+        deleted_message_id: serenity::MessageId,
+    ) -> Option<serenity::Message> {
+        self.process_message_delete_with_chunks(deleted_message_id)
+            .into_iter()
+            .next()
+    }
+
+    /// Like [`Self::process_message_delete`], but returns all messages of the bot response
+    pub(crate) fn process_message_delete_with_chunks(
+        &mut self,
+        deleted_message_id: serenity::MessageId,
+    ) -> Vec<serenity::Message> {
+        let index = match self
             .cache
-            .iter_mut()
-            .find(|(user_msg, _)| user_msg.id == user_msg_id)?;
-        Some(bot_response)
+            .iter()
+            .position(|entry| entry.user_msg.id == deleted_message_id)
+        {
+            Some(x) => x,
+            None => return Vec::new(),
+        };
+        let entry = self.cache.remove(index);
+        if entry.delete_response_on_trigger_delete {
+            let mut responses = entry.chunk_responses;
+            responses.insert(0, entry.bot_response);
+            responses
+        } else {
+            Vec::new()
+        }
     }
 
     /// Notify the [`EditTracker`] that the given user message should be associated with the given
     /// bot response.
-    fn register_response(&mut self, user_msg: serenity::Message, bot_response: serenity::Message) {
-        self.cache.push((user_msg, bot_response));
+    fn register_response(
+        &mut self,
+        user_msg: serenity::Message,
+        bot_response: serenity::Message,
+        delete_response_on_trigger_delete: bool,
+    ) {
+        self.cache.push(TrackedMessage {
+            user_msg,
+            bot_response,
+            chunk_responses: Vec::new(),
+            delete_response_on_trigger_delete,
+        });
+
+        if let Some(max_entries) = self.max_entries {
+            if self.cache.len() > max_entries {
+                let num_evicted = self.cache.len() - max_entries;
+                self.cache.drain(..num_evicted);
+            }
+        }
+    }
+}
+
+/// Makes a new response message a reply to the invocation, if configured (see
+/// [`crate::PrefixFrameworkOptions::reply_to_invocation`]), and sets its allowed mentions. If
+/// `allowed_mentions` is None, the framework's default is used.
+fn set_reference_and_allowed_mentions<U, E>(
+    ctx: crate::prefix::PrefixContext<'_, U, E>,
+    m: &mut serenity::CreateMessage<'_>,
+    allowed_mentions: Option<serenity::CreateAllowedMentions>,
+) {
+    let prefix_options = &ctx.framework.options().prefix_options;
+    let reply_to_invocation = ctx
+        .command
+        .and_then(|command| command.options.reply_to_invocation)
+        .unwrap_or(prefix_options.reply_to_invocation);
+
+    let mut allowed_mentions =
+        allowed_mentions.or_else(|| ctx.framework.options().allowed_mentions.clone());
+    if reply_to_invocation {
+        m.reference_message(ctx.msg);
+        if let Some(allowed_mentions) = &mut allowed_mentions {
+            allowed_mentions.replied_user(prefix_options.mention_replied_user);
+        } else if !prefix_options.mention_replied_user {
+            // Without allowed mentions, Discord pings everything that is mentioned, including the
+            // replied user, so spell that out minus the replied user
+            let mut f = serenity::CreateAllowedMentions::default();
+            f.parse(serenity::ParseValue::Everyone)
+                .parse(serenity::ParseValue::Users)
+                .parse(serenity::ParseValue::Roles)
+                .replied_user(false);
+            allowed_mentions = Some(f);
+        }
+    }
+    if let Some(allowed_mentions) = allowed_mentions {
+        m.allowed_mentions(|m| {
+            *m = allowed_mentions;
+            m
+        });
     }
 }
 
@@ -150,21 +376,37 @@ pub async fn send_prefix_reply<'a, U, E>(
         attachments,
         components,
         ephemeral: _,
+        allowed_mentions,
     } = reply;
 
-    let lock_edit_tracker = || {
-        if let Some(command) = ctx.command {
-            // If we definitely don't need to track this command invocation, stop
-            let execute_untracked_edits = ctx
-                .framework
-                .options()
-                .prefix_options
-                .execute_untracked_edits;
-            if !(command.options.track_edits || execute_untracked_edits) {
-                return None;
-            }
-        }
+    // If we definitely don't need to track this command invocation for edits or deletions,
+    // don't touch the edit tracker
+    let (track_edits, delete_response_on_trigger_delete) = match ctx.command {
+        Some(command) => (
+            command.options.track_edits
+                || ctx
+                    .framework
+                    .options()
+                    .prefix_options
+                    .execute_untracked_edits,
+            command.options.delete_response_on_trigger_delete,
+        ),
+        None => (true, false),
+    };
+    let outgoing = crate::reply::Outgoing::Message {
+        channel_id: ctx.msg.channel_id,
+        content: content.as_deref(),
+    };
+    // Returned instead if the response is captured
+    let placeholder = || {
+        let channel_id = ctx.msg.channel_id;
+        let message =
+            crate::reply::placeholder_message(ctx.discord, channel_id, content.as_deref());
+        Ok(Box::new(message))
+    };
 
+    let prefix_options = &ctx.framework.options().prefix_options;
+    let lock_edit_tracker = || {
         ctx.framework
             .options()
             .prefix_options
@@ -173,38 +415,49 @@ pub async fn send_prefix_reply<'a, U, E>(
             .map(|t| t.write().unwrap())
     };
 
-    let existing_response = lock_edit_tracker()
-        .as_mut()
-        .and_then(|t| t.find_bot_response(ctx.msg.id))
-        .cloned();
+    let existing_response = if track_edits {
+        lock_edit_tracker()
+            .as_mut()
+            .and_then(|t| t.find_bot_response(ctx.msg.id))
+            .cloned()
+    } else {
+        None
+    };
 
     Ok(Box::new(if let Some(mut response) = existing_response {
-        response
-            .edit(ctx.discord, |f| {
-                // Empty string resets content (happens when user replaces text with embed)
-                f.content(content.as_deref().unwrap_or(""));
-
-                match embed {
-                    Some(embed) => f.set_embed(embed),
-                    None => f.set_embeds(Vec::new()),
-                };
-
-                f.0.insert("attachments", serde_json::json! { [] }); // reset attachments
-                for attachment in attachments {
-                    f.attachment(attachment);
-                }
+        // Only cloned if needed, for the on_response_edited callback
+        let old_response = prefix_options
+            .on_response_edited
+            .map(|callback| (callback, response.clone()));
 
-                // When components is None, this will still be run to reset the message components
-                f.components(|f| {
-                    if let Some(components) = components {
-                        *f = components;
-                    }
-                    f
-                });
+        let request = response.edit(ctx.discord, |f| {
+            // Empty string resets content (happens when user replaces text with embed)
+            f.content(content.as_deref().unwrap_or(""));
 
+            match embed {
+                Some(embed) => f.set_embed(embed),
+                None => f.set_embeds(Vec::new()),
+            };
+
+            f.0.insert("attachments", serde_json::json! { [] }); // reset attachments
+            for attachment in attachments {
+                f.attachment(attachment);
+            }
+
+            // When components is None, this will still be run to reset the message components
+            f.components(|f| {
+                if let Some(components) = components {
+                    *f = components;
+                }
                 f
-            })
-            .await?;
+            });
+
+            f
+        });
+        let edited = crate::reply::send_response(ctx.discord, outgoing, request).await?;
+        if edited.is_none() {
+            return placeholder();
+        }
 
         // If the entry still exists after the await, update it to the new contents
         if let Some(response_entry) = lock_edit_tracker()
@@ -214,41 +467,189 @@ pub async fn send_prefix_reply<'a, U, E>(
             *response_entry = response.clone();
         }
 
+        if let Some((callback, old_response)) = &old_response {
+            callback(ctx, old_response, &response).await;
+        }
+
         response
     } else {
-        let new_response = ctx
-            .msg
-            .channel_id
-            .send_message(ctx.discord, |m| {
-                if let Some(content) = content {
-                    m.content(content);
-                }
-                if let Some(embed) = embed {
-                    m.set_embed(embed);
-                }
-                if let Some(allowed_mentions) = &ctx.framework.options().allowed_mentions {
-                    m.allowed_mentions(|m| {
-                        *m = allowed_mentions.clone();
-                        m
-                    });
-                }
-                if let Some(components) = components {
-                    m.components(|c| {
-                        c.0 = components.0;
-                        c
-                    });
-                }
+        let request = ctx.msg.channel_id.send_message(ctx.discord, |m| {
+            if let Some(content) = &content {
+                m.content(content);
+            }
+            if let Some(embed) = embed {
+                m.set_embed(embed);
+            }
+            set_reference_and_allowed_mentions(ctx, m, allowed_mentions);
+            if let Some(components) = components {
+                m.components(|c| {
+                    c.0 = components.0;
+                    c
+                });
+            }
 
-                for attachment in attachments {
-                    m.add_file(attachment);
-                }
-                m
-            })
-            .await?;
-        if let Some(track_edits) = &mut lock_edit_tracker() {
-            track_edits.register_response(ctx.msg.clone(), new_response.clone());
+            for attachment in attachments {
+                m.add_file(attachment);
+            }
+            m
+        });
+        let sent = crate::reply::send_response(ctx.discord, outgoing, request).await?;
+        let new_response = match sent {
+            Some(new_response) => new_response,
+            None => return placeholder(),
+        };
+        if track_edits || delete_response_on_trigger_delete {
+            if let Some(edit_tracker) = &mut lock_edit_tracker() {
+                edit_tracker.register_response(
+                    ctx.msg.clone(),
+                    new_response.clone(),
+                    delete_response_on_trigger_delete,
+                );
+            }
         }
 
         new_response
     }))
 }
+
+/// Sends the further messages of a chunked response (see [`crate::say_chunked_reply`]), after the
+/// first message was sent via [`send_prefix_reply`]. Like the first message, they are replies to
+/// the invocation if configured.
+///
+/// If the invocation is edit tracked, the messages are tracked along with the first message. When
+/// the command is re-run after an edit, they are edited, and the ones that are no longer needed
+/// are deleted.
+pub(crate) async fn send_prefix_reply_chunks<U, E>(
+    ctx: crate::prefix::PrefixContext<'_, U, E>,
+    chunks: &[String],
+) -> Result<(), serenity::Error> {
+    let lock_edit_tracker = || {
+        ctx.framework
+            .options()
+            .prefix_options
+            .edit_tracker
+            .as_ref()
+            .map(|t| t.write().unwrap())
+    };
+    let previous_responses = lock_edit_tracker()
+        .as_mut()
+        .and_then(|t| t.find_chunk_responses(ctx.msg.id))
+        .map(std::mem::take)
+        .unwrap_or_default();
+
+    let mut previous_responses = previous_responses.into_iter();
+    let mut responses = Vec::new();
+    for chunk in chunks {
+        let outgoing = crate::reply::Outgoing::Message {
+            channel_id: ctx.msg.channel_id,
+            content: Some(chunk),
+        };
+        // None if the response was captured
+        let response = match previous_responses.next() {
+            Some(mut response) => {
+                let request = response.edit(ctx.discord, |f| f.content(chunk));
+                let edited = crate::reply::send_response(ctx.discord, outgoing, request).await?;
+                edited.map(|_| response)
+            }
+            None => {
+                let request = ctx.msg.channel_id.send_message(ctx.discord, |m| {
+                    m.content(chunk);
+                    set_reference_and_allowed_mentions(ctx, m, None);
+                    m
+                });
+                crate::reply::send_response(ctx.discord, outgoing, request).await?
+            }
+        };
+        if let Some(response) = response {
+            responses.push(response);
+        }
+    }
+    for surplus_response in previous_responses {
+        let outgoing = crate::reply::Outgoing::Deletion {
+            message_id: surplus_response.id,
+        };
+        let request = surplus_response.delete(ctx.discord);
+        crate::reply::send_response(ctx.discord, outgoing, request).await?;
+    }
+
+    // Only tracked if the first message is tracked as well
+    if let Some(chunk_responses) = lock_edit_tracker()
+        .as_mut()
+        .and_then(|t| t.find_chunk_responses(ctx.msg.id))
+    {
+        *chunk_responses = responses;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn message(id: u64) -> serenity::Message {
+        let mut msg = serenity::CustomMessage::new().build();
+        msg.id = serenity::MessageId(id);
+        msg
+    }
+
+    #[test]
+    fn test_max_entries() {
+        let tracker =
+            EditTracker::for_timespan_with_capacity(std::time::Duration::from_secs(3600), 3);
+        let mut tracker = tracker.write().unwrap();
+        for id in 1..=4 {
+            tracker.register_response(message(id), message(100 + id), false);
+        }
+        assert!(tracker.find_bot_response(serenity::MessageId(1)).is_none());
+        for id in 2..=4 {
+            let response = tracker.find_bot_response(serenity::MessageId(id)).unwrap();
+            assert_eq!(response.id, serenity::MessageId(100 + id));
+        }
+
+        // Accessing an entry protects it from eviction
+        tracker.find_bot_response(serenity::MessageId(2));
+        tracker.register_response(message(5), message(105), false);
+        assert!(tracker.find_bot_response(serenity::MessageId(3)).is_none());
+        assert!(tracker.find_bot_response(serenity::MessageId(2)).is_some());
+    }
+
+    #[test]
+    fn test_is_own_edit() {
+        let bot_id = serenity::UserId(1000);
+        let tracker = EditTracker::for_timespan(std::time::Duration::from_secs(3600));
+        let mut tracker = tracker.write().unwrap();
+
+        let mut bot_response = message(101);
+        bot_response.author.id = bot_id;
+        tracker.register_response(message(1), bot_response, false);
+        // A command message sent by the bot itself, e.g. with execute_self_messages
+        let mut bot_command = message(2);
+        bot_command.author.id = bot_id;
+        tracker.register_response(bot_command, message(102), false);
+
+        // The bot editing its tracked response, with or without author in the update
+        assert!(tracker.is_own_edit(serenity::MessageId(101), Some(bot_id), bot_id));
+        assert!(tracker.is_own_edit(serenity::MessageId(101), None, bot_id));
+        assert!(tracker.is_own_edit(serenity::MessageId(2), None, bot_id));
+        // Untracked bot message
+        assert!(tracker.is_own_edit(serenity::MessageId(3), Some(bot_id), bot_id));
+        // User edits
+        assert!(!tracker.is_own_edit(serenity::MessageId(1), None, bot_id));
+        assert!(!tracker.is_own_edit(serenity::MessageId(1), Some(serenity::UserId(5)), bot_id));
+    }
+
+    #[test]
+    fn test_edit_coalescing() {
+        let tracker = EditTracker::for_timespan(std::time::Duration::from_secs(3600));
+        let mut tracker = tracker.write().unwrap();
+        assert!(tracker.register_edit(serenity::MessageId(1)).is_none());
+
+        tracker.set_min_edit_interval(Some(std::time::Duration::from_secs(2)));
+        let (_, first_edit) = tracker.register_edit(serenity::MessageId(1)).unwrap();
+        let (_, other_message_edit) = tracker.register_edit(serenity::MessageId(2)).unwrap();
+        let (_, second_edit) = tracker.register_edit(serenity::MessageId(1)).unwrap();
+        assert!(!tracker.is_latest_edit(serenity::MessageId(1), first_edit));
+        assert!(tracker.is_latest_edit(serenity::MessageId(1), second_edit));
+        assert!(tracker.is_latest_edit(serenity::MessageId(2), other_message_edit));
+    }
+}
@@ -1,7 +1,7 @@
 use crate::serenity_prelude as serenity;
 
 /// Message builder that abstracts over prefix and application command responses
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct CreateReply<'a> {
     /// Message content
     pub content: Option<String>,
@@ -13,6 +13,9 @@ pub struct CreateReply<'a> {
     pub ephemeral: bool,
     /// Message components, that is, buttons
     pub components: Option<serenity::CreateComponents>,
+    /// Allowed mentions for this message. If None, falls back to
+    /// [`crate::FrameworkOptions::allowed_mentions`]
+    pub allowed_mentions: Option<serenity::CreateAllowedMentions>,
 }
 
 impl<'a> CreateReply<'a> {
@@ -48,9 +51,24 @@ impl<'a> CreateReply<'a> {
         self
     }
 
+    /// Set the allowed mentions for this message, overriding
+    /// [`crate::FrameworkOptions::allowed_mentions`].
+    ///
+    /// Any previously set allowed mentions will be overwritten.
+    pub fn allowed_mentions(
+        &mut self,
+        f: impl FnOnce(&mut serenity::CreateAllowedMentions) -> &mut serenity::CreateAllowedMentions,
+    ) -> &mut Self {
+        let mut allowed_mentions = serenity::CreateAllowedMentions::default();
+        f(&mut allowed_mentions);
+        self.allowed_mentions = Some(allowed_mentions);
+        self
+    }
+
     /// Add an attachment.
     ///
-    /// This will not have an effect in a slash command's initial response!
+    /// This will not have an effect in a slash command's initial response! To send files in both
+    /// prefix and application commands, use [`send_files_reply`].
     pub fn attachment(&mut self, attachment: serenity::AttachmentType<'a>) -> &mut Self {
         self.attachments.push(attachment);
         self
@@ -70,6 +88,119 @@ impl<'a> CreateReply<'a> {
     }
 }
 
+/// Configures how responses sent via [`send_reply`] are retried when they fail due to Discord
+/// rate limits. Other errors are never retried.
+///
+/// Set via [`crate::FrameworkOptions::response_retry`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RetryConfig {
+    /// How many times a rate limited response is retried before the error is returned
+    pub max_retries: u32,
+    /// Whether to wait for Discord's `retry_after` duration before retrying.
+    ///
+    /// serenity doesn't expose `retry_after` in its error type, but its rate limiter holds back
+    /// requests until Discord's rate limit has reset. So if this is true, the response is resent
+    /// right away and serenity does the waiting.
+    pub respect_retry_after: bool,
+    /// How long to wait before each retry if [`Self::respect_retry_after`] is false.
+    pub retry_delay: std::time::Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            respect_retry_after: true,
+            retry_delay: std::time::Duration::from_secs(1),
+        }
+    }
+}
+
+/// Whether the given error is an HTTP 429 Too Many Requests response
+fn is_ratelimit_error(error: &serenity::Error) -> bool {
+    match error {
+        serenity::Error::Http(error) => match &**error {
+            serenity::HttpError::UnsuccessfulRequest(response) => {
+                response.status_code.as_u16() == 429
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Whether the given error is an HTTP 403 Forbidden response, for example because the bot lacks
+/// permissions to send messages in a channel
+fn is_forbidden_error(error: &serenity::Error) -> bool {
+    match error {
+        serenity::Error::Http(error) => match &**error {
+            serenity::HttpError::UnsuccessfulRequest(response) => {
+                response.status_code.as_u16() == 403
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// A request to Discord in response to a command, as described to [`send_response`]. Only read
+/// to capture responses with the `testing` feature
+#[cfg_attr(not(feature = "testing"), allow(dead_code))]
+pub(crate) enum Outgoing<'a> {
+    /// A message sent to a channel, or an edit of one
+    Message {
+        channel_id: serenity::ChannelId,
+        content: Option<&'a str>,
+    },
+    /// A message sent to a user via DM
+    DirectMessage {
+        user_id: serenity::UserId,
+        content: Option<&'a str>,
+    },
+    /// A reaction added to a message
+    Reaction {
+        message_id: serenity::MessageId,
+        reaction: &'a serenity::ReactionType,
+    },
+    /// A deletion of a message
+    Deletion { message_id: serenity::MessageId },
+}
+
+/// Runs a request to Discord in response to a command. All response functions send their
+/// messages, edits, reactions and deletions through here, so that a `MockContext` of the
+/// `testing` feature can capture them instead.
+///
+/// Returns None if the response was captured, in which case `request` is never run.
+pub(crate) async fn send_response<T>(
+    discord: &serenity::Context,
+    response: Outgoing<'_>,
+    request: impl std::future::Future<Output = Result<T, serenity::Error>>,
+) -> Result<Option<T>, serenity::Error> {
+    #[cfg(feature = "testing")]
+    {
+        if crate::testing::capture(discord, response).await {
+            return Ok(None);
+        }
+    }
+    #[cfg(not(feature = "testing"))]
+    let _ = (discord, response);
+
+    request.await.map(Some)
+}
+
+/// Stands in for the message that would have been sent, if [`send_response`] captured it
+pub(crate) fn placeholder_message(
+    discord: &serenity::Context,
+    channel_id: serenity::ChannelId,
+    content: Option<&str>,
+) -> serenity::Message {
+    let mut msg = serenity::CustomMessage::new().build();
+    msg.channel_id = channel_id;
+    msg.author.id = discord.cache.current_user_id();
+    msg.content = content.unwrap_or_default().to_owned();
+    msg
+}
+
 /// Returned from [`send_reply`] to retrieve the sent message object.
 ///
 /// For prefix commands, you can retrieve the sent message directly. For slash commands, Discord
@@ -121,16 +252,67 @@ impl ReplyHandle<'_> {
 /// ).await?;
 /// # Ok(()) }
 /// ```
+///
+/// If [`crate::FrameworkOptions::response_retry`] is set, responses that fail due to rate limits
+/// are retried.
 pub async fn send_reply<'a, U, E>(
     ctx: crate::Context<'_, U, E>,
     builder: impl for<'b> FnOnce(&'b mut CreateReply<'a>) -> &'b mut CreateReply<'a>,
 ) -> Result<Option<ReplyHandle<'_>>, serenity::Error> {
+    let mut reply = CreateReply::default();
+    builder(&mut reply);
+    retry_ratelimited(ctx, reply, |reply| send_built_reply(ctx, reply)).await
+}
+
+/// Sends the reply with the given function. If [`crate::FrameworkOptions::response_retry`] is set,
+/// sending is retried as long as it fails due to rate limits.
+async fn retry_ratelimited<'a, U, E, T, F>(
+    ctx: crate::Context<'_, U, E>,
+    reply: CreateReply<'a>,
+    send: impl Fn(CreateReply<'a>) -> F,
+) -> Result<T, serenity::Error>
+where
+    F: std::future::Future<Output = Result<T, serenity::Error>>,
+{
+    let retry_config = match &ctx.framework().options().response_retry {
+        Some(x) => x,
+        None => return send(reply).await,
+    };
+    let mut retries = 0;
+    loop {
+        match send(reply.clone()).await {
+            Err(e) if retries < retry_config.max_retries && is_ratelimit_error(&e) => {
+                retries += 1;
+                #[cfg(feature = "tracing")]
+                tracing::warn!(
+                    retry = retries,
+                    max_retries = retry_config.max_retries,
+                    "response was rate limited, retrying"
+                );
+                if !retry_config.respect_retry_after {
+                    tokio::time::sleep(retry_config.retry_delay).await;
+                }
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Sends a single attempt of an already built reply
+async fn send_built_reply<'b, U, E>(
+    ctx: crate::Context<'b, U, E>,
+    reply: CreateReply<'_>,
+) -> Result<Option<ReplyHandle<'b>>, serenity::Error> {
     Ok(match ctx {
         crate::Context::Prefix(ctx) => Some(ReplyHandle::Prefix(
-            crate::send_prefix_reply(ctx, builder).await?,
+            send_prefix_reply_or_dm(ctx, reply).await?,
         )),
         crate::Context::Application(ctx) => {
-            crate::send_application_reply(ctx, builder).await?;
+            crate::send_application_reply(ctx, |f| {
+                *f = reply;
+                f
+            })
+            .await?;
 
             if let crate::ApplicationCommandOrAutocompleteInteraction::ApplicationCommand(
                 interaction,
@@ -147,6 +329,84 @@ pub async fn send_reply<'a, U, E>(
     })
 }
 
+/// Sends the reply in the invocation channel. If that's forbidden and
+/// [`crate::PrefixFrameworkOptions::fallback_to_dm_on_send_failure`] is set, the reply is sent to
+/// the invoking user via DM instead
+async fn send_prefix_reply_or_dm<U, E>(
+    ctx: crate::PrefixContext<'_, U, E>,
+    reply: CreateReply<'_>,
+) -> Result<Box<serenity::Message>, serenity::Error> {
+    // Sending consumes the reply, so it's cloned up front in case the fallback is needed. The clone
+    // is skipped if the fallback can't apply, since the reply may contain large attachments
+    let prefix_options = &ctx.framework.options().prefix_options;
+    let fallback_reply = match prefix_options.fallback_to_dm_on_send_failure {
+        true if ctx.msg.guild_id.is_some() => Some(reply.clone()),
+        _ => None,
+    };
+
+    let result = crate::send_prefix_reply(ctx, |f| {
+        *f = reply;
+        f
+    })
+    .await;
+    match (result, fallback_reply) {
+        (Err(e), Some(reply)) if is_forbidden_error(&e) => {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                error = %e,
+                channel_id = %ctx.msg.channel_id,
+                "can't respond in invocation channel, falling back to DM"
+            );
+            let message = send_direct_message(ctx, reply).await?;
+            Ok(Box::new(message))
+        }
+        (result, _) => result,
+    }
+}
+
+/// Like [`send_reply`], but additionally attaches the given files, for example generated images.
+/// Works the same in prefix and application commands.
+///
+/// Discord doesn't support attachments in the initial response to an application command. So if
+/// no response was sent yet, the interaction is deferred first, and the files are sent in the
+/// followup message which replaces the deferred response. The deferred response is ephemeral if
+/// [`CreateReply::ephemeral`] is set.
+///
+/// In edit tracked prefix commands, re-running the command edits the existing response and
+/// replaces all of its attachments with the new files.
+///
+/// ```rust,no_run
+/// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let ctx: poise::Context<'_, (), ()> = todo!();
+/// # let png_bytes: Vec<u8> = Vec::new();
+/// let chart = poise::serenity_prelude::AttachmentType::Bytes {
+///     data: png_bytes.into(),
+///     filename: "chart.png".into(),
+/// };
+/// ctx.send_files(|f| f.content("Here's your chart"), vec![chart]).await?;
+/// # Ok(()) }
+/// ```
+pub async fn send_files_reply<'a, U, E>(
+    ctx: crate::Context<'_, U, E>,
+    builder: impl for<'b> FnOnce(&'b mut CreateReply<'a>) -> &'b mut CreateReply<'a>,
+    files: Vec<serenity::AttachmentType<'a>>,
+) -> Result<Option<ReplyHandle<'_>>, serenity::Error> {
+    let mut reply = CreateReply::default();
+    builder(&mut reply);
+    reply.attachments.extend(files);
+
+    if let crate::Context::Application(ctx) = ctx {
+        let has_sent_initial_response = ctx
+            .has_sent_initial_response
+            .load(std::sync::atomic::Ordering::SeqCst);
+        if !has_sent_initial_response && !reply.attachments.is_empty() {
+            ctx.defer_response(reply.ephemeral).await?;
+        }
+    }
+
+    retry_ratelimited(ctx, reply, |reply| send_built_reply(ctx, reply)).await
+}
+
 /// Shorthand of [`send_reply`] for text-only messages
 pub async fn say_reply<U, E>(
     ctx: crate::Context<'_, U, E>,
@@ -154,3 +414,732 @@ pub async fn say_reply<U, E>(
 ) -> Result<Option<ReplyHandle<'_>>, serenity::Error> {
     send_reply(ctx, |m| m.content(text.into())).await
 }
+
+/// Like [`say_reply`], but the response is deleted after the given duration, for example for
+/// transient confirmations. Returns the sent message, or None if nothing was sent, like in
+/// autocomplete callbacks.
+///
+/// The deletion runs in a background task, so this function returns right after sending. If the
+/// message is deleted by someone else in the meantime, the scheduled deletion is skipped silently.
+/// Ephemeral application command responses can't be deleted this way.
+///
+/// ```rust,no_run
+/// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let ctx: poise::Context<'_, (), ()> = todo!();
+/// ctx.say_temp("Settings saved", std::time::Duration::from_secs(5)).await?;
+/// # Ok(()) }
+/// ```
+pub async fn say_temp_reply<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    text: impl Into<String>,
+    duration: std::time::Duration,
+) -> Result<Option<serenity::Message>, serenity::Error> {
+    let message = match say_reply(ctx, text).await? {
+        Some(handle) => handle.message().await?,
+        None => return Ok(None),
+    };
+
+    let discord = ctx.discord().clone();
+    let (channel_id, message_id) = (message.channel_id, message.id);
+    tokio::spawn(async move {
+        tokio::time::sleep(duration).await;
+        let request = channel_id.delete_message(&discord, message_id);
+        match send_response(&discord, Outgoing::Deletion { message_id }, request).await {
+            // The message was already deleted by someone else
+            Err(e) if is_unknown_message_error(&e) => {}
+            #[cfg(feature = "tracing")]
+            Err(e) => tracing::warn!(error = %e, "failed to delete temporary response"),
+            _ => {}
+        }
+    });
+
+    Ok(Some(message))
+}
+
+/// The message which a [`ProgressHandle`] edits
+enum ProgressMessage<'a> {
+    /// A normal message, sent in response to a prefix command
+    Message(serenity::MessageId),
+    /// The initial response to an application command
+    InitialResponse(&'a serenity::ApplicationCommandInteraction),
+    /// A followup message to an application command
+    Followup(
+        &'a serenity::ApplicationCommandInteraction,
+        serenity::MessageId,
+    ),
+}
+
+/// Returned from [`say_progress_reply`] to repeatedly edit a response, for example to report the
+/// progress of a long operation.
+///
+/// Edits are spaced at least [`Self::MIN_EDIT_INTERVAL`] apart to respect Discord rate limits: if
+/// [`Self::update`] is called sooner, it waits before editing. Dropping the handle leaves the
+/// message in its last state.
+pub struct ProgressHandle<'a> {
+    discord: &'a serenity::Context,
+    channel_id: serenity::ChannelId,
+    message: ProgressMessage<'a>,
+    last_edit: tokio::sync::Mutex<std::time::Instant>,
+}
+
+impl ProgressHandle<'_> {
+    /// Minimum duration between two edits of the message
+    pub const MIN_EDIT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+    /// Replaces the content of the message. Waits first if the previous edit was less than
+    /// [`Self::MIN_EDIT_INTERVAL`] ago. Concurrent updates are applied in order.
+    pub async fn update(&self, content: impl Into<String>) -> Result<(), serenity::Error> {
+        let content = content.into();
+        let mut last_edit = self.last_edit.lock().await;
+        let elapsed = last_edit.elapsed();
+        if elapsed < Self::MIN_EDIT_INTERVAL {
+            tokio::time::sleep(Self::MIN_EDIT_INTERVAL - elapsed).await;
+        }
+
+        let response = Outgoing::Message {
+            channel_id: self.channel_id,
+            content: Some(&content),
+        };
+        let request = async {
+            match self.message {
+                ProgressMessage::Message(message_id) => {
+                    self.channel_id
+                        .edit_message(self.discord, message_id, |f| f.content(&content))
+                        .await?;
+                }
+                ProgressMessage::InitialResponse(interaction) => {
+                    interaction
+                        .edit_original_interaction_response(self.discord, |f| f.content(&content))
+                        .await?;
+                }
+                ProgressMessage::Followup(interaction, message_id) => {
+                    interaction
+                        .edit_followup_message(self.discord, message_id, |f| f.content(&content))
+                        .await?;
+                }
+            }
+            Ok::<_, serenity::Error>(())
+        };
+        send_response(self.discord, response, request).await?;
+        *last_edit = std::time::Instant::now();
+        Ok(())
+    }
+}
+
+/// Sends a message which can be edited repeatedly via the returned [`ProgressHandle`], for
+/// example to report the progress of a long operation. Returns None if nothing was sent, like in
+/// autocomplete callbacks.
+///
+/// In prefix commands, the handle edits the sent message. In application commands, it edits the
+/// initial interaction response, or a followup message if a response was already sent or
+/// deferred.
+///
+/// ```rust,no_run
+/// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let ctx: poise::Context<'_, (), ()> = todo!();
+/// let progress = ctx.say_progress("Processing... 0%").await?;
+/// for percent in &[30, 60, 90] {
+///     // ... do some work ...
+///     if let Some(progress) = &progress {
+///         progress.update(format!("Processing... {}%", percent)).await?;
+///     }
+/// }
+/// if let Some(progress) = &progress {
+///     progress.update("Done!").await?;
+/// }
+/// # Ok(()) }
+/// ```
+pub async fn say_progress_reply<'a, U, E>(
+    ctx: crate::Context<'a, U, E>,
+    text: impl Into<String>,
+) -> Result<Option<ProgressHandle<'a>>, serenity::Error> {
+    let message = match ctx {
+        crate::Context::Prefix(ctx) => {
+            let message = crate::send_prefix_reply(ctx, |f| f.content(text.into())).await?;
+            ProgressMessage::Message(message.id)
+        }
+        crate::Context::Application(ctx) => {
+            let interaction = match ctx.interaction {
+                crate::ApplicationCommandOrAutocompleteInteraction::ApplicationCommand(x) => x,
+                crate::ApplicationCommandOrAutocompleteInteraction::Autocomplete(_) => {
+                    return Ok(None)
+                }
+            };
+            let has_sent_initial_response = ctx
+                .has_sent_initial_response
+                .load(std::sync::atomic::Ordering::SeqCst);
+
+            if has_sent_initial_response {
+                // Followups are sent directly, because send_application_reply doesn't return the
+                // followup message, whose ID is needed to edit it
+                let text = text.into();
+                let response = Outgoing::Message {
+                    channel_id: interaction.channel_id,
+                    content: Some(&text),
+                };
+                let reply = CreateReply {
+                    content: Some(text.clone()),
+                    ephemeral: ctx.command.options().ephemeral,
+                    ..Default::default()
+                };
+                let allowed_mentions = ctx.framework.options().allowed_mentions.as_ref();
+                let request = interaction.create_followup_message(ctx.discord, |f| {
+                    crate::slash::send_as_followup_response(reply, allowed_mentions, f);
+                    f
+                });
+                let message = match send_response(ctx.discord, response, request).await? {
+                    Some(message) => message,
+                    None => placeholder_message(ctx.discord, interaction.channel_id, Some(&text)),
+                };
+                ProgressMessage::Followup(interaction, message.id)
+            } else {
+                crate::send_application_reply(ctx, |f| f.content(text.into())).await?;
+                ProgressMessage::InitialResponse(interaction)
+            }
+        }
+    };
+
+    Ok(Some(ProgressHandle {
+        discord: ctx.discord(),
+        channel_id: ctx.channel_id(),
+        message,
+        last_edit: tokio::sync::Mutex::new(std::time::Instant::now()),
+    }))
+}
+
+/// Send a message to the given channel instead of the channel the command was invoked in, for
+/// example to a log channel.
+///
+/// [`crate::FrameworkOptions::allowed_mentions`] and [`crate::FrameworkOptions::response_retry`]
+/// apply like with [`send_reply`]. However, the message is not an interaction response and not
+/// edit tracked: the invocation and the response are in different channels, so editing the
+/// invocation doesn't edit this message. [`CreateReply::ephemeral`] is ignored.
+///
+/// In application commands, the interaction still needs to be responded to, for example with
+/// [`send_reply`], or Discord shows "The application did not respond".
+///
+/// ```rust,no_run
+/// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let ctx: poise::Context<'_, (), ()> = todo!();
+/// # let log_channel = poise::serenity_prelude::ChannelId(0);
+/// ctx.send_in(log_channel, |f| f.content("A user was reported")).await?;
+/// ctx.say("Thanks, the moderators were notified").await?;
+/// # Ok(()) }
+/// ```
+pub async fn send_reply_in<'a, U, E>(
+    ctx: crate::Context<'_, U, E>,
+    channel_id: serenity::ChannelId,
+    builder: impl for<'b> FnOnce(&'b mut CreateReply<'a>) -> &'b mut CreateReply<'a>,
+) -> Result<serenity::Message, serenity::Error> {
+    let mut reply = CreateReply::default();
+    builder(&mut reply);
+    retry_ratelimited(ctx, reply, |reply| send_message_in(ctx, channel_id, reply)).await
+}
+
+/// Sends a single attempt of an already built reply as a normal message to the given channel
+async fn send_message_in<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    channel_id: serenity::ChannelId,
+    reply: CreateReply<'_>,
+) -> Result<serenity::Message, serenity::Error> {
+    let content = reply.content.clone();
+    let response = Outgoing::Message {
+        channel_id,
+        content: content.as_deref(),
+    };
+    let request = create_message(ctx, channel_id, reply);
+    let sent = send_response(ctx.discord(), response, request).await?;
+    Ok(match sent {
+        Some(message) => message,
+        None => placeholder_message(ctx.discord(), channel_id, content.as_deref()),
+    })
+}
+
+/// Sends a single attempt of an already built reply to the invoking user via DM
+async fn send_direct_message<U, E>(
+    ctx: crate::PrefixContext<'_, U, E>,
+    reply: CreateReply<'_>,
+) -> Result<serenity::Message, serenity::Error> {
+    let content = reply.content.clone();
+    let response = Outgoing::DirectMessage {
+        user_id: ctx.msg.author.id,
+        content: content.as_deref(),
+    };
+    let request = async {
+        let dm_channel = ctx.msg.author.create_dm_channel(ctx.discord).await?;
+        create_message(ctx.into(), dm_channel.id, reply).await
+    };
+    let sent = send_response(ctx.discord, response, request).await?;
+    Ok(match sent {
+        Some(message) => message,
+        // The DM channel is unknown without asking Discord
+        None => placeholder_message(ctx.discord, serenity::ChannelId(0), content.as_deref()),
+    })
+}
+
+/// Sends the reply as a normal message to the given channel, bypassing [`send_response`]. Only
+/// used by [`send_message_in`] and [`send_direct_message`], which go through it
+async fn create_message<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    channel_id: serenity::ChannelId,
+    reply: CreateReply<'_>,
+) -> Result<serenity::Message, serenity::Error> {
+    let CreateReply {
+        content,
+        embed,
+        attachments,
+        components,
+        ephemeral: _,
+        allowed_mentions,
+    } = reply;
+
+    channel_id
+        .send_message(ctx.discord(), |m| {
+            if let Some(content) = content {
+                m.content(content);
+            }
+            if let Some(embed) = embed {
+                m.set_embed(embed);
+            }
+            let allowed_mentions =
+                allowed_mentions.or_else(|| ctx.framework().options().allowed_mentions.clone());
+            if let Some(allowed_mentions) = allowed_mentions {
+                m.allowed_mentions(|m| {
+                    *m = allowed_mentions;
+                    m
+                });
+            }
+            if let Some(components) = components {
+                m.components(|c| {
+                    c.0 = components.0;
+                    c
+                });
+            }
+            for attachment in attachments {
+                m.add_file(attachment);
+            }
+            m
+        })
+        .await
+}
+
+/// Shorthand of [`send_reply_in`] for text-only messages
+pub async fn say_reply_in<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    channel_id: serenity::ChannelId,
+    text: impl Into<String>,
+) -> Result<serenity::Message, serenity::Error> {
+    send_reply_in(ctx, channel_id, |m| m.content(text.into())).await
+}
+
+/// Opening and closing delimiter of code blocks
+const FENCE: &str = "```";
+
+/// Splits text into chunks of at most `limit` bytes, preferably at line breaks. Lines which don't
+/// fit into a chunk on their own are hard-wrapped, but never inside a code block fence.
+///
+/// If a chunk ends inside a code block, the code block is closed at the end of the chunk and
+/// reopened, including its language, at the start of the next chunk.
+fn split_message(text: &str, limit: usize) -> Vec<String> {
+    // Room for the newline and fence which may need to be appended to close a code block
+    let reserved = FENCE.len() + 1;
+
+    // Opening line of the code block that the text so far ends in, if any
+    let mut open_fence = None::<String>;
+    // Start of the next chunk: the reopened code block, if any. Falls back to a fence without the
+    // language if the language is so long that there would be no room left
+    let reopen = |open_fence: &Option<String>| match open_fence {
+        Some(fence) if fence.len() + 1 + reserved < limit => fence.clone(),
+        Some(_) => FENCE.to_owned(),
+        None => String::new(),
+    };
+
+    let mut chunks = Vec::new();
+    let mut chunk = String::new();
+    // Length of the reopened code block at the start of the chunk, i.e. the chunk is empty if
+    // it's not longer than this
+    let mut chunk_start = 0;
+    for line in text.split('\n') {
+        let mut rest = line;
+        loop {
+            let separator = if chunk.is_empty() { "" } else { "\n" };
+            let mut fence_after = open_fence.clone();
+            toggle_fences(&mut fence_after, rest, rest.len());
+            let closing = if fence_after.is_some() { reserved } else { 0 };
+            if chunk.len() + separator.len() + rest.len() + closing <= limit {
+                chunk += separator;
+                chunk += rest;
+                open_fence = fence_after;
+                break;
+            }
+
+            if chunk.len() > chunk_start {
+                finish_chunk(&mut chunks, &mut chunk, chunk_start, &open_fence);
+                chunk = reopen(&open_fence);
+                chunk_start = chunk.len();
+                continue;
+            }
+
+            // The line doesn't fit into a chunk on its own, so it's wrapped
+            let budget = limit.saturating_sub(chunk.len() + separator.len() + reserved);
+            let split_at = wrap_point(rest, budget, open_fence.is_some());
+            chunk += separator;
+            chunk += &rest[..split_at];
+            toggle_fences(&mut open_fence, rest, split_at);
+            rest = &rest[split_at..];
+
+            finish_chunk(&mut chunks, &mut chunk, chunk_start, &open_fence);
+            chunk = reopen(&open_fence);
+            chunk_start = chunk.len();
+        }
+    }
+    if chunk.len() > chunk_start {
+        chunks.push(chunk);
+    }
+    chunks
+}
+
+/// Closes the code block that the chunk ends in, if any, and adds the chunk unless it's empty.
+/// `chunk_start` is the length of the reopened code block at the start of the chunk.
+fn finish_chunk(
+    chunks: &mut Vec<String>,
+    chunk: &mut String,
+    chunk_start: usize,
+    open_fence: &Option<String>,
+) {
+    let mut chunk = std::mem::take(chunk);
+    if let Some(fence) = open_fence {
+        // A code block opened at the very end would be empty, so it's left to the next chunk,
+        // which starts with the fence anyways
+        if chunk.len() >= chunk_start + fence.len() && chunk.ends_with(fence.as_str()) {
+            chunk.truncate(chunk.len() - fence.len());
+            let content_len = chunk.trim_end_matches('\n').len();
+            chunk.truncate(content_len);
+        } else {
+            chunk += "\n";
+            chunk += FENCE;
+        }
+    }
+    if chunk.len() > chunk_start {
+        chunks.push(chunk);
+    }
+}
+
+/// Toggles the code block state at each fence in the first `end` bytes of `line`. The language of
+/// an opening fence is the rest of the line.
+fn toggle_fences(open_fence: &mut Option<String>, line: &str, end: usize) {
+    for (fence_start, _) in line[..end].match_indices(FENCE) {
+        *open_fence = match open_fence {
+            Some(_) => None,
+            None => Some(line[fence_start..].to_owned()),
+        };
+    }
+}
+
+/// Where to hard-wrap a line which is too long for a chunk, given the room in the chunk. Doesn't
+/// cut through a fence, i.e. a run of backticks, or the language after an opening fence. Always
+/// returns a non-zero index, so that wrapping makes progress.
+fn wrap_point(line: &str, budget: usize, in_code_block: bool) -> usize {
+    let mut split_at = budget.min(line.len());
+    while !line.is_char_boundary(split_at) {
+        split_at -= 1;
+    }
+    while split_at > 0 && line[..split_at].ends_with('`') && line[split_at..].starts_with('`') {
+        split_at -= 1;
+    }
+    if split_at == 0 {
+        // Not even the first character or fence fits, so it's taken anyways to make progress
+        let backticks = line.len() - line.trim_start_matches('`').len();
+        let first_char_len = line.chars().next().map_or(0, char::len_utf8);
+        return backticks.max(first_char_len);
+    }
+
+    // The language of an opening fence is only known once the rest of the line is in the chunk
+    let mut opening_fence_start = None;
+    let mut in_code_block = in_code_block;
+    for (fence_start, _) in line[..split_at].match_indices(FENCE) {
+        in_code_block = !in_code_block;
+        opening_fence_start = Some(fence_start).filter(|_| in_code_block);
+    }
+    match opening_fence_start {
+        Some(fence_start) if fence_start > 0 => fence_start,
+        _ => split_at,
+    }
+}
+
+/// Like [`say_reply`], but splits text that is longer than Discord's message length limit into
+/// multiple messages, preferably at line breaks. Code blocks that are split are closed and
+/// reopened in the next message.
+///
+/// In prefix commands, all messages are replies to the invocation if
+/// [`crate::PrefixFrameworkOptions::reply_to_invocation`] is set, and all of them are edit
+/// tracked. When the command is re-run after an edit, the messages are edited, and messages which
+/// are no longer needed are deleted.
+pub async fn say_chunked_reply<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    text: &str,
+) -> Result<(), serenity::Error> {
+    // Discord's limit is 2000 characters; counting bytes is conservative
+    const MESSAGE_LIMIT: usize = 2000;
+
+    let mut chunks = split_message(text, MESSAGE_LIMIT).into_iter();
+    match ctx {
+        crate::Context::Prefix(prefix_ctx) => {
+            if let Some(first_chunk) = chunks.next() {
+                say_reply(ctx, first_chunk).await?;
+            }
+            // Sending subsequent chunks through the edit tracker would overwrite the first chunk,
+            // so they are tracked separately
+            let rest = chunks.collect::<Vec<_>>();
+            crate::send_prefix_reply_chunks(prefix_ctx, &rest).await?;
+        }
+        crate::Context::Application(_) => {
+            for chunk in chunks {
+                say_reply(ctx, chunk).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether the given error is Discord's "Cannot send messages to this user" error, which occurs
+/// when the user has disabled DMs from server members or blocked the bot
+fn is_cannot_message_user_error(error: &serenity::Error) -> bool {
+    match error {
+        serenity::Error::Http(error) => match &**error {
+            serenity::HttpError::UnsuccessfulRequest(response) => response.error.code == 50007,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Sends a reply that only the invoking user can see.
+///
+/// In application commands, the reply is sent as an ephemeral response. In prefix commands, the
+/// reply is sent to the user via DM and
+/// [`crate::PrefixFrameworkOptions::private_reply_reaction`] is added to the invoking message.
+/// Prefix commands invoked in DMs are replied to normally.
+///
+/// If the user doesn't accept DMs from the bot, the reply is sent to the invocation channel
+/// instead, unless [`crate::PrefixFrameworkOptions::private_reply_channel_fallback`] is disabled.
+pub async fn say_private_reply<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    text: impl Into<String>,
+) -> Result<(), serenity::Error> {
+    let text = text.into();
+    let ctx = match ctx {
+        crate::Context::Prefix(ctx) if ctx.msg.guild_id.is_some() => ctx,
+        _ => {
+            send_reply(ctx, |f| f.content(text).ephemeral(true)).await?;
+            return Ok(());
+        }
+    };
+
+    let options = &ctx.framework.options().prefix_options;
+    let mut reply = CreateReply::default();
+    reply.content(text.clone());
+    match send_direct_message(ctx, reply).await {
+        Ok(_) => {
+            if let Some(reaction) = &options.private_reply_reaction {
+                // The reply itself was delivered, so a failing reaction is no reason to error
+                let _: Result<_, _> = add_reaction(ctx.discord, ctx.msg, reaction).await;
+            }
+        }
+        Err(e) if options.private_reply_channel_fallback && is_cannot_message_user_error(&e) => {
+            say_reply(ctx.into(), text).await?;
+        }
+        Err(e) => return Err(e),
+    }
+    Ok(())
+}
+
+/// A response which a command returns instead of sending it itself. The framework sends it after
+/// the command finished, going through the same code path as [`send_reply`]. That way, allowed
+/// mentions, edit tracking and response retries apply as usual.
+///
+/// ```rust
+/// # type Error = Box<dyn std::error::Error + Send + Sync>;
+/// /// Responds with pong
+/// #[poise::command(prefix_command, slash_command)]
+/// async fn ping(ctx: poise::Context<'_, (), Error>) -> Result<poise::CommandResponse, Error> {
+///     Ok(poise::CommandResponse::Text("Pong!".into()))
+/// }
+/// ```
+#[derive(Clone)]
+pub enum CommandResponse {
+    /// Don't send anything
+    None,
+    /// Send a text message. Text longer than Discord's message length limit is split into
+    /// multiple messages like in [`say_chunked_reply`]
+    Text(String),
+    /// Send a single embed
+    Embed(serenity::CreateEmbed),
+    /// Send an arbitrary reply
+    Reply(CreateReply<'static>),
+}
+
+/// Implemented for the types which commands can return in their `Ok` variant: `()` for commands
+/// that send their responses themselves, and [`CommandResponse`] for responses sent by the
+/// framework.
+///
+/// Sending a [`CommandResponse`] requires the error type to be convertible from
+/// [`serenity::Error`].
+pub trait CommandReturn<U, E> {
+    /// Sends the response in the given context, if there is one
+    fn send_response(self, ctx: crate::Context<'_, U, E>) -> crate::BoxFuture<'_, Result<(), E>>;
+}
+
+impl<U, E> CommandReturn<U, E> for () {
+    fn send_response(self, _: crate::Context<'_, U, E>) -> crate::BoxFuture<'_, Result<(), E>> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+impl<U, E> CommandReturn<U, E> for CommandResponse
+where
+    U: Send + Sync,
+    E: From<serenity::Error>,
+{
+    fn send_response(self, ctx: crate::Context<'_, U, E>) -> crate::BoxFuture<'_, Result<(), E>> {
+        Box::pin(async move {
+            match self {
+                CommandResponse::None => {}
+                CommandResponse::Text(text) => say_chunked_reply(ctx, &text).await?,
+                CommandResponse::Embed(embed) => {
+                    send_reply(ctx, |f| {
+                        f.embed = Some(embed);
+                        f
+                    })
+                    .await?;
+                }
+                CommandResponse::Reply(reply) => {
+                    send_reply(ctx, |f| {
+                        *f = reply;
+                        f
+                    })
+                    .await?;
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Returned from [`add_reactions`]
+#[derive(Debug, Default)]
+pub struct AddReactionsOutcome {
+    /// Reactions which couldn't be added, along with the reason
+    pub failed: Vec<(serenity::ReactionType, serenity::Error)>,
+    /// Whether the message was deleted while adding reactions. The remaining reactions are
+    /// skipped in that case and not included in [`Self::failed`]
+    pub message_deleted: bool,
+}
+
+impl AddReactionsOutcome {
+    /// Returns true if all reactions were added
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty() && !self.message_deleted
+    }
+}
+
+/// Whether the given error is Discord's "Unknown Message" error
+fn is_unknown_message_error(error: &serenity::Error) -> bool {
+    match error {
+        serenity::Error::Http(error) => match &**error {
+            serenity::HttpError::UnsuccessfulRequest(response) => response.error.code == 10008,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Adds a single reaction via [`send_response`]
+async fn add_reaction(
+    discord: &serenity::Context,
+    message: &serenity::Message,
+    reaction: &serenity::ReactionType,
+) -> Result<(), serenity::Error> {
+    let response = Outgoing::Reaction {
+        message_id: message.id,
+        reaction,
+    };
+    send_response(discord, response, message.react(discord, reaction.clone())).await?;
+    Ok(())
+}
+
+/// Adds multiple reactions to a message one after another, for example for polls.
+///
+/// Reactions are added sequentially, so serenity's rate limiter can space out the requests.
+/// Failing reactions don't abort the others; they are reported in the returned
+/// [`AddReactionsOutcome`]. If the message is deleted midway, the remaining reactions are skipped.
+pub async fn add_reactions<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    message: &serenity::Message,
+    reactions: &[serenity::ReactionType],
+) -> AddReactionsOutcome {
+    let mut outcome = AddReactionsOutcome::default();
+    for reaction in reactions {
+        if let Err(e) = add_reaction(ctx.discord(), message, reaction).await {
+            if is_unknown_message_error(&e) {
+                outcome.message_deleted = true;
+                break;
+            }
+            outcome.failed.push((reaction.clone(), e));
+        }
+    }
+    outcome
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_split_message() {
+        assert_eq!(split_message("", 10), Vec::<String>::new());
+        assert_eq!(split_message("hello", 10), vec!["hello"]);
+        assert_eq!(split_message("hello\nworld", 10), vec!["hello", "world"]);
+        // Room for closing a code block is always reserved
+        assert_eq!(
+            split_message("helloworldhello", 10),
+            vec!["hellow", "orldhello"]
+        );
+        for chunk in split_message("```rust\nfn a() {}\nfn b() {}\n```\ndone", 20) {
+            assert!(chunk.len() <= 20);
+            assert_eq!(chunk.matches("```").count() % 2, 0, "{:?}", chunk);
+        }
+        assert_eq!(
+            split_message("```rust\nfn a() {}\nfn b() {}\n```", 25),
+            vec!["```rust\nfn a() {}\n```", "```rust\nfn b() {}\n```"],
+        );
+        // Long lines in code blocks are wrapped, and the code block is reopened around them
+        let long_line = format!("```rust\n{}\n```", "x".repeat(30));
+        assert_eq!(
+            split_message(&long_line, 20),
+            vec![
+                "```rust\nxxxxxxxx\n```",
+                "```rust\nxxxxxxxx\n```",
+                "```rust\nxxxxxxxx\n```",
+                "```rust\nxxxxxx\n```"
+            ],
+        );
+        // Wrapping doesn't cut through fences
+        for limit in 10..30 {
+            for text in &[
+                &*long_line,
+                "ab```cd```ef```gh```ij",
+                "text```rust\nfn main() {}\n```",
+            ] {
+                for chunk in split_message(text, limit) {
+                    assert!(chunk.len() <= limit, "{:?}", chunk);
+                    assert_eq!(chunk.matches("```").count() % 2, 0, "{:?}", chunk);
+                }
+            }
+        }
+        assert_eq!(
+            split_message("text```rust\nfn main() {}\n```", 25),
+            vec!["text", "```rust\nfn main() {}\n```"],
+        );
+    }
+}
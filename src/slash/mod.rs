@@ -19,6 +19,7 @@ fn send_as_initial_response(
         attachments: _, // discord doesn't support attachments in initial response :(
         components,
         ephemeral,
+        allowed_mentions: reply_allowed_mentions,
     } = data;
 
     if let Some(content) = content {
@@ -27,9 +28,10 @@ fn send_as_initial_response(
     if let Some(embed) = embed {
         f.add_embed(embed);
     }
-    if let Some(allowed_mentions) = allowed_mentions {
+    // Per-reply allowed mentions take precedence over the framework-wide default
+    if let Some(allowed_mentions) = reply_allowed_mentions.or_else(|| allowed_mentions.cloned()) {
         f.allowed_mentions(|f| {
-            *f = allowed_mentions.clone();
+            *f = allowed_mentions;
             f
         });
     }
@@ -44,7 +46,7 @@ fn send_as_initial_response(
     }
 }
 
-fn send_as_followup_response<'a>(
+pub(crate) fn send_as_followup_response<'a>(
     data: crate::CreateReply<'a>,
     allowed_mentions: Option<&serenity::CreateAllowedMentions>,
     f: &mut serenity::CreateInteractionResponseFollowup<'a>,
@@ -55,6 +57,7 @@ fn send_as_followup_response<'a>(
         attachments,
         components,
         ephemeral,
+        allowed_mentions: reply_allowed_mentions,
     } = data;
 
     if let Some(content) = content {
@@ -69,9 +72,10 @@ fn send_as_followup_response<'a>(
             c
         });
     }
-    if let Some(allowed_mentions) = allowed_mentions {
+    // Per-reply allowed mentions take precedence over the framework-wide default
+    if let Some(allowed_mentions) = reply_allowed_mentions.or_else(|| allowed_mentions.cloned()) {
         f.allowed_mentions(|f| {
-            *f = allowed_mentions.clone();
+            *f = allowed_mentions;
             f
         });
     }
@@ -106,24 +110,27 @@ pub async fn send_application_reply<'a, U, E>(
         .has_sent_initial_response
         .load(std::sync::atomic::Ordering::SeqCst);
 
+    let content = data.content.clone();
+    let outgoing = crate::reply::Outgoing::Message {
+        channel_id: interaction.channel_id,
+        content: content.as_deref(),
+    };
     let allowed_mentions = ctx.framework.options().allowed_mentions.as_ref();
     if has_sent_initial_response {
-        interaction
-            .create_followup_message(ctx.discord, |f| {
-                send_as_followup_response(data, allowed_mentions, f);
-                f
-            })
-            .await?;
+        let request = interaction.create_followup_message(ctx.discord, |f| {
+            send_as_followup_response(data, allowed_mentions, f);
+            f
+        });
+        crate::reply::send_response(ctx.discord, outgoing, request).await?;
     } else {
-        interaction
-            .create_interaction_response(ctx.discord, |r| {
-                r.kind(serenity::InteractionResponseType::ChannelMessageWithSource)
-                    .interaction_response_data(|f| {
-                        send_as_initial_response(data, allowed_mentions, f);
-                        f
-                    })
-            })
-            .await?;
+        let request = interaction.create_interaction_response(ctx.discord, |r| {
+            r.kind(serenity::InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|f| {
+                    send_as_initial_response(data, allowed_mentions, f);
+                    f
+                })
+        });
+        crate::reply::send_response(ctx.discord, outgoing, request).await?;
         ctx.has_sent_initial_response
             .store(true, std::sync::atomic::Ordering::SeqCst);
     }
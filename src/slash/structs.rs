@@ -59,6 +59,14 @@ impl<'a> ApplicationCommandOrAutocompleteInteraction<'a> {
             Self::Autocomplete(x) => &x.user,
         }
     }
+
+    /// Returns the locale of the invoking user's Discord client, like `en-US`
+    pub fn locale(self) -> &'a str {
+        match self {
+            Self::ApplicationCommand(x) => &x.locale,
+            Self::Autocomplete(x) => &x.locale,
+        }
+    }
 }
 
 /// Application command specific context passed to command invocations.
@@ -78,6 +86,9 @@ pub struct ApplicationContext<'a, U, E> {
     pub framework: &'a Framework<U, E>,
     /// The command object which is the current command
     pub command: ApplicationCommand<'a, U, E>,
+    /// Names of the command groups containing the invoked command followed by its own name. See
+    /// [`crate::Context::invoked_command_path`]
+    pub invoked_command_path: &'a [&'static str],
     /// Your custom user data
     pub data: &'a U,
 }
@@ -115,6 +126,11 @@ impl<U, E> ApplicationContext<'_, U, E> {
             .store(true, std::sync::atomic::Ordering::SeqCst);
         Ok(())
     }
+
+    /// Returns the ID of the shard which received the interaction
+    pub fn shard_id(&self) -> serenity::ShardId {
+        serenity::ShardId(self.discord.shard_id)
+    }
 }
 
 /// Application command specific context to an error in user code
@@ -137,9 +153,21 @@ impl<U, E> Clone for ApplicationCommandErrorContext<'_, U, E> {
 /// Application command specific configuration of a framework command
 pub struct ApplicationCommandOptions<U, E> {
     /// Falls back to the framework-specified value on None. See there for documentation.
-    pub on_error: Option<fn(E, ApplicationCommandErrorContext<'_, U, E>) -> BoxFuture<'_, ()>>,
+    ///
+    /// May capture state; see [`Self::set_on_error`].
+    pub on_error: Option<
+        std::sync::Arc<
+            dyn for<'a> Fn(E, ApplicationCommandErrorContext<'a, U, E>) -> BoxFuture<'a, ()>
+                + Send
+                + Sync,
+        >,
+    >,
     /// If this function returns false, this command will not be executed.
     pub check: Option<fn(ApplicationContext<'_, U, E>) -> BoxFuture<'_, Result<bool, E>>>,
+    /// Like [`Self::check`], but can explain why the command was rejected. The reason is passed to
+    /// [`crate::FrameworkOptions::on_check_failure`] unless the rejection is silent.
+    pub check_with_reason:
+        Option<fn(ApplicationContext<'_, U, E>) -> BoxFuture<'_, Result<crate::CheckResult, E>>>,
     /// Whether responses to this command should be ephemeral by default.
     pub ephemeral: bool,
 }
@@ -149,11 +177,25 @@ impl<U, E> Default for ApplicationCommandOptions<U, E> {
         Self {
             on_error: None,
             check: None,
+            check_with_reason: None,
             ephemeral: false,
         }
     }
 }
 
+impl<U, E> ApplicationCommandOptions<U, E> {
+    /// Sets [`Self::on_error`] to the given closure, which may capture state.
+    pub fn set_on_error<F>(&mut self, on_error: F)
+    where
+        F: for<'a> Fn(E, ApplicationCommandErrorContext<'a, U, E>) -> BoxFuture<'a, ()>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.on_error = Some(std::sync::Arc::new(on_error));
+    }
+}
+
 /// A single parameter of a slash command
 pub struct SlashCommandParameter<U, E> {
     /// Builder function for this parameters
@@ -1,6 +1,21 @@
 use crate::serenity_prelude as serenity;
 
 /// Wrapper around either [`crate::ApplicationContext`] or [`crate::PrefixContext`]
+///
+/// Commands which take a [`Context`] can be registered as both prefix and slash command with the
+/// same body. Its methods, like [`Self::say`] or [`Self::author`], abstract over the differences:
+///
+/// ```rust
+/// # type Error = Box<dyn std::error::Error + Send + Sync>;
+/// #[poise::command(prefix_command, slash_command)]
+/// async fn ping(ctx: poise::Context<'_, (), Error>) -> Result<(), Error> {
+///     ctx.say(format!("Pong, {}!", ctx.author().name)).await?;
+///     Ok(())
+/// }
+/// ```
+///
+/// Use the [`crate::PrefixContext`] or [`crate::ApplicationContext`] directly only for behavior
+/// that is specific to one command type.
 pub enum Context<'a, U, E> {
     /// Application command context
     Application(crate::ApplicationContext<'a, U, E>),
@@ -32,10 +47,16 @@ impl<'a, U, E> Context<'a, U, E> {
     ///
     /// No-op if this is an autocomplete context
     ///
+    /// In prefix commands, a typing indicator is shown instead, so that the same command code
+    /// acknowledges the invocation in both cases. Discord hides the indicator once the bot sends
+    /// a message, or after about ten seconds; for a longer lasting indicator, see
+    /// [`Self::defer_or_broadcast()`].
+    ///
     /// This will make the response public; to make it ephemeral, use [`Self::defer_ephemeral()`].
     pub async fn defer(self) -> Result<(), serenity::Error> {
-        if let Self::Application(ctx) = self {
-            ctx.defer_response(false).await?;
+        match self {
+            Self::Application(ctx) => ctx.defer_response(false).await?,
+            Self::Prefix(ctx) => ctx.msg.channel_id.broadcast_typing(ctx.discord).await?,
         }
         Ok(())
     }
@@ -44,8 +65,9 @@ impl<'a, U, E> Context<'a, U, E> {
     ///
     /// This will make the response ephemeral; to make it public, use [`Self::defer()`].
     pub async fn defer_ephemeral(self) -> Result<(), serenity::Error> {
-        if let Self::Application(ctx) = self {
-            ctx.defer_response(true).await?;
+        match self {
+            Self::Application(ctx) => ctx.defer_response(true).await?,
+            Self::Prefix(ctx) => ctx.msg.channel_id.broadcast_typing(ctx.discord).await?,
         }
         Ok(())
     }
@@ -73,6 +95,96 @@ impl<'a, U, E> Context<'a, U, E> {
         crate::say_reply(self, text).await
     }
 
+    /// Shorthand of [`crate::say_temp_reply`]
+    pub async fn say_temp(
+        self,
+        text: impl Into<String>,
+        duration: std::time::Duration,
+    ) -> Result<Option<serenity::Message>, serenity::Error> {
+        crate::say_temp_reply(self, text, duration).await
+    }
+
+    /// Shorthand of [`crate::say_progress_reply`]
+    pub async fn say_progress(
+        self,
+        text: impl Into<String>,
+    ) -> Result<Option<crate::ProgressHandle<'a>>, serenity::Error> {
+        crate::say_progress_reply(self, text).await
+    }
+
+    /// Shorthand of [`crate::builtins::confirm`]
+    #[cfg(feature = "collector")]
+    pub async fn confirm(self, prompt: &str) -> Result<bool, crate::builtins::ConfirmationError> {
+        crate::builtins::confirm(self, prompt).await
+    }
+
+    /// Registers a handler for message component interactions with the given custom ID, like
+    /// clicks on a button sent by this command. The handler stays active for
+    /// [`crate::FrameworkOptions::component_timeout`].
+    ///
+    /// See [`crate::Framework::register_component`] for details.
+    ///
+    /// ```rust,no_run
+    /// # async fn _test(ctx: poise::Context<'_, (), poise::serenity::Error>) -> Result<(), poise::serenity::Error> {
+    /// # use poise::serenity_prelude as serenity;
+    /// let custom_id = format!("{}_accept", ctx.id());
+    /// ctx.register_component(custom_id.clone(), |ctx| {
+    ///     Box::pin(async move {
+    ///         ctx.interaction
+    ///             .create_interaction_response(ctx.discord, |r| {
+    ///                 r.interaction_response_data(|d| d.content("Accepted!"))
+    ///             })
+    ///             .await
+    ///     })
+    /// });
+    /// ctx.send(|m| {
+    ///     m.content("Accept the rules?").components(|c| {
+    ///         c.create_action_row(|r| r.create_button(|b| b.custom_id(custom_id).label("Accept")))
+    ///     })
+    /// })
+    /// .await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn register_component<F>(&self, custom_id: impl Into<String>, handler: F)
+    where
+        F: for<'b> Fn(crate::ComponentContext<'b, U, E>) -> crate::BoxFuture<'b, Result<(), E>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let framework = self.framework();
+        let timeout = framework.options().component_timeout;
+        framework.register_component(custom_id, timeout, handler);
+    }
+
+    /// Shorthand of [`crate::say_chunked_reply`]
+    pub async fn say_chunked(self, text: &str) -> Result<(), serenity::Error> {
+        crate::say_chunked_reply(self, text).await
+    }
+
+    /// Shorthand of [`crate::say_private_reply`]
+    pub async fn say_private(self, text: impl Into<String>) -> Result<(), serenity::Error> {
+        crate::say_private_reply(self, text).await
+    }
+
+    /// Shorthand of [`crate::say_reply_in`]
+    pub async fn say_in(
+        self,
+        channel_id: serenity::ChannelId,
+        text: impl Into<String>,
+    ) -> Result<serenity::Message, serenity::Error> {
+        crate::say_reply_in(self, channel_id, text).await
+    }
+
+    /// Shorthand of [`crate::add_reactions`]
+    pub async fn add_reactions(
+        self,
+        message: &serenity::Message,
+        reactions: &[serenity::ReactionType],
+    ) -> crate::AddReactionsOutcome {
+        crate::add_reactions(self, message, reactions).await
+    }
+
     /// Shorthand of [`crate::send_reply`]
     pub async fn send<'b>(
         self,
@@ -80,9 +192,42 @@ impl<'a, U, E> Context<'a, U, E> {
     ) -> Result<Option<crate::ReplyHandle<'a>>, serenity::Error> {
         crate::send_reply(self, builder).await
     }
+
+    /// Shorthand of [`crate::send_files_reply`]
+    pub async fn send_files<'b>(
+        self,
+        builder: impl for<'c> FnOnce(&'c mut crate::CreateReply<'b>) -> &'c mut crate::CreateReply<'b>,
+        files: Vec<serenity::AttachmentType<'b>>,
+    ) -> Result<Option<crate::ReplyHandle<'a>>, serenity::Error> {
+        crate::send_files_reply(self, builder, files).await
+    }
+
+    /// Shorthand of [`crate::send_reply_in`]
+    pub async fn send_in<'b>(
+        self,
+        channel_id: serenity::ChannelId,
+        builder: impl for<'c> FnOnce(&'c mut crate::CreateReply<'b>) -> &'c mut crate::CreateReply<'b>,
+    ) -> Result<serenity::Message, serenity::Error> {
+        crate::send_reply_in(self, channel_id, builder).await
+    }
 }
 
 impl<'a, U, E> Context<'a, U, E> {
+    /// Returns the locale to respond in, like `de` or `en-US`.
+    ///
+    /// In application commands, this is the locale of the invoking user's Discord client. In prefix
+    /// commands, it's determined by [`crate::PrefixFrameworkOptions::locale`], or None if that is
+    /// not set.
+    pub async fn locale(self) -> Option<String> {
+        match self {
+            Self::Application(ctx) => Some(ctx.interaction.locale().to_owned()),
+            Self::Prefix(ctx) => match ctx.framework.options().prefix_options.locale {
+                Some(locale) => locale(ctx).await,
+                None => None,
+            },
+        }
+    }
+
     /// Return the stored [`serenity::Context`] within the underlying context type.
     pub fn discord(&self) -> &'a serenity::Context {
         match self {
@@ -91,6 +236,15 @@ impl<'a, U, E> Context<'a, U, E> {
         }
     }
 
+    /// Returns the ID of the shard which received the invocation, for example to include it in
+    /// logs of sharded bots
+    pub fn shard_id(&self) -> serenity::ShardId {
+        match self {
+            Self::Application(ctx) => ctx.shard_id(),
+            Self::Prefix(ctx) => ctx.shard_id(),
+        }
+    }
+
     /// Return a read-only reference to [`crate::Framework`].
     pub fn framework(&self) -> &'a crate::Framework<U, E> {
         match self {
@@ -147,6 +301,53 @@ impl<'a, U, E> Context<'a, U, E> {
         }
     }
 
+    /// Whether the command was invoked by a bot account. Only possible in prefix commands, because
+    /// bots can't invoke application commands. The bot's own messages only invoke commands if
+    /// allowed, see [`crate::PrefixCommandOptions::allow_self_author`].
+    ///
+    /// Messages sent by webhooks count as bot messages as well, see [`Self::is_webhook`].
+    pub fn is_bot(&self) -> bool {
+        self.author().bot
+    }
+
+    /// Whether the command was invoked by a message sent via a webhook. Always false in
+    /// application commands.
+    pub fn is_webhook(&self) -> bool {
+        match self {
+            Self::Application(_) => false,
+            Self::Prefix(ctx) => ctx.msg.webhook_id.is_some(),
+        }
+    }
+
+    /// Returns the guild member object of the command author, or None if not invoked in a guild.
+    ///
+    /// In application commands, the member object sent along with the interaction is returned.
+    /// In prefix commands, [`serenity::Message::member`] lacks some fields, so the member is
+    /// retrieved from the cache instead, or via HTTP on cache miss. The HTTP result is inserted
+    /// into the cache if the guild is cached, so that subsequent calls don't need a request.
+    ///
+    /// Returns None as well if the HTTP request fails.
+    pub async fn author_member(self) -> Option<serenity::Member> {
+        let guild_id = self.guild_id()?;
+        match self {
+            Self::Application(ctx) => ctx.interaction.member().cloned(),
+            Self::Prefix(ctx) => {
+                let user_id = ctx.msg.author.id;
+                if let Some(member) = ctx.discord.cache.member(guild_id, user_id) {
+                    return Some(member);
+                }
+                let member = ctx
+                    .discord
+                    .http
+                    .get_member(guild_id.0, user_id.0)
+                    .await
+                    .ok()?;
+                cache_member(&ctx.discord.cache, &member);
+                Some(member)
+            }
+        }
+    }
+
     /// Return a ID that uniquely identifies this command invocation.
     pub fn id(&self) -> u64 {
         match self {
@@ -177,6 +378,25 @@ impl<'a, U, E> Context<'a, U, E> {
         })
     }
 
+    /// Returns the names of the invoked command's parent commands followed by its own name, for
+    /// example `["config", "set"]` when invoking the `set` subcommand of `config`. Useful for
+    /// logging or analytics in code shared by many commands:
+    ///
+    /// ```rust
+    /// # async fn _test(ctx: poise::Context<'_, (), ()>) {
+    /// println!("{} invoked {}", ctx.author().name, ctx.invoked_command_path().join(" "));
+    /// # }
+    /// ```
+    ///
+    /// Commands which weren't registered with the framework only report their own name. Returns an
+    /// empty slice if there is no command, i.e. if [`Self::command`] returns None.
+    pub fn invoked_command_path(&self) -> &'a [&'static str] {
+        match self {
+            Self::Prefix(ctx) => ctx.invoked_command_path,
+            Self::Application(ctx) => ctx.invoked_command_path,
+        }
+    }
+
     /// Returns the prefix this command was invoked with, or a slash (`/`), if this is an
     /// application command.
     pub fn prefix(&self) -> &'a str {
@@ -186,3 +406,15 @@ impl<'a, U, E> Context<'a, U, E> {
         }
     }
 }
+
+/// Inserts a member retrieved via HTTP into serenity's cache. serenity only fills its cache from
+/// gateway events, so the member is passed through the member update event which Discord would
+/// send for it. Does nothing if the member's guild isn't cached.
+fn cache_member(cache: &serenity::Cache, member: &serenity::Member) {
+    // The event payload consists of the member fields, including the guild ID
+    let event: Result<serenity::GuildMemberUpdateEvent, _> =
+        serde_json::to_value(member).and_then(serde_json::from_value);
+    if let Ok(mut event) = event {
+        cache.update(&mut event);
+    }
+}
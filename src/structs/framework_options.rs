@@ -22,6 +22,11 @@ fn prepare_command_definition<U, E>(
     } else {
         panic!("Empty command definition (no implementations)");
     };
+    debug_assert!(
+        !(id.guild_only && id.dm_only),
+        "command {} can't be both guild_only and dm_only",
+        id.identifying_name,
+    );
     if let Some(prefix_command) = &mut prefix_command {
         prefix_command.id = id.clone();
     }
@@ -103,29 +108,138 @@ impl<U, E> CommandBuilder<U, E> {
     }
 }
 
+/// How the default [`FrameworkOptions::on_error`] handler deals with command errors. Set via
+/// [`FrameworkOptions::default_error_handler`].
+pub enum DefaultErrorHandler<E> {
+    /// Print the error to the console
+    Log,
+    /// Reply to the invoking user with a message built from the error
+    ReplyToUser {
+        /// Formats the error into the reply message
+        template: fn(&E) -> String,
+    },
+    /// Discard the error
+    Silent,
+}
+impl<E> Clone for DefaultErrorHandler<E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<E> Copy for DefaultErrorHandler<E> {}
+
 /// Framework configuration
 pub struct FrameworkOptions<U, E> {
     /// Provide a callback to be invoked when any user code yields an error.
-    pub on_error: fn(E, crate::ErrorContext<'_, U, E>) -> BoxFuture<'_, ()>,
-    /// Called before every command
+    ///
+    /// Unlike most other callbacks, this may be a closure that captures state, for example a
+    /// metrics client. See [`Self::set_on_error`].
+    ///
+    /// The default handler handles command errors according to [`Self::default_error_handler`].
+    ///
+    /// With the `tracing` feature, command errors are logged in the invocation span with their
+    /// [location](crate::CommandErrorLocation) only, because the error type isn't required to
+    /// implement `Display`. Log the error itself here if needed.
+    pub on_error: std::sync::Arc<
+        dyn for<'a> Fn(E, crate::ErrorContext<'a, U, E>) -> BoxFuture<'a, ()> + Send + Sync,
+    >,
+    /// How the default [`Self::on_error`] handler deals with errors from commands and their
+    /// callbacks. Has no effect if [`Self::on_error`] was replaced, or if the command has its own
+    /// error handler.
+    ///
+    /// Other errors, for example from the event listener or user data setup, are always logged by
+    /// the default handler. Defaults to [`DefaultErrorHandler::Log`].
+    pub default_error_handler: DefaultErrorHandler<E>,
+    /// Called before every command, after all checks have passed
     pub pre_command: fn(crate::Context<'_, U, E>) -> BoxFuture<'_, ()>,
-    /// Called after every command
-    pub post_command: fn(crate::Context<'_, U, E>) -> BoxFuture<'_, ()>,
-    /// Provide a callback to be invoked before every command. The command will only be executed
-    /// if the callback returns true.
+    /// Called after every command, along with the outcome of the command.
+    ///
+    /// This also runs if the command returned an error or panicked, so it can be used to reliably
+    /// clean up resources, like committing or rolling back a database transaction.
     ///
-    /// If individual commands add their own check, both callbacks are run and must return true.
+    /// If a check rejects the invocation, this runs as well, with
+    /// [`crate::CommandOutcome::Rejected`]. [`Self::pre_command`] isn't called in that case.
+    pub post_command:
+        for<'a> fn(crate::Context<'a, U, E>, crate::CommandOutcome<'a, E>) -> BoxFuture<'a, ()>,
+    /// Provide a callback to be invoked before every prefix and application command. The command
+    /// will only be executed if the callback returns true.
+    ///
+    /// This global check runs before any command specific check. If individual commands add their
+    /// own check, it is only run if this callback returned true, and must return true as well.
+    /// For prefix subcommands, it only runs once per invocation, for the top-level command.
     pub command_check: Option<fn(crate::Context<'_, U, E>) -> BoxFuture<'_, Result<bool, E>>>,
+    /// Called when [`Self::command_check`] returns false
+    pub on_global_check_failure:
+        Option<fn(crate::Context<'_, U, E>) -> BoxFuture<'_, Result<(), E>>>,
+    /// Called when a command's `check_with_reason` rejects an invocation non-silently. The
+    /// rejection reason is given as an argument.
+    pub on_check_failure:
+        Option<fn(crate::Context<'_, U, E>, Option<String>) -> BoxFuture<'_, Result<(), E>>>,
+    /// Called when a user who is not in [`Self::owners`] invokes a command with
+    /// [`crate::CommandId::owners_only`] set
+    pub not_an_owner_handler: Option<fn(crate::Context<'_, U, E>) -> BoxFuture<'_, Result<(), E>>>,
+    /// Called if the invoking user is lacking any of the permissions specified in
+    /// [`crate::CommandId::required_permissions`]. The list of missing permissions is given as an
+    /// argument.
+    ///
+    /// If None, application commands fall back to
+    /// [`crate::ApplicationFrameworkOptions::missing_permissions_handler`] and prefix commands
+    /// are silently ignored.
+    pub missing_user_permissions_handler:
+        Option<fn(crate::Context<'_, U, E>, serenity::Permissions) -> BoxFuture<'_, Result<(), E>>>,
+    /// Called when a command with [`crate::CommandId::guild_only`] is invoked in DMs
+    pub guild_only_handler: Option<fn(crate::Context<'_, U, E>) -> BoxFuture<'_, Result<(), E>>>,
+    /// Called when a command with [`crate::CommandId::dm_only`] is invoked in a guild
+    pub dm_only_handler: Option<fn(crate::Context<'_, U, E>) -> BoxFuture<'_, Result<(), E>>>,
+    /// Decides whether an invocation bypasses command cooldowns, for example for supporters or
+    /// server boosters. Called before the cooldowns are checked.
+    ///
+    /// If this returns true, the cooldowns are neither checked nor started for the invocation.
+    /// That means an exempt invocation doesn't reset the cooldown timers either, so users without
+    /// exemption aren't affected by it, even in shared cooldown buckets like the global or
+    /// per-channel one.
+    ///
+    /// This is called once per command while checking the cooldowns (see
+    /// [`crate::CheckKind::Cooldown`]), and the result decides about starting the cooldown as well.
+    pub cooldown_exempt: Option<fn(crate::Context<'_, U, E>) -> BoxFuture<'_, bool>>,
     /// Called when a command is invoked before its cooldown has expired
     pub cooldown_hit:
         Option<fn(crate::Context<'_, U, E>, std::time::Duration) -> BoxFuture<'_, Result<(), E>>>,
+    /// Called when an invocation is rejected because too many invocations of the command are
+    /// already running (see [`crate::CommandId::concurrency`]). The rejected invocation doesn't
+    /// start the command's cooldown
+    pub max_concurrency_reached: Option<
+        fn(crate::Context<'_, U, E>, crate::MaxConcurrencyReached) -> BoxFuture<'_, Result<(), E>>,
+    >,
+    /// Called when a command is invoked after [`crate::Framework::shutdown`] was called. The
+    /// rejected invocation doesn't start the command's cooldown
+    pub shutting_down_handler: Option<fn(crate::Context<'_, U, E>) -> BoxFuture<'_, Result<(), E>>>,
+    /// The order in which the pre-command checks are evaluated. The first failing check stops the
+    /// evaluation, so only its handler (for example [`Self::not_an_owner_handler`] or
+    /// [`Self::cooldown_hit`]) is called.
+    ///
+    /// Checks missing from this list are evaluated afterwards, in the order of
+    /// [`crate::CheckKind::DEFAULT_ORDER`]. Duplicate entries are ignored.
+    ///
+    /// Defaults to [`crate::CheckKind::DEFAULT_ORDER`].
+    pub check_order: &'static [crate::CheckKind],
     /// Called if the bot is lacking any of the permissions specified in
     /// [`crate::CommandId::required_bot_permissions`]. The list of missing permissions is given as
     /// an argument.
     pub missing_bot_permissions_handler:
         fn(crate::Context<'_, U, E>, serenity::Permissions) -> BoxFuture<'_, Result<(), E>>,
-    /// Default set of allowed mentions to use for all responses
+    /// Default set of allowed mentions to use for all responses. Can be overridden per response
+    /// with [`crate::CreateReply::allowed_mentions`].
+    ///
+    /// By default, only user pings and pings of the replied-to user are allowed. In particular,
+    /// `@everyone`, `@here` and role mentions in echoed user input don't ping anyone.
     pub allowed_mentions: Option<serenity::CreateAllowedMentions>,
+    /// If Some, responses that fail because of Discord rate limits are retried. Defaults to None,
+    /// i.e. no retries.
+    pub response_retry: Option<crate::RetryConfig>,
+    /// How long component handlers registered via [`crate::Context::register_component`] stay
+    /// active. Defaults to 15 minutes.
+    pub component_timeout: std::time::Duration,
     /// Called on every Discord event. Can be used to react to non-command events, like messages
     /// deletions or guild updates.
     pub listener: for<'a> fn(
@@ -139,10 +253,39 @@ pub struct FrameworkOptions<U, E> {
     /// Prefix command specific options.
     pub prefix_options: crate::PrefixFrameworkOptions<U, E>,
     /// User IDs which are allowed to use owners_only commands
+    ///
+    /// [`crate::FrameworkBuilder`] adds the application owner to this set on startup. If the set
+    /// was left empty, the members of the application's team are added as well.
     pub owners: std::collections::HashSet<serenity::UserId>,
 }
 
 impl<U, E> FrameworkOptions<U, E> {
+    /// Sets [`Self::on_error`] to the given closure, which may capture state.
+    ///
+    /// ```rust
+    /// # type Error = Box<dyn std::error::Error + Send + Sync>;
+    /// use std::sync::{atomic::AtomicUsize, atomic::Ordering, Arc};
+    ///
+    /// let error_count = Arc::new(AtomicUsize::new(0));
+    /// let mut options = poise::FrameworkOptions::<(), Error>::default();
+    /// options.set_on_error(move |error, _ctx| {
+    ///     let error_count = Arc::clone(&error_count);
+    ///     Box::pin(async move {
+    ///         error_count.fetch_add(1, Ordering::SeqCst);
+    ///         println!("Encountered an error: {}", error);
+    ///     })
+    /// });
+    /// ```
+    pub fn set_on_error<F>(&mut self, on_error: F)
+    where
+        F: for<'a> Fn(E, crate::ErrorContext<'a, U, E>) -> BoxFuture<'a, ()>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.on_error = std::sync::Arc::new(on_error);
+    }
+
     /// Add a command definition, which can include a prefix implementation, slash implementation,
     /// and context menu implementation, to the framework.
     ///
@@ -192,6 +335,23 @@ where
     U: Send + Sync,
     E: std::fmt::Display + Send,
 {
+    let command_ctx = match &ctx {
+        crate::ErrorContext::Command(err_ctx) => Some(err_ctx.ctx()),
+        _ => None,
+    };
+    if let Some(ctx) = command_ctx {
+        match ctx.framework().options().default_error_handler {
+            DefaultErrorHandler::Log => {}
+            DefaultErrorHandler::ReplyToUser { template } => {
+                if let Err(e) = ctx.say(template(&error)).await {
+                    println!("Failed to reply with error message: {}", e);
+                }
+                return;
+            }
+            DefaultErrorHandler::Silent => return,
+        }
+    }
+
     match ctx {
         crate::ErrorContext::Setup => println!("Error in user data setup: {}", error),
         crate::ErrorContext::Listener(event) => println!(
@@ -215,6 +375,14 @@ where
                 }
             }
         }
+        crate::ErrorContext::InvokeOnMention(ctx) => println!(
+            "Error in mention callback from message \"{}\": {}",
+            ctx.msg.content, error
+        ),
+        crate::ErrorContext::NonCommandMessage(ctx) => println!(
+            "Error in non-command message callback from message \"{}\": {}",
+            ctx.msg.content, error
+        ),
         crate::ErrorContext::Autocomplete(err_ctx) => match &err_ctx.ctx.command {
             crate::ApplicationCommand::Slash(cmd) => {
                 println!("Error in slash command \"{}\": {}", cmd.name, error)
@@ -223,17 +391,39 @@ where
                 println!("Error in context menu command \"{}\": {}", cmd.name, error)
             }
         },
+        crate::ErrorContext::Component(ctx) => println!(
+            "Error in component handler for \"{}\": {}",
+            ctx.interaction.data.custom_id, error
+        ),
     }
 }
 
-impl<U: Send + Sync, E: std::fmt::Display + Send> Default for FrameworkOptions<U, E> {
+impl<U: Send + Sync + 'static, E: std::fmt::Display + Send + 'static> Default
+    for FrameworkOptions<U, E>
+{
     fn default() -> Self {
+        let on_error: fn(E, crate::ErrorContext<'_, U, E>) -> BoxFuture<'_, ()> =
+            |error, ctx| Box::pin(default_error_handler(error, ctx));
+
         Self {
-            on_error: |error, ctx| Box::pin(default_error_handler(error, ctx)),
+            on_error: std::sync::Arc::new(on_error),
+            default_error_handler: DefaultErrorHandler::Log,
             listener: |_, _, _, _| Box::pin(async { Ok(()) }),
             pre_command: |_| Box::pin(async {}),
-            post_command: |_| Box::pin(async {}),
+            post_command: |_, _| Box::pin(async {}),
             command_check: None,
+            on_global_check_failure: None,
+            on_check_failure: Some(|ctx, reason| {
+                Box::pin(async move {
+                    let msg = reason
+                        .unwrap_or_else(|| "You are not allowed to use this command".to_owned());
+                    let _: Result<_, _> = ctx.send(|b| b.content(msg).ephemeral(true)).await;
+
+                    Ok(())
+                })
+            }),
+            cooldown_exempt: None,
+            check_order: crate::CheckKind::DEFAULT_ORDER,
             cooldown_hit: Some(|ctx, cooldown_left| {
                 Box::pin(async move {
                     let msg = format!(
@@ -245,6 +435,63 @@ impl<U: Send + Sync, E: std::fmt::Display + Send> Default for FrameworkOptions<U
                     Ok(())
                 })
             }),
+            max_concurrency_reached: Some(|ctx, max_concurrency_reached| {
+                Box::pin(async move {
+                    let msg = max_concurrency_reached.to_string();
+                    let _: Result<_, _> = ctx.send(|b| b.content(msg).ephemeral(true)).await;
+
+                    Ok(())
+                })
+            }),
+            shutting_down_handler: Some(|ctx| {
+                Box::pin(async move {
+                    let _: Result<_, _> = ctx
+                        .send(|b| {
+                            b.content("The bot is shutting down. Please try again later")
+                                .ephemeral(true)
+                        })
+                        .await;
+
+                    Ok(())
+                })
+            }),
+            not_an_owner_handler: Some(|ctx| {
+                Box::pin(async move {
+                    let _: Result<_, _> = ctx
+                        .send(|b| {
+                            b.content("Only bot owners can use this command")
+                                .ephemeral(true)
+                        })
+                        .await;
+
+                    Ok(())
+                })
+            }),
+            missing_user_permissions_handler: None,
+            guild_only_handler: Some(|ctx| {
+                Box::pin(async move {
+                    let _: Result<_, _> = ctx
+                        .send(|b| {
+                            b.content("This command can only be used in servers")
+                                .ephemeral(true)
+                        })
+                        .await;
+
+                    Ok(())
+                })
+            }),
+            dm_only_handler: Some(|ctx| {
+                Box::pin(async move {
+                    let _: Result<_, _> = ctx
+                        .send(|b| {
+                            b.content("This command can only be used in DMs")
+                                .ephemeral(true)
+                        })
+                        .await;
+
+                    Ok(())
+                })
+            }),
             missing_bot_permissions_handler: |ctx, missing_permissions| {
                 Box::pin(async move {
                     let msg = format!(
@@ -259,9 +506,13 @@ impl<U: Send + Sync, E: std::fmt::Display + Send> Default for FrameworkOptions<U
             allowed_mentions: Some({
                 let mut f = serenity::CreateAllowedMentions::default();
                 // Only support direct user pings by default
-                f.empty_parse().parse(serenity::ParseValue::Users);
+                f.empty_parse()
+                    .parse(serenity::ParseValue::Users)
+                    .replied_user(true);
                 f
             }),
+            response_retry: None,
+            component_timeout: std::time::Duration::from_secs(15 * 60),
             application_options: Default::default(),
             prefix_options: Default::default(),
             owners: Default::default(),
@@ -117,6 +117,12 @@ pub enum ErrorContext<'a, U, E> {
     Command(CommandErrorContext<'a, U, E>),
     /// Error in autocomplete callback
     Autocomplete(crate::ApplicationCommandErrorContext<'a, U, E>),
+    /// Error in [`crate::PrefixFrameworkOptions::invoke_on_mention`]
+    InvokeOnMention(crate::PrefixContext<'a, U, E>),
+    /// Error in [`crate::PrefixFrameworkOptions::non_command_message`]
+    NonCommandMessage(crate::PrefixContext<'a, U, E>),
+    /// Error in a component handler registered via [`crate::Context::register_component`]
+    Component(crate::ComponentContext<'a, U, E>),
 }
 
 impl<U, E> Clone for ErrorContext<'_, U, E> {
@@ -126,6 +132,9 @@ impl<U, E> Clone for ErrorContext<'_, U, E> {
             Self::Listener(x) => Self::Listener(x),
             Self::Command(x) => Self::Command(x.clone()),
             Self::Autocomplete(x) => Self::Autocomplete(x.clone()),
+            Self::InvokeOnMention(x) => Self::InvokeOnMention(*x),
+            Self::NonCommandMessage(x) => Self::NonCommandMessage(*x),
+            Self::Component(x) => Self::Component(*x),
         }
     }
 }
@@ -167,13 +176,30 @@ pub struct CommandId {
     pub identifying_name: String,
     /// Identifier for the category that this command will be displayed in for help commands.
     pub category: Option<&'static str>,
-    /// Whether to hide this command in help menus.
+    /// Whether to hide this command in help menus. The command can still be invoked.
+    ///
+    /// [`crate::builtins::help`] still lists hidden commands to
+    /// [owners](crate::FrameworkOptions::owners).
     pub hide_in_help: bool,
     /// Short description of the command. Displayed inline in help menus and similar.
     pub inline_help: Option<&'static str>,
+    /// Returns the short description of the command in the given locale, like `de` or `en-US`, or
+    /// None to fall back to [`Self::inline_help`]. See [`crate::Context::locale`] for where the
+    /// locale comes from.
+    pub description_localized: Option<fn(&str) -> Option<String>>,
     /// Handles command cooldowns. Mainly for framework internal use
     pub cooldowns: std::sync::Mutex<crate::Cooldowns>,
-    /// Permissions which users must have to invoke this command.
+    /// Limits how many invocations of this command may run at the same time. Mainly for framework
+    /// internal use
+    ///
+    /// The limits are enforced after all checks passed. The cooldown is only started once a slot
+    /// was acquired, so invocations over the limit don't put the user on cooldown. They are
+    /// rejected and passed to [`crate::FrameworkOptions::max_concurrency_reached`].
+    pub concurrency: std::sync::Mutex<crate::Concurrency>,
+    /// Permissions which users must have to invoke this command. They are checked against the
+    /// invoking member's effective permissions in the channel.
+    ///
+    /// In DMs, this check is skipped unless [`Self::guild_only`] is set.
     ///
     /// Set to [`serenity::Permissions::empty()`] by default
     pub required_permissions: serenity::Permissions,
@@ -186,20 +212,142 @@ pub struct CommandId {
     /// If true, only users from the [owners list](crate::FrameworkOptions::owners) may use this
     /// command.
     pub owners_only: bool,
+    /// If true, this command can't be used in DMs. Invocations in DMs are passed to
+    /// [`crate::FrameworkOptions::guild_only_handler`] instead.
+    ///
+    /// Mutually exclusive with [`Self::dm_only`].
+    pub guild_only: bool,
+    /// If true, this command can only be used in DMs. Invocations in guilds are passed to
+    /// [`crate::FrameworkOptions::dm_only_handler`] instead.
+    ///
+    /// Mutually exclusive with [`Self::guild_only`].
+    pub dm_only: bool,
+}
+
+impl CommandId {
+    /// Returns the short description of the command in the given locale via
+    /// [`Self::description_localized`], falling back to [`Self::inline_help`] if the locale is
+    /// unknown or has no translation.
+    pub fn localized_inline_help(&self, locale: Option<&str>) -> Option<String> {
+        let localized = match (self.description_localized, locale) {
+            (Some(description_localized), Some(locale)) => description_localized(locale),
+            _ => None,
+        };
+        localized.or_else(|| self.inline_help.map(String::from))
+    }
+}
+
+/// Outcome of a command invocation, passed to [`crate::FrameworkOptions::post_command`]
+pub enum CommandOutcome<'a, E> {
+    /// The command completed successfully
+    Success,
+    /// The command returned an error. The error is passed to the error handler afterwards
+    Error(&'a E),
+    /// The command panicked. The panic is resumed after the post command hook has run
+    Panic,
+    /// The command exceeded its timeout and was cancelled (see
+    /// [`crate::PrefixCommandOptions::timeout`])
+    Timeout,
+    /// A check (see [`crate::CheckKind`]) or [`crate::PrefixFrameworkOptions::command_enabled`]
+    /// rejected the invocation, so the command didn't run
+    Rejected,
+}
+// manual Copy+Clone implementations because Rust is getting confused about the type parameter
+impl<E> Clone for CommandOutcome<'_, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<E> Copy for CommandOutcome<'_, E> {}
+
+/// Returned from a command's `check_with_reason` callback to decide whether the command may run
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CheckResult {
+    /// The command may run
+    Pass,
+    /// The command will not run
+    Reject {
+        /// Explanation why the command was rejected, passed to
+        /// [`crate::FrameworkOptions::on_check_failure`]
+        reason: Option<String>,
+        /// If true, the rejection is silent and [`crate::FrameworkOptions::on_check_failure`] is
+        /// not called
+        silent: bool,
+    },
+}
+
+/// A group of pre-command checks, used to configure their evaluation order with
+/// [`crate::FrameworkOptions::check_order`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CheckKind {
+    /// [`CommandId::guild_only`] and [`CommandId::dm_only`]
+    GuildOnly,
+    /// [`CommandId::owners_only`]
+    OwnerOnly,
+    /// [`CommandId::required_permissions`], then [`CommandId::required_bot_permissions`]
+    Permissions,
+    /// The command [cooldowns](CommandId::cooldowns). The cooldown is only started once all checks
+    /// passed.
+    ///
+    /// For prefix subcommands, the cooldowns of the parent commands are checked as well, like all
+    /// other checks, but only the cooldown of the invoked command is started.
+    Cooldown,
+    /// [`crate::FrameworkOptions::command_check`], then the command's `check` and
+    /// `check_with_reason`.
+    ///
+    /// For prefix subcommands, the global `command_check` only runs once, along with the checks
+    /// of the top-level command.
+    CustomCheck,
+}
+
+impl CheckKind {
+    /// The default value of [`crate::FrameworkOptions::check_order`]
+    pub const DEFAULT_ORDER: &'static [CheckKind] = &[
+        CheckKind::GuildOnly,
+        CheckKind::OwnerOnly,
+        CheckKind::Permissions,
+        CheckKind::Cooldown,
+        CheckKind::CustomCheck,
+    ];
 }
 
 /// Used for command errors to store the specific operation in a command's execution where an
 /// error occured
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub enum CommandErrorLocation {
     /// Error occured in the main command code
     Body,
     /// Error occured in one of the pre-command checks
     Check,
+    /// Error occured while parsing the arguments of a prefix command. The error is an
+    /// [`crate::ArgumentParseError`]
+    ArgumentParse,
     /// Error occured in a parameter autocomplete callback
     Autocomplete,
     /// Error occured in [`crate::FrameworkOptions::cooldown_hit`]
     CooldownCallback,
+    /// Error occured in [`crate::FrameworkOptions::max_concurrency_reached`]
+    MaxConcurrencyCallback,
+    /// Error occured in [`crate::FrameworkOptions::shutting_down_handler`]
+    ShuttingDownCallback,
     /// Error occured in [`crate::FrameworkOptions::missing_bot_permissions_handler`]
     MissingBotPermissionsCallback,
+    /// Error occured in [`crate::FrameworkOptions::not_an_owner_handler`]
+    NotAnOwnerCallback,
+    /// Error occured in [`crate::FrameworkOptions::missing_user_permissions_handler`]
+    MissingUserPermissionsCallback,
+    /// Error occured in [`crate::FrameworkOptions::guild_only_handler`]
+    GuildOnlyCallback,
+    /// Error occured in [`crate::FrameworkOptions::dm_only_handler`]
+    DmOnlyCallback,
+    /// Error occured in [`crate::PrefixFrameworkOptions::subcommand_required_handler`]
+    SubcommandRequiredCallback,
+    /// Error occured in [`crate::PrefixFrameworkOptions::command_disabled_handler`]
+    CommandDisabledCallback,
+    /// Error occured in [`crate::PrefixFrameworkOptions::on_command_timeout`]
+    TimeoutCallback,
+    /// Error occured in [`crate::FrameworkOptions::on_check_failure`]
+    CheckFailureCallback,
+    /// Error occured in [`crate::FrameworkOptions::on_global_check_failure`]
+    GlobalCheckFailureCallback,
 }
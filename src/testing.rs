@@ -0,0 +1,376 @@
+//! Utilities to invoke prefix commands in tests, without a connection to Discord. Requires the
+//! `testing` feature.
+//!
+//! ```rust,no_run
+//! # type Error = Box<dyn std::error::Error + Send + Sync>;
+//! # #[poise::command(prefix_command)]
+//! # async fn ping(ctx: poise::Context<'_, (), Error>) -> Result<(), Error> {
+//! #     ctx.say("Pong!").await?;
+//! #     Ok(())
+//! # }
+//! # #[tokio::main] async fn main() {
+//! use poise::testing::{CapturedResponse, MockContext};
+//!
+//! let mock = MockContext::new(poise::FrameworkOptions::default(), ());
+//! mock.invoke(&ping().prefix.unwrap(), "").await.unwrap();
+//! assert!(matches!(
+//!     &mock.responses()[..],
+//!     [CapturedResponse::Message { content: Some(content), .. }] if content == "Pong!"
+//! ));
+//! # }
+//! ```
+
+use crate::serenity_prelude as serenity;
+
+/// A response that a command tried to send to Discord, captured by [`MockContext`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum CapturedResponse {
+    /// A message sent via [`crate::send_reply`], [`crate::say_reply`], [`crate::send_reply_in`]
+    /// or functions built on them like [`crate::Context::say`]. Edits of edit tracked responses
+    /// are captured as new messages.
+    Message {
+        /// The channel the message was sent to
+        channel_id: serenity::ChannelId,
+        /// The text content of the message, if any. Embeds, components and attachments are not
+        /// captured
+        content: Option<String>,
+    },
+    /// A message sent to a user via DM, for example by [`crate::say_private_reply`]
+    DirectMessage {
+        /// The user the message was sent to
+        user_id: serenity::UserId,
+        /// The text content of the message, if any
+        content: Option<String>,
+    },
+    /// A reaction added via [`crate::add_reactions`] or [`crate::say_private_reply`]
+    Reaction {
+        /// The message that was reacted to
+        message_id: serenity::MessageId,
+        /// The reaction that was added
+        reaction: serenity::ReactionType,
+    },
+    /// A deletion of a response, for example of a [temporary response](crate::say_temp_reply)
+    /// once its duration elapsed
+    Deletion {
+        /// The message that was deleted
+        message_id: serenity::MessageId,
+    },
+}
+
+/// Key of the captured responses in the mocked [`serenity::Context::data`]. Its presence tells the
+/// response functions to capture responses instead of sending them
+struct CapturedResponses;
+
+impl serenity::TypeMapKey for CapturedResponses {
+    type Value = std::sync::Arc<std::sync::Mutex<Vec<CapturedResponse>>>;
+}
+
+/// Stores the response if the context belongs to a [`MockContext`]. Returns whether it did, in
+/// which case the response must not be sent to Discord. Called by [`crate::reply::send_response`]
+pub(crate) async fn capture(
+    discord: &serenity::Context,
+    response: crate::reply::Outgoing<'_>,
+) -> bool {
+    let data = discord.data.read().await;
+    let responses = match data.get::<CapturedResponses>() {
+        Some(x) => x,
+        None => return false,
+    };
+
+    let response = match response {
+        crate::reply::Outgoing::Message {
+            channel_id,
+            content,
+        } => CapturedResponse::Message {
+            channel_id,
+            content: content.map(String::from),
+        },
+        crate::reply::Outgoing::DirectMessage { user_id, content } => {
+            CapturedResponse::DirectMessage {
+                user_id,
+                content: content.map(String::from),
+            }
+        }
+        crate::reply::Outgoing::Reaction {
+            message_id,
+            reaction,
+        } => CapturedResponse::Reaction {
+            message_id,
+            reaction: reaction.clone(),
+        },
+        crate::reply::Outgoing::Deletion { message_id } => {
+            CapturedResponse::Deletion { message_id }
+        }
+    };
+    responses.lock().unwrap().push(response);
+    true
+}
+
+/// Invokes prefix commands over a fake message and captures the responses they try to send,
+/// instead of talking to Discord. Useful to unit test argument parsing and command logic.
+///
+/// Only responses sent via poise's response functions are captured (see [`CapturedResponse`]).
+/// Calling serenity's HTTP methods directly, for example `ctx.msg.react()`, fails because there is
+/// no Discord connection.
+///
+/// [`crate::Framework::http`] and [`crate::Framework::cache`] return the same mocked HTTP client
+/// and cache as [`serenity::Context`]. So commands may use them, but HTTP requests fail as well.
+pub struct MockContext<U, E> {
+    framework: crate::Framework<U, E>,
+    discord: serenity::Context,
+    message: serenity::Message,
+    prefix: String,
+    responses: std::sync::Arc<std::sync::Mutex<Vec<CapturedResponse>>>,
+}
+
+impl<U, E> MockContext<U, E> {
+    /// Creates a mock context with the given framework options and user data. The invoking
+    /// message is sent by user 1 in channel 1, outside of any guild, with the prefix `~`.
+    pub fn new(options: crate::FrameworkOptions<U, E>, data: U) -> Self {
+        let responses = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut type_map = serenity::TypeMap::new();
+        type_map.insert::<CapturedResponses>(responses.clone());
+
+        // Nothing is ever sent through the shard in tests, so the receiving end is dropped
+        let (shard_tx, _) = futures::channel::mpsc::unbounded();
+        let discord = serenity::Context {
+            data: std::sync::Arc::new(tokio::sync::RwLock::new(type_map)),
+            shard: serenity::ShardMessenger::new(shard_tx),
+            shard_id: 0,
+            http: std::sync::Arc::new(serenity::Http::new_with_token("")),
+            cache: std::sync::Arc::new(serenity::Cache::new()),
+        };
+
+        let mut message = serenity::CustomMessage::new().build();
+        message.author.id = serenity::UserId(1);
+        message.channel_id = serenity::ChannelId(1);
+
+        let framework = crate::Framework::new_offline(
+            options,
+            data,
+            discord.http.clone(),
+            discord.cache.clone(),
+        );
+        Self {
+            framework,
+            discord,
+            message,
+            prefix: "~".into(),
+            responses,
+        }
+    }
+
+    /// Sets the prefix which the invoking message starts with
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Sets the author of the invoking message
+    pub fn author(mut self, author: serenity::User) -> Self {
+        self.message.author = author;
+        self
+    }
+
+    /// Sets the channel of the invoking message
+    pub fn channel_id(mut self, channel_id: serenity::ChannelId) -> Self {
+        self.message.channel_id = channel_id;
+        self
+    }
+
+    /// Sets the guild of the invoking message. None means the message was sent in DMs
+    pub fn guild_id(mut self, guild_id: Option<serenity::GuildId>) -> Self {
+        self.message.guild_id = guild_id;
+        self
+    }
+
+    /// Runs the command with the given arguments, as if the message `{prefix}{name} {args}` was
+    /// sent. Argument parsing and the command body are run; checks, cooldowns and other dispatch
+    /// logic are not.
+    pub async fn invoke(
+        &self,
+        command: &crate::PrefixCommand<U, E>,
+        args: &str,
+    ) -> Result<(), (E, crate::CommandErrorLocation)> {
+        let mut message = self.message.clone();
+        message.content = format!("{}{} {}", self.prefix, command.name, args);
+
+        let ctx = crate::PrefixContext {
+            discord: &self.discord,
+            msg: &message,
+            prefix: &self.prefix,
+            args_offset: message.content.len() - args.len(),
+            framework: &self.framework,
+            data: self.framework.get_user_data().await,
+            command: Some(command),
+            invoked_command_path: std::slice::from_ref(&command.name),
+            invoked_by_edit: false,
+        };
+        (command.action)(ctx, args).await
+    }
+
+    /// Returns the responses captured so far, in the order they were sent
+    pub fn responses(&self) -> Vec<CapturedResponse> {
+        self.responses.lock().unwrap().clone()
+    }
+
+    /// Forgets the responses captured so far, for example between multiple invocations
+    pub fn clear_responses(&self) {
+        self.responses.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    async fn greet_action(
+        ctx: crate::PrefixContext<'_, (), serenity::Error>,
+        args: &str,
+    ) -> Result<(), (serenity::Error, crate::CommandErrorLocation)> {
+        let reactions = [serenity::ReactionType::Unicode("👋".into())];
+        crate::say_reply(ctx.into(), format!("Hello, {}!", args))
+            .await
+            .map_err(|e| (e, crate::CommandErrorLocation::Body))?;
+        assert!(crate::add_reactions(ctx.into(), ctx.msg, &reactions)
+            .await
+            .is_success());
+        Ok(())
+    }
+
+    fn greet() -> crate::PrefixCommand<(), serenity::Error> {
+        crate::PrefixCommand {
+            name: "greet",
+            action: |ctx, args| Box::pin(greet_action(ctx, args)),
+            id: std::sync::Arc::new(crate::CommandId {
+                identifying_name: String::from("greet"),
+                category: None,
+                hide_in_help: false,
+                inline_help: None,
+                description_localized: None,
+                cooldowns: std::sync::Mutex::new(crate::Cooldowns::new(Default::default())),
+                concurrency: std::sync::Mutex::new(crate::Concurrency::new(Default::default())),
+                required_permissions: serenity::Permissions::empty(),
+                required_bot_permissions: serenity::Permissions::empty(),
+                owners_only: false,
+                guild_only: false,
+                dm_only: false,
+            }),
+            parameters: &[],
+            options: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_capture_responses() {
+        let mock = MockContext::new(crate::FrameworkOptions::default(), ())
+            .channel_id(serenity::ChannelId(5));
+        assert!(mock.invoke(&greet(), "world").await.is_ok());
+        assert_eq!(
+            mock.responses(),
+            vec![
+                CapturedResponse::Message {
+                    channel_id: serenity::ChannelId(5),
+                    content: Some("Hello, world!".into()),
+                },
+                CapturedResponse::Reaction {
+                    message_id: mock.message.id,
+                    reaction: serenity::ReactionType::Unicode("👋".into()),
+                },
+            ]
+        );
+
+        mock.clear_responses();
+        assert_eq!(mock.responses(), vec![]);
+    }
+
+    async fn whisper_action(
+        ctx: crate::PrefixContext<'_, (), serenity::Error>,
+        args: &str,
+    ) -> Result<(), (serenity::Error, crate::CommandErrorLocation)> {
+        let duration = std::time::Duration::from_millis(1);
+        crate::say_private_reply(ctx.into(), args)
+            .await
+            .map_err(|e| (e, crate::CommandErrorLocation::Body))?;
+        crate::say_temp_reply(ctx.into(), "Check your DMs", duration)
+            .await
+            .map_err(|e| (e, crate::CommandErrorLocation::Body))?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_capture_private_and_temporary_responses() {
+        let mock = MockContext::new(crate::FrameworkOptions::default(), ())
+            .guild_id(Some(serenity::GuildId(1)));
+        let whisper = crate::PrefixCommand {
+            name: "whisper",
+            action: |ctx, args| Box::pin(whisper_action(ctx, args)),
+            ..greet()
+        };
+        assert!(mock.invoke(&whisper, "psst").await.is_ok());
+        // Lets the temporary response's deletion run
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        let placeholder_id =
+            crate::reply::placeholder_message(&mock.discord, mock.message.channel_id, None).id;
+        assert_eq!(
+            mock.responses(),
+            vec![
+                CapturedResponse::DirectMessage {
+                    user_id: mock.message.author.id,
+                    content: Some("psst".into()),
+                },
+                CapturedResponse::Reaction {
+                    message_id: mock.message.id,
+                    reaction: serenity::ReactionType::Unicode("✅".into()),
+                },
+                CapturedResponse::Message {
+                    channel_id: mock.message.channel_id,
+                    content: Some("Check your DMs".into()),
+                },
+                CapturedResponse::Deletion {
+                    message_id: placeholder_id,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_runtime_prefix_commands() {
+        let mock = MockContext::new(crate::FrameworkOptions::default(), ());
+        let greet_meta = || crate::PrefixCommandMeta {
+            command: greet(),
+            subcommands: Vec::new(),
+        };
+        assert!(mock.framework.add_prefix_command(greet_meta()).is_ok());
+        assert!(mock.framework.add_prefix_command(greet_meta()).is_err());
+
+        let runtime_commands = mock.framework.runtime_prefix_commands();
+        assert!(mock
+            .framework
+            .find_prefix_command(&runtime_commands, &["greet"])
+            .is_some());
+        let paths = mock
+            .framework
+            .prefix_commands_iter(&runtime_commands)
+            .map(|(path, _)| path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec![vec!["greet"]]);
+    }
+
+    #[test]
+    fn test_offline_framework_http_and_cache() {
+        let mock = MockContext::new(
+            crate::FrameworkOptions::<(), serenity::Error>::default(),
+            (),
+        );
+        assert!(std::sync::Arc::ptr_eq(
+            &mock.framework.http(),
+            &mock.discord.http
+        ));
+        assert!(std::sync::Arc::ptr_eq(
+            &mock.framework.cache(),
+            &mock.discord.cache
+        ));
+    }
+}
@@ -22,6 +22,10 @@ impl<K: Eq, V> OrderedMap<K, V> {
         }
     }
 
+    pub fn retain(&mut self, mut f: impl FnMut(&K, &V) -> bool) {
+        self.0.retain(|(k, v)| f(k, v));
+    }
+
     pub fn get_or_insert_with(&mut self, k: K, v: impl FnOnce() -> V) -> &mut V {
         match self.0.iter().position(|entry| entry.0 == k) {
             Some(i) => &mut self.0[i].1,
@@ -40,3 +44,42 @@ impl<K, V> IntoIterator for OrderedMap<K, V> {
         self.0.into_iter()
     }
 }
+
+/// Number of single-character insertions, deletions or substitutions needed to turn `a` into `b`
+pub fn levenshtein_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    // Distances from the current prefix of `a` to every prefix of `b`
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &b_char) in b.iter().enumerate() {
+            let substitution = diagonal + if a_char == b_char { 0 } else { 1 };
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Returns a `'static` copy of the given command path, for
+/// [`crate::PrefixContext::invoked_command_path`] and
+/// [`crate::ApplicationContext::invoked_command_path`].
+///
+/// Each distinct path is allocated once and kept for the rest of the program, so the memory use is
+/// bounded by the number of commands
+pub fn intern_command_path(path: &[&'static str]) -> &'static [&'static str] {
+    static PATHS: once_cell::sync::Lazy<
+        std::sync::Mutex<std::collections::HashSet<&'static [&'static str]>>,
+    > = once_cell::sync::Lazy::new(Default::default);
+
+    let mut paths = PATHS.lock().unwrap();
+    match paths.get(path) {
+        Some(interned) => *interned,
+        None => {
+            let interned: &'static [&'static str] = Box::leak(path.into());
+            paths.insert(interned);
+            interned
+        }
+    }
+}